@@ -0,0 +1,291 @@
+//! Small, deterministic cleanups of the html returned by the API.
+
+/// Contents of bracketed markers that are removed from text, besides
+/// numbers and single letters as in `[1]` or `[a]`.
+const BRACKET_MARKERS: &[&str] = &[
+    "citation needed",
+    "clarification needed",
+    "better source needed",
+    "verification needed",
+    "failed verification",
+    "according to whom?",
+    "by whom?",
+    "when?",
+    "who?",
+    "which?",
+    "dubious – discuss",
+    "original research?",
+];
+
+/// Words in a parenthetical that mark it as a pronunciation.
+const PRONUNCIATION_MARKERS: &[&str] = &["ipa", "pronounced", "pronunciation", "listen"];
+
+/// Hosts of web archives, whose links are archived copies of a source.
+const ARCHIVE_HOSTS: &[&str] = &[
+    "web.archive.org",
+    "archive.today",
+    "archive.ph",
+    "archive.is",
+    "www.webcitation.org",
+];
+
+/// Removes `<tag ...>...</tag>` elements, contents included.
+fn remove_elements(html: &str, tag: &str) -> String {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find(&*open) {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find(&*close) {
+            Some(end) => &rest[start + end + close.len()..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Removes all tags, keeping their text.
+fn strip_tags(html: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Decodes the named entities MediaWiki emits and numeric ones.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(e) if e <= 10 => e,
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|x| u32::from_str_radix(x, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|x| x.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_marker(content: &str) -> bool {
+    let lower = content.trim().to_lowercase();
+    (!lower.is_empty() && lower.chars().all(|c| c.is_ascii_digit()))
+        || (lower.chars().count() == 1 && lower.chars().all(|c| c.is_alphabetic()))
+        || lower.starts_with("note ")
+        || lower.starts_with("nb ")
+        || BRACKET_MARKERS.contains(&&*lower)
+}
+
+/// Removes reference markers like `[1]`, `[a]` or `[citation needed]`.
+fn remove_markers(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        match rest[start..].find(']') {
+            Some(end) if is_marker(&rest[start + 1..start + end]) => {
+                rest = &rest[start + end + 1..];
+            }
+            _ => {
+                out.push('[');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_pronunciation(content: &str) -> bool {
+    let lower = content.to_lowercase();
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|x| PRONUNCIATION_MARKERS.contains(&x))
+        || (content.contains('/') && content.contains(['ˈ', 'ˌ', 'ː']))
+}
+
+/// Removes parenthesized pronunciations like `(/ˈæl.bərt/; German: [ˈalbɛʁt])`,
+/// nested parentheses included.
+fn remove_pronunciations(text: &str) -> String {
+    let mut out = String::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '(' {
+            let mut depth = 0;
+            let mut end = None;
+            for (j, &c) in chars.iter().enumerate().skip(i) {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(j);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(end) = end {
+                let content: String = chars[i + 1..end].iter().collect();
+                if is_pronunciation(&content) {
+                    i = end + 1;
+                    continue;
+                }
+            }
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Collapses runs of whitespace into a single space and removes spaces
+/// left before punctuation.
+fn collapse_whitespace(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = String::new();
+    for c in collapsed.chars() {
+        if matches!(c, ',' | '.' | ';' | ':' | ')') && out.ends_with(' ') {
+            out.pop();
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Cleans up the html of a paragraph to plain text.
+pub fn clean_paragraph(html: &str) -> String {
+    let html = remove_elements(html, "sup");
+    let html = remove_elements(&html, "style");
+    let text = decode_entities(&strip_tags(&html));
+    collapse_whitespace(&remove_pronunciations(&remove_markers(&text)))
+}
+
+/// Returns the text of the first non-empty `<p>` in `html`.
+pub fn first_paragraph(html: &str) -> Option<String> {
+    let mut rest = html;
+    while let Some(start) = rest.find("<p") {
+        let after = &rest[start + 2..];
+        if !after.starts_with(['>', ' ']) {
+            rest = after;
+            continue;
+        }
+        let open_end = after.find('>')? + 1;
+        let body = &after[open_end..];
+        let end = body.find("</p>").unwrap_or(body.len());
+        let text = clean_paragraph(&body[..end]);
+        if !text.is_empty() {
+            return Some(text);
+        }
+        rest = &body[end..];
+    }
+    None
+}
+
+/// Cleans up html to plain text, keeping bracketed and parenthesized text.
+pub fn text(html: &str) -> String {
+    let html = remove_elements(html, "style");
+    collapse_whitespace(&decode_entities(&strip_tags(&html)))
+}
+
+/// Returns the value of the attribute `name` in the opening tag `tag`.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&*format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')?;
+    Some(decode_entities(&tag[start..start + end]))
+}
+
+/// Returns the targets and texts of the external links in `html`. Protocol
+/// relative links get the `https:` scheme.
+pub fn external_links(html: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<a ") {
+        rest = &rest[start..];
+        let open_end = match rest.find('>') {
+            Some(x) => x,
+            None => break,
+        };
+        let href = attribute(&rest[..open_end], "href");
+        rest = &rest[open_end + 1..];
+        let end = rest.find("</a>").unwrap_or(rest.len());
+        let href = match href {
+            Some(x) if x.starts_with("//") => format!("https:{}", x),
+            Some(x) if x.starts_with("http://") || x.starts_with("https://") => x,
+            _ => continue,
+        };
+        links.push((href, text(&rest[..end])));
+        rest = &rest[end..];
+    }
+    links
+}
+
+/// Whether `url` points to an archived copy in a web archive.
+pub fn is_archive(url: &str) -> bool {
+    let host = url.split("//").nth(1).and_then(|x| x.split('/').next());
+    host.is_some_and(|host| ARCHIVE_HOSTS.contains(&host))
+}
+
+/// Returns the id and the html of the text of every entry of the
+/// reference lists in `html`.
+pub fn references(html: &str) -> Vec<(String, String)> {
+    let mut references = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<li id=\"cite_note-") {
+        rest = &rest[start + 8..];
+        let id = match rest.find('"') {
+            Some(end) => rest[..end].to_owned(),
+            None => break,
+        };
+        let end = rest.find("</li>").unwrap_or(rest.len());
+        let item = &rest[..end];
+        let body = match item.find("class=\"reference-text\"") {
+            Some(x) => &item[x..],
+            None => item,
+        };
+        let body = body.find('>').map_or("", |x| &body[x + 1..]);
+        references.push((id, body.to_owned()));
+        rest = &rest[end..];
+    }
+    references
+}
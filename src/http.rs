@@ -1,3 +1,9 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
 pub use crate::Error;
 
 pub trait HttpClient {
@@ -12,32 +18,1512 @@ pub trait HttpClient {
     fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
     where
         I: Iterator<Item = (&'a str, &'a str)>;
+
+    /// Run an http POST request with the given url, sending the args as a
+    /// form, returning the result as a string. Defaults to `get`.
+    fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.get(base_url, args)
+    }
+
+    /// Like `get`, also sending the given headers with this request.
+    /// Defaults to `get`, ignoring the headers.
+    fn get_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let _ = headers;
+        self.get(base_url, args)
+    }
+
+    /// Like `post`, also sending the given headers with this request.
+    /// Defaults to `post`, ignoring the headers.
+    fn post_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let _ = headers;
+        self.post(base_url, args)
+    }
+
+    /// Run an http POST request with the given url, sending the args and
+    /// `file` as `multipart/form-data`, returning the result as a string.
+    /// Fails by default, as clients have to implement it to support
+    /// uploads.
+    fn post_multipart<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        file: &FilePart,
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let _ = (base_url, args, file);
+        Err(Error::HTTPError(Box::from(
+            "multipart requests aren't supported by this client",
+        )))
+    }
+
+    /// Like `get_with_headers`, also returning the status and the headers
+    /// of the response. Successful and `304 Not Modified` responses are
+    /// returned as `Ok`. Defaults to `get_with_headers`, as a `200`
+    /// response without headers.
+    fn get_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        Ok(Response {
+            status: 200,
+            headers: Vec::new(),
+            body: self.get_with_headers(base_url, args, headers)?,
+        })
+    }
+
+    /// Like `post_with_headers`, also returning the status and the headers
+    /// of the response, as `get_response` does. Defaults to
+    /// `post_with_headers`, as a `200` response without headers.
+    fn post_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        Ok(Response {
+            status: 200,
+            headers: Vec::new(),
+            body: self.post_with_headers(base_url, args, headers)?,
+        })
+    }
+}
+
+/// A file sent by `HttpClient::post_multipart`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilePart<'a> {
+    /// Name of the form field.
+    pub name: &'a str,
+    pub filename: &'a str,
+    pub content: &'a [u8],
+}
+
+/// A response to `HttpClient::get_response` or `HttpClient::post_response`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    /// The headers, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// The value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|x| x.0.eq_ignore_ascii_case(name))
+            .map(|x| &*x.1)
+    }
+}
+
+/// Object safe version of `HttpClient`, implemented for every client so
+/// `BoxedClient` can store it as a trait object.
+trait ErasedClient {
+    fn user_agent(&mut self, user_agent: String);
+    fn bearer_token(&mut self, bearer_token: String);
+    fn get(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>;
+    fn post(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>;
+    fn post_multipart(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        file: &FilePart,
+    ) -> Result<String, Error>;
+    fn get_response(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>;
+    fn post_response(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>;
+}
+
+impl<C: HttpClient> ErasedClient for C {
+    fn user_agent(&mut self, user_agent: String) {
+        HttpClient::user_agent(self, user_agent);
+    }
+
+    fn bearer_token(&mut self, bearer_token: String) {
+        HttpClient::bearer_token(self, bearer_token);
+    }
+
+    fn get(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error> {
+        self.get_with_headers(base_url, args.iter().cloned(), headers)
+    }
+
+    fn post(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error> {
+        self.post_with_headers(base_url, args.iter().cloned(), headers)
+    }
+
+    fn post_multipart(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        file: &FilePart,
+    ) -> Result<String, Error> {
+        HttpClient::post_multipart(self, base_url, args.iter().cloned(), file)
+    }
+
+    fn get_response(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        HttpClient::get_response(self, base_url, args.iter().cloned(), headers)
+    }
+
+    fn post_response(
+        &self,
+        base_url: &str,
+        args: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        HttpClient::post_response(self, base_url, args.iter().cloned(), headers)
+    }
+}
+
+/// A client chosen at runtime, for when the type of the client can't be
+/// part of the type of `Wikipedia`, e.g. to use either a real or a
+/// recording client as `Wikipedia<BoxedClient>`.
+pub struct BoxedClient {
+    client: Box<dyn ErasedClient>,
+}
+
+impl BoxedClient {
+    pub fn new<C: HttpClient + 'static>(client: C) -> Self {
+        BoxedClient {
+            client: Box::new(client),
+        }
+    }
+}
+
+impl HttpClient for BoxedClient {
+    fn user_agent(&mut self, user_agent: String) {
+        self.client.user_agent(user_agent);
+    }
+
+    fn bearer_token(&mut self, bearer_token: String) {
+        self.client.bearer_token(bearer_token);
+    }
+
+    fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.get(base_url, &args.collect::<Vec<_>>(), &[])
+    }
+
+    fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post(base_url, &args.collect::<Vec<_>>(), &[])
+    }
+
+    fn post_multipart<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        file: &FilePart,
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client
+            .post_multipart(base_url, &args.collect::<Vec<_>>(), file)
+    }
+
+    fn get_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client
+            .get(base_url, &args.collect::<Vec<_>>(), headers)
+    }
+
+    fn post_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client
+            .post(base_url, &args.collect::<Vec<_>>(), headers)
+    }
+
+    fn get_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client
+            .get_response(base_url, &args.collect::<Vec<_>>(), headers)
+    }
+
+    fn post_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client
+            .post_response(base_url, &args.collect::<Vec<_>>(), headers)
+    }
+}
+
+/// A client borrowed from a `Wikipedia`, used by `LanguageScope` to send
+/// requests through the client of the `Wikipedia` it was made from. The
+/// user agent and the bearer token can't be changed through it, so
+/// `user_agent` and `bearer_token` do nothing.
+#[derive(Debug)]
+pub struct BorrowedClient<'c, C: HttpClient>(pub(crate) &'c C);
+
+impl<C: HttpClient> HttpClient for BorrowedClient<'_, C> {
+    fn user_agent(&mut self, _user_agent: String) {}
+
+    fn bearer_token(&mut self, _bearer_token: String) {}
+
+    fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.get(base_url, args)
+    }
+
+    fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.post(base_url, args)
+    }
+
+    fn post_multipart<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        file: &FilePart,
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.post_multipart(base_url, args, file)
+    }
+
+    fn get_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.get_with_headers(base_url, args, headers)
+    }
+
+    fn post_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.post_with_headers(base_url, args, headers)
+    }
+
+    fn get_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.get_response(base_url, args, headers)
+    }
+
+    fn post_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.0.post_response(base_url, args, headers)
+    }
+}
+
+/// When and how often `Retry` repeats a failed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts, the first one included.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further one and
+    /// reduced by a random amount of up to half.
+    pub base_delay: Duration,
+    /// Whether to wait as long as the `Retry-After` header asks instead of
+    /// the computed delay.
+    pub honor_retry_after: bool,
+    /// Longest delay between attempts. A request whose `Retry-After` asks
+    /// for more fails with its error instead of blocking that long.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            honor_retry_after: true,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait before retry number `retry`, or `None` if the
+    /// server asked to wait longer than `max_delay`.
+    fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after.filter(|_| self.honor_retry_after) {
+            return Some(retry_after).filter(|x| *x <= self.max_delay);
+        }
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << (retry - 1).min(16))
+            .min(self.max_delay);
+        // the clock is random enough to keep clients from retrying in step
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.subsec_nanos());
+        Some(delay.mul_f64(1.0 - f64::from(nanos % 1000) / 2000.0))
+    }
+}
+
+/// Whether `error` may go away when repeating the request, returning how
+/// long the server asked to wait if it did.
+fn transient(error: &Error) -> Option<Option<Duration>> {
+    match *error {
+        Error::RateLimited { retry_after } => Some(retry_after),
+        Error::Http {
+            status,
+            retry_after,
+            ..
+        } if (500..600).contains(&status) => Some(retry_after),
+        Error::Timeout => Some(None),
+        Error::IOError(ref e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::UnexpectedEof
+            ) =>
+        {
+            Some(None)
+        }
+        #[cfg(feature = "http-client")]
+        Error::HTTPError(ref e)
+            if e.downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_connect()) =>
+        {
+            Some(None)
+        }
+        _ => None,
+    }
+}
+
+/// Wraps a client to repeat GET requests failing with
+/// `Error::RateLimited`, a server error status, a timeout or a dropped
+/// connection, as configured
+/// by a `RetryPolicy`. POST requests are sent once, including queries sent
+/// as POST because of their length (see `Wikipedia::post_threshold`).
+pub struct Retry<C> {
+    client: C,
+    policy: RetryPolicy,
+}
+
+impl<C: HttpClient> Retry<C> {
+    pub fn new(client: C, policy: RetryPolicy) -> Self {
+        Retry { client, policy }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// Calls `send` until it succeeds, fails with an error that isn't
+    /// transient or asks to wait longer than `max_delay`, or runs out of
+    /// attempts.
+    fn retry<T>(&self, send: impl Fn() -> Result<T, Error>) -> Result<T, Error> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let error = match send() {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            let retry_after = match transient(&error) {
+                Some(retry_after) if attempts < self.policy.max_attempts => retry_after,
+                Some(_) if attempts > 1 => {
+                    return Err(Error::RetriesExhausted {
+                        attempts,
+                        source: Box::new(error),
+                    })
+                }
+                _ => return Err(error),
+            };
+            match self.policy.delay(attempts, retry_after) {
+                Some(delay) => thread::sleep(delay),
+                None => return Err(error),
+            }
+        }
+    }
+}
+
+impl<C: HttpClient + Default> Default for Retry<C> {
+    fn default() -> Self {
+        Retry::new(C::default(), RetryPolicy::default())
+    }
+}
+
+impl<C: HttpClient + Clone> Clone for Retry<C> {
+    fn clone(&self) -> Self {
+        Retry::new(self.client.clone(), self.policy)
+    }
+}
+
+impl<C: HttpClient> HttpClient for Retry<C> {
+    fn user_agent(&mut self, user_agent: String) {
+        self.client.user_agent(user_agent);
+    }
+
+    fn bearer_token(&mut self, bearer_token: String) {
+        self.client.bearer_token(bearer_token);
+    }
+
+    fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.get_with_headers(base_url, args, &[])
+    }
+
+    fn get_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let args: Vec<_> = args.collect();
+        self.retry(|| {
+            self.client
+                .get_with_headers(base_url, args.iter().cloned(), headers)
+        })
+    }
+
+    fn get_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let args: Vec<_> = args.collect();
+        self.retry(|| {
+            self.client
+                .get_response(base_url, args.iter().cloned(), headers)
+        })
+    }
+
+    fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post(base_url, args)
+    }
+
+    fn post_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_with_headers(base_url, args, headers)
+    }
+
+    fn post_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_response(base_url, args, headers)
+    }
+
+    fn post_multipart<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        file: &FilePart,
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_multipart(base_url, args, file)
+    }
+}
+
+/// A response stored in a `ResponseCache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// When the response stops being fresh and has to be revalidated.
+    pub expires: SystemTime,
+}
+
+impl CachedResponse {
+    fn is_fresh(&self) -> bool {
+        SystemTime::now() < self.expires
+    }
+}
+
+/// Storage of the responses of `CachedClient`, keyed by request. Caches
+/// are best effort: a response that can't be stored is dropped, and
+/// expired responses may be returned by `get` so they can be revalidated.
+pub trait ResponseCache: Send + Sync {
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+    fn put(&self, key: &str, response: CachedResponse);
+    /// Drops all the stored responses.
+    fn clear(&self);
+}
+
+struct MemoryEntry {
+    response: CachedResponse,
+    /// Value of `Memory::tick` when the entry was last read or stored.
+    used: u64,
+}
+
+#[derive(Default)]
+struct Memory {
+    entries: HashMap<String, MemoryEntry>,
+    tick: u64,
+}
+
+/// Keeps up to `max_entries` responses in memory, dropping the least
+/// recently used one to make room.
+pub struct MemoryCache {
+    max_entries: usize,
+    memory: Mutex<Memory>,
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        MemoryCache {
+            max_entries,
+            memory: Mutex::new(Memory::default()),
+        }
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut memory = self.memory.lock().unwrap();
+        memory.tick += 1;
+        let tick = memory.tick;
+        let entry = memory.entries.get_mut(key)?;
+        entry.used = tick;
+        Some(entry.response.clone())
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut memory = self.memory.lock().unwrap();
+        memory.tick += 1;
+        let used = memory.tick;
+        if memory.entries.len() >= self.max_entries && !memory.entries.contains_key(key) {
+            let oldest = memory
+                .entries
+                .iter()
+                .min_by_key(|x| x.1.used)
+                .map(|x| x.0.clone());
+            if let Some(oldest) = oldest {
+                memory.entries.remove(&oldest);
+            }
+        }
+        memory
+            .entries
+            .insert(key.to_owned(), MemoryEntry { response, used });
+    }
+
+    fn clear(&self) {
+        self.memory.lock().unwrap().entries.clear();
+    }
+}
+
+/// Keeps responses in a directory, one file per request named after a hash
+/// of it, so they outlive the process. A file starts with a line of JSON
+/// with the request and the metadata of the response, followed by the body.
+#[cfg(feature = "file-cache")]
+pub struct FileCache {
+    directory: std::path::PathBuf,
+}
+
+#[cfg(feature = "file-cache")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FileHeader {
+    key: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Seconds since the epoch.
+    expires: u64,
+}
+
+#[cfg(feature = "file-cache")]
+impl FileCache {
+    /// Stores responses in `directory`, creating it if needed.
+    pub fn new<P: Into<std::path::PathBuf>>(directory: P) -> Result<Self, Error> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(FileCache { directory })
+    }
+
+    fn path(&self, key: &str) -> std::path::PathBuf {
+        // FNV-1a, as the hashers of std may change between releases
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, x| {
+            (hash ^ u64::from(x)).wrapping_mul(0x100000001b3)
+        });
+        self.directory.join(format!("{:016x}", hash))
+    }
+}
+
+#[cfg(feature = "file-cache")]
+impl ResponseCache for FileCache {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let contents = std::fs::read_to_string(self.path(key)).ok()?;
+        let (header, body) = contents.split_once('\n')?;
+        let header: FileHeader = serde_json::from_str(header).ok()?;
+        // another request with the same hash
+        if header.key != key {
+            return None;
+        }
+        Some(CachedResponse {
+            body: body.to_owned(),
+            etag: header.etag,
+            last_modified: header.last_modified,
+            expires: UNIX_EPOCH + Duration::from_secs(header.expires),
+        })
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let header = FileHeader {
+            key: key.to_owned(),
+            etag: response.etag,
+            last_modified: response.last_modified,
+            expires: response
+                .expires
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |x| x.as_secs()),
+        };
+        let header = match serde_json::to_string(&header) {
+            Ok(header) => header,
+            Err(_) => return,
+        };
+        // written aside and renamed, so readers never see half a file
+        let path = self.path(key);
+        let partial = path.with_extension("partial");
+        if std::fs::write(&partial, format!("{}\n{}", header, response.body)).is_ok() {
+            let _ = std::fs::rename(&partial, &path);
+        }
+    }
+
+    /// Removes the files written by the cache, named by the hash of their
+    /// key, leaving other files in the directory alone.
+    fn clear(&self) {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = match name.to_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            let hash = name.strip_suffix(".partial").unwrap_or(name);
+            if hash.len() == 16 && hash.bytes().all(|x| x.is_ascii_hexdigit()) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Wraps a client to keep the responses to GET requests in a
+/// `ResponseCache` for `ttl`, so repeated requests with the same url, args
+/// and headers aren't sent again. Failed and POST requests aren't cached.
+///
+/// Expired responses with an `ETag` or `Last-Modified` header are kept and
+/// revalidated by sending `If-None-Match` or `If-Modified-Since`, so a
+/// `304 Not Modified` answer renews them without sending the body again.
+///
+/// A `Cache-Control: no-cache` header, as accepted by `get_with_headers`,
+/// skips the cached response and stores the new one in its place. POST
+/// requests always bypass the cache and are sent to the wrapped client,
+/// with or without that header. Clones share the cache, as does a
+/// `Wikipedia` used from several threads.
+pub struct CachedClient<C> {
+    client: C,
+    ttl: Duration,
+    cache: Arc<dyn ResponseCache>,
+}
+
+impl<C: HttpClient> CachedClient<C> {
+    /// Keeps up to `max_entries` responses in a `MemoryCache`.
+    pub fn new(client: C, ttl: Duration, max_entries: usize) -> Self {
+        CachedClient::with_cache(client, ttl, Arc::new(MemoryCache::new(max_entries)))
+    }
+
+    pub fn with_cache(client: C, ttl: Duration, cache: Arc<dyn ResponseCache>) -> Self {
+        CachedClient { client, ttl, cache }
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// Drops all the cached responses.
+    pub fn purge(&self) {
+        self.cache.clear();
+    }
+
+    fn store(&self, key: &str, response: &Response) {
+        self.cache.put(
+            key,
+            CachedResponse {
+                body: response.body.clone(),
+                etag: response.header("etag").map(|x| x.to_owned()),
+                last_modified: response.header("last-modified").map(|x| x.to_owned()),
+                expires: SystemTime::now() + self.ttl,
+            },
+        );
+    }
+}
+
+/// Keeps responses for five minutes, up to a thousand of them.
+impl<C: HttpClient + Default> Default for CachedClient<C> {
+    fn default() -> Self {
+        CachedClient::new(C::default(), Duration::from_secs(300), 1000)
+    }
+}
+
+impl<C: HttpClient + Clone> Clone for CachedClient<C> {
+    fn clone(&self) -> Self {
+        CachedClient::with_cache(self.client.clone(), self.ttl, self.cache.clone())
+    }
+}
+
+impl<C: HttpClient> HttpClient for CachedClient<C> {
+    fn user_agent(&mut self, user_agent: String) {
+        self.client.user_agent(user_agent);
+    }
+
+    /// Also purges the cache, as responses may differ between users.
+    fn bearer_token(&mut self, bearer_token: String) {
+        self.client.bearer_token(bearer_token);
+        self.purge();
+    }
+
+    fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.get_with_headers(base_url, args, &[])
+    }
+
+    fn get_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let args: Vec<_> = args.collect();
+        // left out of the key, so a refresh replaces the plain response
+        let keyed: Vec<_> = headers
+            .iter()
+            .filter(|x| !x.0.eq_ignore_ascii_case("cache-control"))
+            .collect();
+        let key = serde_json::to_string(&(base_url, &args, &keyed))?;
+        let no_cache = headers.iter().any(|x| {
+            x.0.eq_ignore_ascii_case("cache-control") && x.1.eq_ignore_ascii_case("no-cache")
+        });
+        let cached = self.cache.get(&key);
+        let mut conditional = headers.to_vec();
+        match cached {
+            Some(ref cached) if cached.is_fresh() && !no_cache => return Ok(cached.body.clone()),
+            Some(ref cached) if !cached.is_fresh() => {
+                if let Some(ref etag) = cached.etag {
+                    conditional.push(("If-None-Match", etag));
+                }
+                if let Some(ref last_modified) = cached.last_modified {
+                    conditional.push(("If-Modified-Since", last_modified));
+                }
+            }
+            _ => {}
+        }
+        let response = self
+            .client
+            .get_response(base_url, args.into_iter(), &conditional)?;
+        match cached {
+            Some(mut cached) if response.status == 304 => {
+                cached.expires = SystemTime::now() + self.ttl;
+                self.cache.put(&key, cached.clone());
+                Ok(cached.body)
+            }
+            _ if response.status == 304 => Err(Error::Http {
+                status: 304,
+                body_snippet: String::new(),
+                retry_after: None,
+            }),
+            _ => {
+                self.store(&key, &response);
+                Ok(response.body)
+            }
+        }
+    }
+
+    fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post(base_url, args)
+    }
+
+    fn post_with_headers<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_with_headers(base_url, args, headers)
+    }
+
+    fn post_response<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_response(base_url, args, headers)
+    }
+
+    fn post_multipart<'a, I>(
+        &self,
+        base_url: &str,
+        args: I,
+        file: &FilePart,
+    ) -> Result<String, Error>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.client.post_multipart(base_url, args, file)
+    }
+}
+
+/// Token bucket allowing up to `requests` requests per `interval`, and a
+/// burst of as many. Clones share the bucket, so a limiter can cap several
+/// `Wikipedia` objects together.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    requests: u32,
+    interval: Duration,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter for `requests` requests per `interval`, failing if
+    /// either is zero.
+    pub fn new(requests: u32, interval: Duration) -> Result<Self, Error> {
+        if requests == 0 {
+            return Err(Error::InvalidParameter("requests".to_owned()));
+        }
+        if interval.is_zero() {
+            return Err(Error::InvalidParameter("interval".to_owned()));
+        }
+        Ok(RateLimiter {
+            requests,
+            interval,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(requests),
+                last: Instant::now(),
+            })),
+        })
+    }
+
+    /// Takes a token, sleeping until one is available.
+    pub fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let rate = f64::from(self.requests) / self.interval.as_secs_f64();
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.last).as_secs_f64() * rate;
+            bucket.tokens = (bucket.tokens + refill).min(f64::from(self.requests));
+            bucket.last = now;
+            // a negative balance reserves the next tokens for the callers
+            // already waiting
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        };
+        thread::sleep(wait);
+    }
 }
 
 #[cfg(feature = "http-client")]
 pub mod default {
     use reqwest;
-    use std::io::Read;
+    use std::io::{self, Read};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
-    use super::{Error, HttpClient};
+    use super::{Error, FilePart, HttpClient, Response, Retry, RetryPolicy};
 
+    /// Blocking client backed by `reqwest`. The underlying
+    /// `reqwest::blocking::Client` is created once and reused for all
+    /// requests, so connections are pooled. Clones share the pool.
+    /// Responses compressed with gzip or brotli are decompressed, see
+    /// `ClientBuilder::compression`.
+    ///
+    /// Cookies set by the server are sent back with later requests, which
+    /// keeps the session of `Wikipedia::login`. Clones share the cookies.
+    /// They are only sent to the hosts and paths they were set for, see
+    /// `CookieJar`, and are kept as long as the client, ignoring their
+    /// expiry date. They aren't sent while a bearer token is set, so an
+    /// OAuth token doesn't mix with a login session.
+    #[derive(Clone)]
     pub struct Client {
+        client: reqwest::blocking::Client,
         user_agent: String,
         bearer_token: Option<String>,
+        headers: Vec<(String, String)>,
+        cookies: Arc<Mutex<CookieJar>>,
+        timeout: Option<Duration>,
     }
 
-    impl Default for Client {
-        fn default() -> Self {
+    impl Client {
+        /// Creates a client sending requests through `client`, which can be
+        /// configured with its own pool, proxy or TLS settings.
+        pub fn with_client(client: reqwest::blocking::Client) -> Self {
             Client {
+                client,
                 user_agent: "wikipedia (https://github.com/seppo0010/wikipedia-rs)".to_owned(),
                 bearer_token: None,
+                headers: Vec::new(),
+                cookies: Arc::new(Mutex::new(CookieJar::default())),
+                timeout: None,
+            }
+        }
+
+        /// Adds a header sent with every request, like `Api-User-Agent`.
+        /// Invalid names or values make the requests fail.
+        pub fn with_header(mut self, name: &str, value: &str) -> Self {
+            self.headers.push((name.to_owned(), value.to_owned()));
+            self
+        }
+
+        /// Fails requests with `Error::Timeout` when they take longer than
+        /// `timeout`, connecting included, instead of the timeout of the
+        /// underlying `reqwest::blocking::Client`. Keeps the rest of the
+        /// configuration, unlike `with_timeout`.
+        pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = Some(timeout);
+            self
+        }
+
+        /// Wraps the client to retry failed requests as configured by
+        /// `policy`.
+        pub fn with_retries(self, policy: RetryPolicy) -> Retry<Self> {
+            Retry::new(self, policy)
+        }
+
+        /// Creates a client sending all requests through the proxy at
+        /// `url`, failing if the url is invalid.
+        pub fn with_proxy(url: &str) -> Result<Self, Error> {
+            ClientBuilder::new().proxy(url).build()
+        }
+
+        /// Creates a client failing requests with `Error::Timeout` when they
+        /// take longer than `timeout`, connecting included. Panics if the
+        /// TLS backend can't be initialized, like `Client::default`.
+        pub fn with_timeout(timeout: Duration) -> Self {
+            ClientBuilder::new()
+                .timeout(timeout)
+                .build()
+                .expect("failed to build the http client")
+        }
+
+        fn send(
+            &self,
+            request: reqwest::blocking::RequestBuilder,
+            headers: &[(&str, &str)],
+        ) -> Result<String, Error> {
+            let response = self.send_response(request, headers)?;
+            if response.status == 304 {
+                return Err(Error::Http {
+                    status: 304,
+                    body_snippet: String::new(),
+                    retry_after: None,
+                });
+            }
+            Ok(response.body)
+        }
+
+        /// Sends `request`, failing unless the status is successful or
+        /// `304 Not Modified`.
+        fn send_response(
+            &self,
+            mut request: reqwest::blocking::RequestBuilder,
+            headers: &[(&str, &str)],
+        ) -> Result<Response, Error> {
+            request = request.header(reqwest::header::USER_AGENT, self.user_agent.clone());
+            for (name, value) in &self.headers {
+                request = request.header(&**name, &**value);
+            }
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            if let Some(ref bearer_token) = self.bearer_token {
+                request = request.header(
+                    reqwest::header::AUTHORIZATION,
+                    format!("Bearer {}", bearer_token),
+                );
+            }
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+            let mut request = request.build()?;
+            if self.bearer_token.is_none() {
+                let cookies = self.cookies.lock().unwrap().header(request.url());
+                if let Some(value) =
+                    cookies.and_then(|x| reqwest::header::HeaderValue::from_str(&x).ok())
+                {
+                    request.headers_mut().insert(reqwest::header::COOKIE, value);
+                }
+            }
+            let mut response = self.client.execute(request)?;
+            {
+                let mut cookies = self.cookies.lock().unwrap();
+                for cookie in response.headers().get_all(reqwest::header::SET_COOKIE) {
+                    if let Ok(cookie) = cookie.to_str() {
+                        cookies.set(response.url(), cookie);
+                    }
+                }
+            }
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|x| x.to_str().ok())
+                .and_then(|x| x.trim().parse().ok())
+                .map(Duration::from_secs);
+            let response_headers = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned()))
+                })
+                .collect();
+
+            let mut body = Vec::new();
+            let read = response.read_to_end(&mut body).map_err(|e| match e.kind() {
+                io::ErrorKind::TimedOut => Error::Timeout,
+                _ => Error::IOError(e),
+            });
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(Error::RateLimited { retry_after });
+            }
+            if !status.is_success() && status != reqwest::StatusCode::NOT_MODIFIED {
+                return Err(Error::Http {
+                    status: status.as_u16(),
+                    body_snippet: snippet(&String::from_utf8_lossy(&body)),
+                    retry_after,
+                });
+            }
+            read?;
+            Ok(Response {
+                status: status.as_u16(),
+                headers: response_headers,
+                body: String::from_utf8(body)
+                    .map_err(|e| Error::IOError(io::Error::new(io::ErrorKind::InvalidData, e)))?,
+            })
+        }
+    }
+
+    /// A cookie, with the host or domain and the path it applies to.
+    #[derive(Debug, Clone)]
+    struct Cookie {
+        name: String,
+        value: String,
+        domain: String,
+        /// Set without a `Domain` attribute, so only sent to `domain` and
+        /// not its subdomains.
+        host_only: bool,
+        path: String,
+        secure: bool,
+    }
+
+    impl Cookie {
+        fn applies_to(&self, host: &str, path: &str, https: bool) -> bool {
+            let host_matches = host == self.domain
+                || (!self.host_only && host.ends_with(&*format!(".{}", self.domain)));
+            let path_matches = path == self.path
+                || (path.starts_with(&*self.path)
+                    && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+            host_matches && path_matches && (https || !self.secure)
+        }
+    }
+
+    /// The cookies kept by `Client`, matched by their `Domain` and `Path`
+    /// like browsers do, so the session of one wiki isn't sent to other
+    /// hosts. `Secure` cookies are only sent over https.
+    #[derive(Debug, Default)]
+    pub(crate) struct CookieJar {
+        cookies: Vec<Cookie>,
+    }
+
+    impl CookieJar {
+        /// Stores the cookie of a `Set-Cookie` header received from `url`,
+        /// or removes it if the header expires it. Cookies for a domain
+        /// `url` doesn't belong to are ignored.
+        pub(crate) fn set(&mut self, url: &reqwest::Url, header: &str) {
+            let host = match url.host_str() {
+                Some(host) => host.to_ascii_lowercase(),
+                None => return,
+            };
+            let mut parts = header.split(';');
+            let (name, value) = match parts.next().and_then(|x| x.split_once('=')) {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => return,
+            };
+            let mut expired = value == "deleted";
+            let mut domain = None;
+            let mut path = None;
+            let mut secure = false;
+            for part in parts {
+                let (key, attribute) = part.split_once('=').unwrap_or((part, ""));
+                let attribute = attribute.trim();
+                match &*key.trim().to_ascii_lowercase() {
+                    "max-age" => expired |= attribute.parse::<i64>().is_ok_and(|x| x <= 0),
+                    "domain" if !attribute.is_empty() => {
+                        domain = Some(attribute.trim_start_matches('.').to_ascii_lowercase())
+                    }
+                    "path" if attribute.starts_with('/') => path = Some(attribute.to_owned()),
+                    "secure" => secure = true,
+                    _ => {}
+                }
+            }
+            if let Some(ref domain) = domain {
+                if host != *domain && !host.ends_with(&*format!(".{}", domain)) {
+                    return;
+                }
+            }
+            // defaults to the directory of the request path
+            let path = path.unwrap_or_else(|| match url.path().rfind('/') {
+                Some(i) if i > 0 => url.path()[..i].to_owned(),
+                _ => "/".to_owned(),
+            });
+            let cookie = Cookie {
+                name: name.to_owned(),
+                value: value.to_owned(),
+                host_only: domain.is_none(),
+                domain: domain.unwrap_or(host),
+                path,
+                secure,
+            };
+            self.cookies.retain(|x| {
+                x.name != cookie.name || x.domain != cookie.domain || x.path != cookie.path
+            });
+            if !expired {
+                self.cookies.push(cookie);
+            }
+        }
+
+        /// The value of the `Cookie` header to send to `url`, if any cookie
+        /// applies to it.
+        pub(crate) fn header(&self, url: &reqwest::Url) -> Option<String> {
+            let host = url.host_str()?.to_ascii_lowercase();
+            let https = url.scheme() == "https";
+            let cookies: Vec<_> = self
+                .cookies
+                .iter()
+                .filter(|x| x.applies_to(&host, url.path(), https))
+                .map(|x| format!("{}={}", x.name, x.value))
+                .collect();
+            if cookies.is_empty() {
+                None
+            } else {
+                Some(cookies.join("; "))
+            }
+        }
+    }
+
+    fn url_error<E: std::error::Error + 'static>(url: &str, e: E) -> Error {
+        Error::URLError {
+            url: url.to_owned(),
+            source: Some(Box::new(e)),
+        }
+    }
+
+    /// Maximum number of characters of a response body kept in
+    /// `Error::Http`.
+    const SNIPPET_LEN: usize = 500;
+
+    /// Truncates `body` to `SNIPPET_LEN` characters.
+    fn snippet(body: &str) -> String {
+        let body = body.trim();
+        match body.char_indices().nth(SNIPPET_LEN) {
+            Some((i, _)) => format!("{}…", &body[..i]),
+            None => body.to_owned(),
+        }
+    }
+
+    /// Encodes `args` and `file` as `multipart/form-data`, returning the
+    /// boundary and the body.
+    pub(crate) fn multipart(args: &[(&str, &str)], file: &FilePart) -> (String, Vec<u8>) {
+        let contains = |boundary: &str| {
+            args.iter().any(|x| x.1.contains(boundary))
+                || file
+                    .content
+                    .windows(boundary.len())
+                    .any(|x| x == boundary.as_bytes())
+        };
+        let mut boundary = "wikipedia-rs-boundary".to_owned();
+        let mut i = 0;
+        while contains(&boundary) {
+            i += 1;
+            boundary = format!("wikipedia-rs-boundary-{}", i);
+        }
+        // quotes can't be escaped in the header, browsers encode them
+        let quote = |x: &str| x.replace('"', "%22").replace(['\r', '\n'], " ");
+        let mut body = Vec::new();
+        for (name, value) in args {
+            body.extend(
+                format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                    boundary,
+                    quote(name),
+                    value
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                boundary,
+                quote(file.name),
+                quote(file.filename)
+            )
+            .as_bytes(),
+        );
+        body.extend(file.content);
+        body.extend(format!("\r\n--{}--\r\n", boundary).as_bytes());
+        (boundary, body)
+    }
+
+    /// Builds a `Client` with separate connect and total timeouts and a
+    /// proxy. The timeouts are unset by default, so requests wait for the
+    /// server forever. Without a proxy, the one given by the `HTTP_PROXY`,
+    /// `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` environment variables is
+    /// used.
+    #[derive(Debug, Clone, Default)]
+    pub struct ClientBuilder {
+        connect_timeout: Option<Duration>,
+        timeout: Option<Duration>,
+        proxy: Option<String>,
+        no_proxy: bool,
+        compression: Option<bool>,
+    }
+
+    impl ClientBuilder {
+        pub fn new() -> Self {
+            ClientBuilder::default()
+        }
+
+        /// Sets the time allowed to establish the connection.
+        pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+            self.connect_timeout = Some(timeout);
+            self
+        }
+
+        /// Sets the time allowed for a whole request, from connecting until
+        /// the response is read.
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = Some(timeout);
+            self
+        }
+
+        /// Sends all requests through the proxy at `url`, like
+        /// `http://proxy.example:3128`, ignoring the environment.
+        pub fn proxy(mut self, url: &str) -> Self {
+            self.proxy = Some(url.to_owned());
+            self
+        }
+
+        /// Connects directly, ignoring the proxy environment variables.
+        pub fn no_proxy(mut self) -> Self {
+            self.no_proxy = true;
+            self
+        }
+
+        /// Sets whether responses are requested compressed with gzip or
+        /// brotli, sending `Accept-Encoding`, and decompressed. Enabled by
+        /// default, disable it to receive the responses as sent.
+        pub fn compression(mut self, enabled: bool) -> Self {
+            self.compression = Some(enabled);
+            self
+        }
+
+        /// Builds the client, failing with `Error::URLError` if the proxy url
+        /// is invalid.
+        pub fn build(self) -> Result<Client, Error> {
+            let mut builder = reqwest::blocking::Client::builder().timeout(self.timeout);
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.connect_timeout(timeout);
+            }
+            if self.no_proxy {
+                builder = builder.no_proxy();
+            }
+            if let Some(enabled) = self.compression {
+                builder = builder.gzip(enabled).brotli(enabled);
             }
+            if let Some(ref url) = self.proxy {
+                let proxy = reqwest::Proxy::all(&**url).map_err(|e| url_error(url, e))?;
+                builder = builder.proxy(proxy);
+            }
+            Ok(Client::with_client(builder.build()?))
+        }
+    }
+
+    impl Default for Client {
+        fn default() -> Self {
+            Client::with_client(reqwest::blocking::Client::new())
         }
     }
 
     impl From<reqwest::Error> for Error {
         fn from(e: reqwest::Error) -> Error {
-            Error::HTTPError(Box::new(e))
+            if e.is_timeout() {
+                Error::Timeout
+            } else {
+                Error::HTTPError(Box::new(e))
+            }
         }
     }
 
@@ -54,22 +1540,92 @@ pub mod default {
         where
             I: Iterator<Item = (&'a str, &'a str)>,
         {
-            let url =
-                reqwest::Url::parse_with_params(base_url, args).map_err(|_| Error::URLError)?;
-            let mut request = reqwest::blocking::Client::new()
-                .get(url)
-                .header(reqwest::header::USER_AGENT, self.user_agent.clone());
-            if let Some(ref bearer_token) = self.bearer_token {
-                request = request.header(
-                    reqwest::header::AUTHORIZATION,
-                    format!("Bearer {}", bearer_token),
-                );
-            }
-            let mut response = request.send()?.error_for_status()?;
+            self.get_with_headers(base_url, args, &[])
+        }
 
-            let mut response_str = String::new();
-            response.read_to_string(&mut response_str)?;
-            Ok(response_str)
+        fn get_with_headers<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<String, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let url = reqwest::Url::parse_with_params(base_url, args)
+                .map_err(|e| url_error(base_url, e))?;
+            self.send(self.client.get(url), headers)
+        }
+
+        fn get_response<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<Response, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let url = reqwest::Url::parse_with_params(base_url, args)
+                .map_err(|e| url_error(base_url, e))?;
+            self.send_response(self.client.get(url), headers)
+        }
+
+        fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.post_with_headers(base_url, args, &[])
+        }
+
+        fn post_with_headers<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<String, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let url = reqwest::Url::parse(base_url).map_err(|e| url_error(base_url, e))?;
+            let form: Vec<_> = args.collect();
+            self.send(self.client.post(url).form(&form), headers)
+        }
+
+        fn post_response<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<Response, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let url = reqwest::Url::parse(base_url).map_err(|e| url_error(base_url, e))?;
+            let form: Vec<_> = args.collect();
+            self.send_response(self.client.post(url).form(&form), headers)
+        }
+
+        fn post_multipart<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            file: &FilePart,
+        ) -> Result<String, Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let url = reqwest::Url::parse(base_url).map_err(|e| url_error(base_url, e))?;
+            let (boundary, body) = multipart(&args.collect::<Vec<_>>(), file);
+            let request = self
+                .client
+                .post(url)
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .body(body);
+            self.send(request, &[])
         }
     }
 }
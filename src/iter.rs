@@ -1,60 +1,479 @@
+use std::collections::{HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::vec::IntoIter;
 
 use serde_json::Value;
 
-use super::{http, Page, Result};
+use super::{http, CategoryMemberType, ChangeType, Error, Limit, Page, Result, Wikipedia};
 
 pub type IterElems = Vec<(String, String)>;
 
+/// The state of an iterator, as serialized by `continue_token`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Token {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    params: Option<Vec<(String, String)>>,
+    /// Continuation that fetches the current batch, `None` for the first.
+    #[serde(rename = "continue")]
+    cont: Option<IterElems>,
+    /// Number of items of the current batch already returned.
+    offset: usize,
+}
+
+impl Token {
+    fn encode(self) -> String {
+        serde_json::to_string(&self).expect("a token is always serializable")
+    }
+
+    fn decode(token: &str) -> Result<Token> {
+        serde_json::from_str(token).map_err(|_| Error::InvalidParameter("token".to_string()))
+    }
+}
+
+/// Builds the token of an iterator which returned `offset` items of the
+/// batch fetched with `current`, `None` if it is exhausted.
+fn token(
+    params: Option<&[(String, String)]>,
+    inner: &IntoIter<Value>,
+    cont: &Option<IterElems>,
+    current: &Option<IterElems>,
+    offset: usize,
+) -> Option<Token> {
+    let (cont, offset) = if inner.len() == 0 {
+        (Some(cont.clone()?), 0)
+    } else {
+        (current.clone(), offset)
+    };
+    Some(Token {
+        params: params.map(|x| x.to_vec()),
+        cont,
+        offset,
+    })
+}
+
+/// Per-call settings of an `Iter`, overriding the `*_results` fields of
+/// `Wikipedia`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IterOptions {
+    page_size: Option<String>,
+    max_items: Option<usize>,
+}
+
+impl IterOptions {
+    pub fn new() -> IterOptions {
+        IterOptions::default()
+    }
+
+    /// Sets the number of items fetched per request, a number between 1
+    /// and 500 or `max`.
+    pub fn page_size<S: ToString>(mut self, page_size: S) -> IterOptions {
+        self.page_size = Some(page_size.to_string());
+        self
+    }
+
+    /// Stops the iterator, and its requests, after `max_items` items.
+    pub fn max_items(mut self, max_items: usize) -> IterOptions {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Parses the page size, if any.
+    fn limit(&self) -> Result<Option<Limit>> {
+        self.page_size
+            .as_deref()
+            .map(|x| {
+                x.parse()
+                    .map_err(|_| Error::InvalidParameter("page_size".to_string()))
+            })
+            .transpose()
+    }
+}
+
 pub struct Iter<'a, A: 'a + http::HttpClient, B: IterItem> {
     page: &'a Page<'a, A>,
     inner: IntoIter<Value>,
     cont: Option<Vec<(String, String)>>,
+    current: Option<IterElems>,
+    offset: usize,
+    page_size: Option<Limit>,
+    remaining: Option<usize>,
+    /// Set when fetching a batch failed, which ends the iterator.
+    failed: bool,
     phantom: PhantomData<B>,
 }
 
 impl<'a, A: http::HttpClient, B: IterItem> Iter<'a, A, B> {
     pub fn new(page: &'a Page<A>) -> Result<Iter<'a, A, B>> {
-        let (array, cont) = B::request_next(page, &None)?;
+        Iter::with_options(page, &IterOptions::default())
+    }
+
+    /// Like `new`, with the page size and number of items given by
+    /// `options`.
+    pub fn with_options(page: &'a Page<A>, options: &IterOptions) -> Result<Iter<'a, A, B>> {
+        Iter::start(page, None, 0, options.limit()?, options.max_items)
+    }
+
+    /// Resumes iterating where the iterator that returned `token` from
+    /// `continue_token` stopped.
+    pub fn from_token(page: &'a Page<A>, token: &str) -> Result<Iter<'a, A, B>> {
+        let token = Token::decode(token)?;
+        Iter::start(page, token.cont, token.offset, None, None)
+    }
+
+    fn start(
+        page: &'a Page<A>,
+        current: Option<IterElems>,
+        offset: usize,
+        page_size: Option<Limit>,
+        max_items: Option<usize>,
+    ) -> Result<Self> {
+        let (array, cont) = B::request_next(page, &current, page_size)?;
+        let mut inner = array.into_iter();
+        if offset > 0 {
+            inner.nth(offset - 1);
+        }
         Ok(Iter {
             page,
-            inner: array.into_iter(),
+            inner,
             cont,
+            current,
+            offset,
+            page_size,
+            remaining: max_items,
+            failed: false,
             phantom: PhantomData,
         })
     }
 
+    /// Turns this into an iterator which returns the errors of requests
+    /// for later batches and of unexpected items, instead of ending.
+    pub fn fallible(self) -> TryIter<'a, A, B> {
+        TryIter { iter: self }
+    }
+
+    /// Returns a token to resume iterating later with `from_token`, or
+    /// `None` if there are no more items. Resuming fetches the batch the
+    /// iterator is in again, so no items are skipped.
+    pub fn continue_token(&self) -> Option<String> {
+        Some(token(None, &self.inner, &self.cont, &self.current, self.offset)?.encode())
+    }
+
     fn fetch_next(&mut self) -> Result<()> {
         if self.cont.is_some() {
-            let (array, cont) = B::request_next(self.page, &self.cont)?;
+            let (array, cont) = B::request_next(self.page, &self.cont, self.page_size)?;
             self.inner = array.into_iter();
-            self.cont = cont;
+            self.current = std::mem::replace(&mut self.cont, cont);
+            self.offset = 0;
         }
         Ok(())
     }
+
+    /// The API reports no totals for the properties of a page, so only the
+    /// fetched batch and `max_items` bound the number of items.
+    fn bounds(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            return (0, Some(0));
+        }
+        let (lower, upper) = size_hint(self.inner.len(), self.cont.is_some(), None);
+        match self.remaining {
+            Some(x) => (lower.min(x), Some(upper.map_or(x, |u| u.min(x)))),
+            None => (lower, upper),
+        }
+    }
+
+    /// Returns the next raw item, fetching the next batch if needed.
+    fn next_value(&mut self) -> Option<Result<Value>> {
+        if self.remaining == Some(0) || self.failed {
+            return None;
+        }
+        let value = match self.inner.next() {
+            Some(v) => v,
+            None => {
+                self.cont.as_ref()?;
+                if let Err(e) = self.fetch_next() {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+                self.inner.next()?
+            }
+        };
+        self.offset += 1;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        Some(Ok(value))
+    }
 }
 
 impl<A: http::HttpClient, B: IterItem> Iterator for Iter<'_, A, B> {
     type Item = B;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.inner.next() {
-            Some(ref v) => B::from_value(v),
-            None => match self.cont {
-                Some(_) => match self.fetch_next() {
-                    Ok(_) => self.inner.next().and_then(|x| B::from_value(&x)),
-                    Err(_) => None,
-                },
-                None => None,
-            },
+        self.next_value()?.ok().and_then(|x| B::from_value(&x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bounds()
+    }
+}
+
+/// Like `Iter`, returning errors as items instead of ending. After a
+/// failed request the iterator ends, and `continue_token` can be used to
+/// retry later.
+pub struct TryIter<'a, A: 'a + http::HttpClient, B: IterItem> {
+    iter: Iter<'a, A, B>,
+}
+
+impl<A: http::HttpClient, B: IterItem> TryIter<'_, A, B> {
+    /// See `Iter::continue_token`.
+    pub fn continue_token(&self) -> Option<String> {
+        self.iter.continue_token()
+    }
+}
+
+impl<A: http::HttpClient, B: IterItem> Iterator for TryIter<'_, A, B> {
+    type Item = Result<B>;
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(
+            self.iter
+                .next_value()?
+                .and_then(|x| B::from_value(&x).ok_or(Error::JSONPathError("query.pages[]"))),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.bounds()
+    }
+}
+
+/// Like `Iter`, for items of a `list` query that isn't tied to a `Page`.
+/// Entries which can't be parsed, like changes of an unknown type, are
+/// skipped.
+pub struct ListIter<'a, A: 'a + http::HttpClient, B: ListItem> {
+    wikipedia: &'a Wikipedia<A>,
+    params: Vec<(String, String)>,
+    inner: IntoIter<Value>,
+    cont: Option<Vec<(String, String)>>,
+    current: Option<IterElems>,
+    offset: usize,
+    /// Number of items the listing is expected to have, if queried.
+    total: Option<usize>,
+    yielded: usize,
+    /// Set when fetching a batch failed, which ends the iterator.
+    failed: bool,
+    phantom: PhantomData<B>,
+}
+
+impl<'a, A: http::HttpClient, B: ListItem> ListIter<'a, A, B> {
+    pub fn new(wikipedia: &'a Wikipedia<A>, params: Vec<(String, String)>) -> Result<Self> {
+        ListIter::start(wikipedia, params, None, 0)
+    }
+
+    /// Resumes iterating where the iterator that returned `token` from
+    /// `continue_token` stopped. The token includes the parameters of the
+    /// listing, so it must come from a `ListIter` of the same item type.
+    pub fn from_token(wikipedia: &'a Wikipedia<A>, token: &str) -> Result<Self> {
+        let token = Token::decode(token)?;
+        let params = token
+            .params
+            .ok_or_else(|| Error::InvalidParameter("token".to_string()))?;
+        ListIter::start(wikipedia, params, token.cont, token.offset)
+    }
+
+    fn start(
+        wikipedia: &'a Wikipedia<A>,
+        params: Vec<(String, String)>,
+        current: Option<IterElems>,
+        offset: usize,
+    ) -> Result<Self> {
+        let (array, cont) = wikipedia.request_list(B::LIST, borrow(&params), &current)?;
+        let mut inner = parseable::<B>(array);
+        if offset > 0 {
+            inner.nth(offset - 1);
         }
+        Ok(ListIter {
+            wikipedia,
+            params,
+            inner,
+            cont,
+            current,
+            offset,
+            total: None,
+            yielded: 0,
+            failed: false,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Turns this into an iterator which returns the errors of requests
+    /// for later batches, instead of ending.
+    pub fn fallible(self) -> TryListIter<'a, A, B> {
+        TryListIter { iter: self }
+    }
+
+    /// Gets the value of a parameter of the listing.
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|x| x.0 == name).map(|x| &*x.1)
+    }
+
+    /// Like `Iter::continue_token`.
+    pub fn continue_token(&self) -> Option<String> {
+        Some(
+            token(
+                Some(&self.params),
+                &self.inner,
+                &self.cont,
+                &self.current,
+                self.offset,
+            )?
+            .encode(),
+        )
+    }
+
+    fn fetch_next(&mut self) -> Result<()> {
+        if self.cont.is_some() {
+            let (array, cont) =
+                self.wikipedia
+                    .request_list(B::LIST, borrow(&self.params), &self.cont)?;
+            self.inner = parseable::<B>(array);
+            self.current = std::mem::replace(&mut self.cont, cont);
+            self.offset = 0;
+        }
+        Ok(())
+    }
+
+    fn bounds(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            return (0, Some(0));
+        }
+        size_hint(
+            self.inner.len(),
+            self.cont.is_some(),
+            self.total.map(|x| x.saturating_sub(self.yielded)),
+        )
     }
+
+    /// Returns the next item, fetching the next batch if needed.
+    fn next_item(&mut self) -> Option<Result<B>> {
+        if self.failed {
+            return None;
+        }
+        let value = match self.inner.next() {
+            Some(v) => v,
+            None => {
+                self.cont.as_ref()?;
+                if let Err(e) = self.fetch_next() {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+                self.inner.next()?
+            }
+        };
+        self.offset += 1;
+        self.yielded += 1;
+        // the batch only holds parseable entries
+        B::from_value(&value).map(Ok)
+    }
+}
+
+/// Drops the entries of a batch which can't be parsed as `B`, so they are
+/// neither returned nor counted by `size_hint`.
+fn parseable<B: ListItem>(array: Vec<Value>) -> IntoIter<Value> {
+    array
+        .into_iter()
+        .filter(|x| B::from_value(x).is_some())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+impl<'a, A: http::HttpClient> ListIter<'a, A, CategoryMember> {
+    /// Queries the number of members of the category, so `size_hint`
+    /// returns it as upper bound. The count is maintained by the wiki and
+    /// may be slightly off for categories that changed recently.
+    pub fn with_count_hint(mut self) -> Result<Self> {
+        let title = self
+            .param("cmtitle")
+            .ok_or_else(|| Error::InvalidParameter("cmtitle".to_string()))?;
+        let info = self.wikipedia.category_info(title)?;
+        let total = match self.param("cmtype") {
+            Some(types) => types
+                .split('|')
+                .map(|x| match x {
+                    "page" => info.pages,
+                    "subcat" => info.subcats,
+                    "file" => info.files,
+                    _ => 0,
+                })
+                .sum(),
+            None => info.pages + info.subcats + info.files,
+        };
+        self.total = Some(total as usize);
+        Ok(self)
+    }
+}
+
+/// Bounds of the number of items left in an iterator with `buffered` items
+/// fetched, `cont` telling if there are more batches and `total` the
+/// expected number of items not returned yet, if known.
+fn size_hint(buffered: usize, cont: bool, total: Option<usize>) -> (usize, Option<usize>) {
+    if !cont {
+        return (buffered, Some(buffered));
+    }
+    (buffered, total.map(|x| x.max(buffered)))
+}
+
+fn borrow(params: &[(String, String)]) -> Vec<(&str, &str)> {
+    params.iter().map(|x| (&*x.0, &*x.1)).collect()
+}
+
+impl<A: http::HttpClient, B: ListItem> Iterator for ListIter<'_, A, B> {
+    type Item = B;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_item()?.ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bounds()
+    }
+}
+
+/// Like `ListIter`, returning the errors of requests as items instead of
+/// ending. After a failed request the iterator ends, and `continue_token`
+/// can be used to retry later.
+pub struct TryListIter<'a, A: 'a + http::HttpClient, B: ListItem> {
+    iter: ListIter<'a, A, B>,
+}
+
+impl<A: http::HttpClient, B: ListItem> TryListIter<'_, A, B> {
+    /// See `ListIter::continue_token`.
+    pub fn continue_token(&self) -> Option<String> {
+        self.iter.continue_token()
+    }
+}
+
+impl<A: http::HttpClient, B: ListItem> Iterator for TryListIter<'_, A, B> {
+    type Item = Result<B>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_item()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.bounds()
+    }
+}
+
+pub trait ListItem: Sized {
+    /// Name of the `list` module, which is also the key of the results.
+    const LIST: &'static str;
+    fn from_value(value: &Value) -> Option<Self>;
 }
 
 pub trait IterItem: Sized {
+    /// Fetches a batch of items, `limit` overrides the number of items per
+    /// request configured in `Wikipedia`.
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<IterElems>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<IterElems>)>;
     fn from_value(value: &Value) -> Option<Self>;
 }
@@ -70,8 +489,9 @@ impl IterItem for Image {
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<IterElems>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<IterElems>)> {
-        page.request_images(cont)
+        page.request_images(cont, limit)
     }
 
     fn from_value(value: &Value) -> Option<Image> {
@@ -109,6 +529,60 @@ impl IterItem for Image {
     }
 }
 
+/// An image of a page together with the metadata of its file.
+#[derive(Debug, PartialEq)]
+pub struct DetailedImage {
+    pub url: String,
+    pub title: String,
+    pub description_url: String,
+    pub mime: String,
+    pub width: u64,
+    pub height: u64,
+
+    /// Size of the file in bytes
+    pub size: u64,
+}
+
+impl IterItem for DetailedImage {
+    fn request_next<A: http::HttpClient>(
+        page: &Page<A>,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<Value>, Option<IterElems>)> {
+        page.request_images_detailed(cont, limit)
+    }
+
+    fn from_value(value: &Value) -> Option<DetailedImage> {
+        let obj = value.as_object()?;
+        let info = obj
+            .get("imageinfo")
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.first())
+            .and_then(|x| x.as_object());
+        let text = |key: &str| {
+            info.and_then(|x| x.get(key))
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        let number = |key: &str| info.and_then(|x| x.get(key)).and_then(|x| x.as_u64());
+
+        Some(DetailedImage {
+            url: text("url"),
+            title: obj
+                .get("title")
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned(),
+            description_url: text("descriptionurl"),
+            mime: text("mime"),
+            width: number("width").unwrap_or(0),
+            height: number("height").unwrap_or(0),
+            size: number("size").unwrap_or(0),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Reference {
     pub url: String,
@@ -118,20 +592,22 @@ impl IterItem for Reference {
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<IterElems>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<IterElems>)> {
-        page.request_extlinks(cont)
+        page.request_extlinks(cont, limit)
     }
 
     fn from_value(value: &Value) -> Option<Reference> {
         value
             .as_object()
-            .and_then(|x| x.get("*"))
+            .and_then(|x| x.get("*").or_else(|| x.get("url")))
             .and_then(|x| x.as_str())
             .map(|s| Reference {
-                url: if s.starts_with("http:") {
-                    s.to_owned()
-                } else {
+                // protocol relative urls start with `//`
+                url: if s.starts_with("//") {
                     format!("http:{}", s)
+                } else {
+                    s.to_owned()
                 },
             })
     }
@@ -140,24 +616,117 @@ impl IterItem for Reference {
 #[derive(Debug, PartialEq)]
 pub struct Link {
     pub title: String,
+
+    /// The namespace id of the linked page
+    pub namespace: i32,
 }
 
 impl IterItem for Link {
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
-        page.request_links(cont)
+        page.request_links(cont, limit)
     }
 
     fn from_value(value: &Value) -> Option<Link> {
-        value
-            .as_object()
-            .and_then(|x| x.get("title"))
-            .and_then(|x| x.as_str())
-            .map(|s| Link {
-                title: s.to_owned(),
-            })
+        let l = value.as_object()?;
+        Some(Link {
+            title: l.get("title")?.as_str()?.to_owned(),
+            namespace: l.get("ns").and_then(|n| n.as_i64()).unwrap_or(0) as i32,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Backlink {
+    pub title: String,
+    pub pageid: u64,
+
+    /// The namespace id of the linking page
+    pub ns: i64,
+
+    /// Whether the linking page is a redirect
+    pub redirect: bool,
+
+    /// The redirect this page links through, if it doesn't link directly
+    pub via: Option<String>,
+}
+
+impl IterItem for Backlink {
+    fn request_next<A: http::HttpClient>(
+        page: &Page<A>,
+        cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
+        page.request_backlinks(cont, limit)
+    }
+
+    fn from_value(value: &Value) -> Option<Backlink> {
+        let b = value.as_object()?;
+        Some(Backlink {
+            title: b.get("title")?.as_str()?.into(),
+            pageid: b.get("pageid").and_then(|n| n.as_u64()).unwrap_or(0),
+            ns: b.get("ns").and_then(|n| n.as_i64()).unwrap_or(0),
+            redirect: b.contains_key("redirect"),
+            via: b.get("via").and_then(|n| n.as_str()).map(|n| n.into()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Redirect {
+    pub title: String,
+    pub pageid: u64,
+
+    /// The section the redirect points to, if any
+    pub fragment: Option<String>,
+}
+
+impl IterItem for Redirect {
+    fn request_next<A: http::HttpClient>(
+        page: &Page<A>,
+        cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
+        page.request_redirects(cont, limit)
+    }
+
+    fn from_value(value: &Value) -> Option<Redirect> {
+        let r = value.as_object()?;
+        Some(Redirect {
+            title: r.get("title")?.as_str()?.into(),
+            pageid: r.get("pageid").and_then(|n| n.as_u64()).unwrap_or(0),
+            fragment: r.get("fragment").and_then(|n| n.as_str()).map(|n| n.into()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    /// The full title, including the namespace prefix
+    pub title: String,
+
+    /// The namespace id, 10 for `Template:`
+    pub ns: i64,
+}
+
+impl IterItem for Template {
+    fn request_next<A: http::HttpClient>(
+        page: &Page<A>,
+        cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
+        page.request_templates(cont, limit)
+    }
+
+    fn from_value(value: &Value) -> Option<Template> {
+        let t = value.as_object()?;
+        Some(Template {
+            title: t.get("title")?.as_str()?.into(),
+            ns: t.get("ns").and_then(|n| n.as_i64()).unwrap_or(10),
+        })
     }
 }
 
@@ -168,20 +737,26 @@ pub struct LangLink {
 
     /// The page title in this language, may be `None` if undefined
     pub title: Option<String>,
+
+    /// The full url of the page in this language
+    pub url: Option<String>,
 }
 
 impl IterItem for LangLink {
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
-        page.request_langlinks(cont)
+        page.request_langlinks(cont, limit)
     }
 
     fn from_value(value: &Value) -> Option<LangLink> {
-        value.as_object().map(|l| LangLink {
-            lang: l.get("lang").unwrap().as_str().unwrap().into(),
+        let l = value.as_object()?;
+        Some(LangLink {
+            lang: l.get("lang")?.as_str()?.into(),
             title: l.get("*").and_then(|n| n.as_str()).map(|n| n.into()),
+            url: l.get("url").and_then(|n| n.as_str()).map(|n| n.into()),
         })
     }
 }
@@ -189,27 +764,249 @@ impl IterItem for LangLink {
 #[derive(Debug, PartialEq)]
 pub struct Category {
     pub title: String,
+
+    /// Whether this is a hidden maintenance or tracking category
+    pub hidden: bool,
+
+    /// The sort key given for the page in this category, if any
+    pub sortkey: Option<String>,
 }
 
 impl IterItem for Category {
     fn request_next<A: http::HttpClient>(
         page: &Page<A>,
         cont: &Option<Vec<(String, String)>>,
+        limit: Option<Limit>,
     ) -> Result<(Vec<Value>, Option<Vec<(String, String)>>)> {
-        page.request_categories(cont)
+        page.request_categories(cont, limit)
     }
 
     fn from_value(value: &Value) -> Option<Category> {
-        value
-            .as_object()
-            .and_then(|x| x.get("title"))
-            .and_then(|x| x.as_str())
-            .map(|s| Category {
-                title: if let Some(st) = s.strip_prefix("Category: ") {
-                    st.to_owned()
-                } else {
-                    s.to_owned()
-                },
-            })
+        let obj = value.as_object()?;
+        obj.get("title").and_then(|x| x.as_str()).map(|s| Category {
+            title: if let Some(st) = s.strip_prefix("Category: ") {
+                st.to_owned()
+            } else {
+                s.to_owned()
+            },
+            hidden: obj.contains_key("hidden"),
+            // `sortkey` is the binary collation key, the prefix is the one
+            // written in the wikitext
+            sortkey: obj
+                .get("sortkeyprefix")
+                .and_then(|x| x.as_str())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_owned()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImageUsage {
+    pub title: String,
+    pub pageid: u64,
+
+    /// The namespace id of the embedding page
+    pub ns: i64,
+}
+
+impl ListItem for ImageUsage {
+    const LIST: &'static str = "imageusage";
+
+    fn from_value(value: &Value) -> Option<ImageUsage> {
+        let u = value.as_object()?;
+        Some(ImageUsage {
+            title: u.get("title")?.as_str()?.into(),
+            pageid: u.get("pageid").and_then(|n| n.as_u64()).unwrap_or(0),
+            ns: u.get("ns").and_then(|n| n.as_i64()).unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CategoryMember {
+    pub title: String,
+    pub pageid: u64,
+
+    /// The namespace id, 14 for subcategories
+    pub ns: i64,
+
+    /// Whether the member is a page, a subcategory or a file
+    pub kind: CategoryMemberType,
+
+    /// When the member was added to the category
+    pub timestamp: Option<String>,
+}
+
+impl ListItem for CategoryMember {
+    const LIST: &'static str = "categorymembers";
+
+    fn from_value(value: &Value) -> Option<CategoryMember> {
+        let m = value.as_object()?;
+        Some(CategoryMember {
+            title: m.get("title")?.as_str()?.into(),
+            pageid: m.get("pageid").and_then(|n| n.as_u64()).unwrap_or(0),
+            ns: m.get("ns").and_then(|n| n.as_i64()).unwrap_or(0),
+            kind: m
+                .get("type")
+                .and_then(|n| n.as_str())
+                .and_then(CategoryMemberType::from_api)
+                .unwrap_or(CategoryMemberType::Page),
+            timestamp: m
+                .get("timestamp")
+                .and_then(|n| n.as_str())
+                .map(|n| n.into()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AllPage {
+    pub title: String,
+    pub pageid: u64,
+    pub ns: i64,
+}
+
+impl ListItem for AllPage {
+    const LIST: &'static str = "allpages";
+
+    fn from_value(value: &Value) -> Option<AllPage> {
+        let p = value.as_object()?;
+        Some(AllPage {
+            title: p.get("title")?.as_str()?.into(),
+            pageid: p.get("pageid").and_then(|n| n.as_u64()).unwrap_or(0),
+            ns: p.get("ns").and_then(|n| n.as_i64()).unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RecentChange {
+    pub kind: ChangeType,
+    pub title: String,
+    pub ns: i64,
+    pub timestamp: String,
+
+    /// The user name, or the IP address for anonymous edits
+    pub user: String,
+    pub comment: String,
+
+    /// The page size in bytes before and after the change
+    pub old_len: u64,
+    pub new_len: u64,
+
+    /// The revision created by the change, 0 for log entries
+    pub revid: u64,
+    pub old_revid: u64,
+}
+
+impl ListItem for RecentChange {
+    const LIST: &'static str = "recentchanges";
+
+    fn from_value(value: &Value) -> Option<RecentChange> {
+        let c = value.as_object()?;
+        let string = |name| {
+            c.get(name)
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        let number = |name| c.get(name).and_then(|n| n.as_u64()).unwrap_or(0);
+        Some(RecentChange {
+            kind: ChangeType::from_api(c.get("type")?.as_str()?)?,
+            title: c.get("title")?.as_str()?.into(),
+            ns: c.get("ns").and_then(|n| n.as_i64()).unwrap_or(0),
+            timestamp: string("timestamp"),
+            user: string("user"),
+            comment: string("comment"),
+            old_len: number("oldlen"),
+            new_len: number("newlen"),
+            revid: number("revid"),
+            old_revid: number("old_revid"),
+        })
+    }
+}
+
+/// A change to a watched page, see `Wikipedia::watchlist`.
+#[derive(Debug, PartialEq)]
+pub struct WatchlistChange(pub RecentChange);
+
+impl ListItem for WatchlistChange {
+    const LIST: &'static str = "watchlist";
+
+    fn from_value(value: &Value) -> Option<WatchlistChange> {
+        RecentChange::from_value(value).map(WatchlistChange)
+    }
+}
+
+/// Breadth-first iterator over the members of a category and its
+/// subcategories, see `Wikipedia::category_members_recursive`.
+pub struct CategoryTraversal<'a, A: 'a + http::HttpClient> {
+    wikipedia: &'a Wikipedia<A>,
+    max_depth: u8,
+    remaining: usize,
+    /// Categories left to list, with their depth
+    queue: VecDeque<(String, u8)>,
+    current: Option<(ListIter<'a, A, CategoryMember>, u8)>,
+    seen: HashSet<String>,
+}
+
+impl<'a, A: http::HttpClient> CategoryTraversal<'a, A> {
+    pub fn new(
+        wikipedia: &'a Wikipedia<A>,
+        category: &str,
+        max_depth: u8,
+        max_pages: usize,
+    ) -> CategoryTraversal<'a, A> {
+        let category = if category.starts_with("Category:") {
+            category.to_owned()
+        } else {
+            format!("Category:{}", category)
+        };
+        let mut seen = HashSet::new();
+        seen.insert(category.clone());
+        let mut queue = VecDeque::new();
+        queue.push_back((category, 0));
+        CategoryTraversal {
+            wikipedia,
+            max_depth,
+            remaining: max_pages,
+            queue,
+            current: None,
+            seen,
+        }
+    }
+}
+
+impl<A: http::HttpClient> Iterator for CategoryTraversal<'_, A> {
+    type Item = CategoryMember;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            if self.current.is_none() {
+                let (category, depth) = self.queue.pop_front()?;
+                // categories which fail to load are skipped
+                if let Ok(members) = self.wikipedia.category_members(&category) {
+                    self.current = Some((members, depth));
+                }
+                continue;
+            }
+            let (members, depth) = self.current.as_mut()?;
+            let member = match members.next() {
+                Some(m) => m,
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+            if !self.seen.insert(member.title.clone()) {
+                continue;
+            }
+            if member.kind == CategoryMemberType::Subcat && *depth < self.max_depth {
+                self.queue.push_back((member.title.clone(), *depth + 1));
+            }
+            self.remaining -= 1;
+            return Some(member);
+        }
+        None
     }
 }
@@ -11,21 +11,36 @@
 //! ```
 #[cfg(feature = "http-client")]
 extern crate reqwest;
+extern crate serde;
 extern crate serde_json;
 extern crate thiserror;
 #[cfg(feature = "http-client")]
 extern crate url;
 
 use std::cmp::PartialEq;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::io;
+use std::ops::Deref;
 use std::result;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
+mod html;
 pub mod http;
 pub mod iter;
-pub use iter::{Iter, IterElems};
+mod languages;
+#[cfg(feature = "rest")]
+pub mod rest;
+mod wikitext;
+pub use iter::{Iter, IterElems, IterOptions, ListIter, TryIter, TryListIter};
 
 const LANGUAGE_URL_MARKER: &str = "{language}";
 
+/// Parameters holding passwords or tokens, masked in `RequestInfo`.
+const SECRET_PARAMS: &[&str] = &["lgpassword", "lgtoken", "token"];
+
 macro_rules! results {
     ($data: expr, $query_field: expr) => {
         // There has to be a better way to write the following code
@@ -35,7 +50,7 @@ macro_rules! results {
             .and_then(|x| x.as_object())
             .and_then(|x| x.get($query_field))
             .and_then(|x| x.as_array())
-            .ok_or(Error::JSONPathError)?
+            .ok_or(Error::JSONPathError(concat!("query.", $query_field)))?
             .into_iter()
             .filter_map(|i| {
                 i.as_object()
@@ -47,20 +62,19 @@ macro_rules! results {
 }
 
 macro_rules! cont {
-    ($this: expr, $cont: expr, $($params: expr),*) => {{
+    ($this: expr, $cont: expr, $($params: expr),*) => {
+        cont!($this, $cont, $($params),*; Vec::<(&str, &str)>::new())
+    };
+    ($this: expr, $cont: expr, $($params: expr),*; $extra: expr) => {{
         let qp = $this.identifier.query_param();
-        let mut params = vec![
-            $($params),*,
+        let mut params = vec![$($params),*];
+        params.extend($extra);
+        params.extend([
             ("format", "json"),
             ("action", "query"),
             (&*qp.0, &*qp.1),
-        ];
-        match *$cont {
-            Some(ref v) => {
-                for x in v.iter() { params.push((&*x.0, &*x.1)); }
-            },
-            None => params.push(("continue", "")),
-        }
+        ]);
+        push_continue(&mut params, $cont);
         let q = $this.wikipedia.query(params.into_iter())?;
 
         let pages = q
@@ -69,7 +83,7 @@ macro_rules! cont {
             .and_then(|x| x.as_object())
             .and_then(|x| x.get("pages"))
             .and_then(|x| x.as_object())
-            .ok_or(Error::JSONPathError)?;
+            .ok_or(Error::JSONPathError("query.pages"))?;
 
         Ok((pages.values().cloned().collect(), $this.parse_cont(&q)?))
     }}
@@ -78,1119 +92,9895 @@ macro_rules! cont {
 /// Wikipedia failed to fetch some information
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    /// Malformed URL
-    #[error("URL Error")]
-    URLError,
-    /// Some error communicating with the server
+    /// Malformed URL, with the parse error if there is one
+    #[error("URL Error: {url}")]
+    URLError {
+        url: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error>>,
+    },
+    /// Some error communicating with the server, given as the source
     #[error("HTTP Error")]
     HTTPError(#[from] Box<dyn std::error::Error>),
+    /// The server answered with an error status, with the start of the
+    /// response body, possibly asking to retry after some time
+    #[error("HTTP Status {status}: {body_snippet}")]
+    Http {
+        status: u16,
+        body_snippet: String,
+        retry_after: Option<std::time::Duration>,
+    },
+    /// The server asked to slow down, with an HTTP 429 status or the
+    /// `ratelimited` API error, possibly saying for how long
+    #[error("Rate Limited")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+    },
+    /// The request kept failing after the given number of attempts
+    #[error("Failed after {attempts} attempts: {source}")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+    /// The server didn't answer within the configured timeout
+    #[error("Timeout")]
+    Timeout,
     /// Error reading response
     #[error("IO Error: {0}")]
     IOError(#[from] io::Error),
     /// Failed to parse JSON response
     #[error("JSON Error: {0}")]
     JSONError(#[from] serde_json::error::Error),
-    /// Missing required keys in the JSON response
-    #[error("JSON Path Error")]
-    JSONPathError,
+    /// Missing required keys in the JSON response, with the path that was
+    /// looked up (e.g. `query.pages[].title`, where `[]` is any element)
+    #[error("JSON Path Error: {0}")]
+    JSONPathError(&'static str),
     /// One of the parameters provided (identified by `String`) is invalid
     #[error("Invalid Parameter: {0}")]
     InvalidParameter(String),
+    /// The requested page does not exist
+    #[error("Page Not Found: {title}")]
+    PageNotFound { title: String },
+    /// The revision does not exist or belongs to another page
+    #[error("Bad Revision Id: {0}")]
+    BadRevisionId(u64),
+    /// The page has no section with the given index
+    #[error("Section Not Found: {0}")]
+    SectionNotFound(String),
+    /// The API answered with an error object
+    #[error("API Error: {code}: {info}")]
+    Api { code: String, info: String },
+    /// The page is a disambiguation page, listing the candidate titles
+    #[error("Disambiguation Page: {}", .0.join(", "))]
+    Disambiguation(Vec<String>),
+    /// The wiki refused to log in, with the reason it gave
+    #[error("Login Failed: {0}")]
+    LoginFailed(String),
+    /// The page was edited since the revision an edit is based on
+    #[error("Edit Conflict")]
+    EditConflict,
+    /// The csrf token was refused, even after fetching a new one
+    #[error("Bad Token")]
+    BadToken,
+    /// A change or a listing needing a login was attempted without one
+    #[error("Not Logged In")]
+    NotLoggedIn,
 }
 
 pub type Result<T> = result::Result<T, Error>;
 
-#[derive(Debug)]
-pub struct Wikipedia<A: http::HttpClient> {
-    /// HttpClient struct.
-    pub client: A,
-    /// Url is created by concatenating `pre_language_url` + `language` + `post_language_url`.
-    pub pre_language_url: String,
-    pub post_language_url: String,
-    pub language: String,
-    /// Number of results to fetch when searching.
-    pub search_results: u32,
-    /// Number of images to fetch in each request when calling `get_images`.
-    /// The iterator will go through all of them, fetching pages of this size.
-    /// It can be the string "max" to fetch as many as possible on every request.
-    pub images_results: String,
-    /// Like `images_results`, for links and references.
-    pub links_results: String,
-    /// Like `images_results`, for categories.
-    pub categories_results: String,
+/// A search hit including the metadata returned by the API.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SearchResult {
+    /// The page title.
+    pub title: String,
+    /// Raw HTML snippet of the matching text, matches are wrapped in
+    /// `<span class="searchmatch">`.
+    #[serde(default)]
+    pub snippet: String,
+    /// Number of words in the page.
+    #[serde(rename = "wordcount", default)]
+    pub word_count: u64,
+    /// Size of the page in bytes.
+    #[serde(rename = "size", default)]
+    pub size_bytes: u64,
+    /// Timestamp of the last edit, e.g. `2024-01-01T00:00:00Z`.
+    #[serde(default)]
+    pub timestamp: String,
 }
 
-impl<A: http::HttpClient + Default> Default for Wikipedia<A> {
-    fn default() -> Self {
-        Wikipedia::new(A::default())
-    }
+/// A page of results along with the offset to request the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchPage {
+    /// Page titles in this batch.
+    pub results: Vec<String>,
+    /// Offset to pass to get the following batch, `None` if there are no
+    /// more results.
+    pub next_offset: Option<u32>,
 }
 
-impl<A: http::HttpClient + Clone> Clone for Wikipedia<A> {
-    fn clone(&self) -> Self {
-        Wikipedia {
-            client: self.client.clone(),
-            pre_language_url: self.pre_language_url.clone(),
-            post_language_url: self.post_language_url.clone(),
-            language: self.language.clone(),
-            search_results: self.search_results,
-            images_results: self.images_results.clone(),
-            links_results: self.links_results.clone(),
-            categories_results: self.categories_results.clone(),
+/// What a search is matched against (`srwhat`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Full text search, the API default.
+    Text,
+    /// Search titles only.
+    Title,
+    /// Exact title match, returns at most one result.
+    NearMatch,
+}
+
+impl SearchMode {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SearchMode::Text => "text",
+            SearchMode::Title => "title",
+            SearchMode::NearMatch => "nearmatch",
         }
     }
 }
 
-impl<A: http::HttpClient> Wikipedia<A> {
-    /// Creates a new object using the provided client and default values.
-    pub fn new(client: A) -> Self {
-        Wikipedia {
-            client,
-            pre_language_url: "https://".to_owned(),
-            post_language_url: ".wikipedia.org/w/api.php".to_owned(),
-            language: "en".to_owned(),
-            search_results: 10,
-            images_results: "max".to_owned(),
-            links_results: "max".to_owned(),
-            categories_results: "max".to_owned(),
+/// Order of search results (`srsort`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchSort {
+    /// Sort by relevance, the API default.
+    Relevance,
+    CreateTimestampAsc,
+    CreateTimestampDesc,
+    IncomingLinksAsc,
+    IncomingLinksDesc,
+    JustMatch,
+    LastEditAsc,
+    LastEditDesc,
+    None,
+    Random,
+    UserRandom,
+}
+
+impl SearchSort {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SearchSort::Relevance => "relevance",
+            SearchSort::CreateTimestampAsc => "create_timestamp_asc",
+            SearchSort::CreateTimestampDesc => "create_timestamp_desc",
+            SearchSort::IncomingLinksAsc => "incoming_links_asc",
+            SearchSort::IncomingLinksDesc => "incoming_links_desc",
+            SearchSort::JustMatch => "just_match",
+            SearchSort::LastEditAsc => "last_edit_asc",
+            SearchSort::LastEditDesc => "last_edit_desc",
+            SearchSort::None => "none",
+            SearchSort::Random => "random",
+            SearchSort::UserRandom => "user_random",
         }
     }
+}
 
-    /// Returns a list of languages in the form of (`identifier`, `language`),
-    /// for example [("en", "English"), ("es", "Español")]
-    pub fn get_languages(&self) -> Result<IterElems> {
-        let q = self.query(
-            vec![
-                ("meta", "siteinfo"),
-                ("siprop", "languages"),
-                ("format", "json"),
-                ("action", "query"),
-            ]
-            .into_iter(),
-        )?;
+/// Parameters for `Wikipedia::search_opts`. `None` and empty values leave
+/// the API defaults in place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchOptions {
+    /// Namespaces to search in.
+    pub namespaces: Vec<u32>,
+    /// What to match the query against.
+    pub mode: Option<SearchMode>,
+    /// How to sort the results.
+    pub sort: Option<SearchSort>,
+    /// Number of results to skip.
+    pub offset: Option<u32>,
+}
 
-        Ok(q.as_object()
-            .and_then(|x| x.get("query"))
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("languages"))
-            .and_then(|x| x.as_array())
-            .ok_or(Error::JSONPathError)?
-            .iter()
-            .filter_map(|x| {
-                let o = x.as_object();
-                Some((
-                    match o
-                        .and_then(|x| x.get("code"))
-                        .and_then(|x| x.as_str())
-                        .map(|x| x.to_owned())
-                    {
-                        Some(v) => v,
-                        None => return None,
-                    },
-                    match o
-                        .and_then(|x| x.get("*"))
-                        .and_then(|x| x.as_str())
-                        .map(|x| x.to_owned())
-                    {
-                        Some(v) => v,
-                        None => return None,
-                    },
-                ))
-            })
-            .collect())
-    }
+/// Parameters for `Page::edit`. The defaults replace the whole page,
+/// creating it if needed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditOptions {
+    /// Marks the edit as a bot edit, if the user has the `bot` right.
+    pub bot: bool,
+    pub minor: bool,
+    /// Replaces only the section with this index, or adds a section with
+    /// `new`.
+    pub section: Option<String>,
+    /// Heading of the section added with `new`, which defaults to the
+    /// summary.
+    pub section_title: Option<String>,
+    /// Fails with `Error::Api` if the page already exists.
+    pub create_only: bool,
+    /// Fails with `Error::Api` if the page doesn't exist.
+    pub no_create: bool,
+    /// Timestamp of the revision the edit is based on, so an edit made in
+    /// the meantime fails with `Error::EditConflict`.
+    pub base_timestamp: Option<String>,
+    /// Fails with `Error::NotLoggedIn` instead of editing anonymously if
+    /// the session was lost.
+    pub require_login: bool,
+}
 
-    /// Returns the api url
-    pub fn base_url(&self) -> String {
-        format!(
-            "{}{}{}",
-            self.pre_language_url, self.language, self.post_language_url
-        )
-    }
+/// A saved edit, see `Page::edit`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditResult {
+    pub pageid: u64,
+    pub title: String,
+    /// Unset if the page was created.
+    pub old_revid: Option<u64>,
+    /// Unset if the text didn't change anything.
+    pub new_revid: Option<u64>,
+    pub new_timestamp: Option<String>,
+    /// Whether the text was the same as the current one, so no revision
+    /// was saved.
+    pub no_change: bool,
+}
 
-    /// Updates the url format. The substring `{language}` will be replaced
-    /// with the selected language.
-    pub fn set_base_url(&mut self, base_url: &str) {
-        let index = match base_url.find(LANGUAGE_URL_MARKER) {
-            Some(i) => i,
-            None => {
-                self.pre_language_url = base_url.to_owned();
-                self.language = "".to_owned();
-                self.post_language_url = "".to_owned();
-                return;
-            }
-        };
-        self.pre_language_url = base_url[0..index].to_owned();
-        self.post_language_url = base_url[index + LANGUAGE_URL_MARKER.len()..].to_owned();
-    }
+/// Why an upload wasn't saved, see `Wikipedia::upload`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadWarning {
+    /// A file with the same name exists.
+    Exists(String),
+    /// Files with the same content exist, with these names.
+    Duplicate(Vec<String>),
+    /// Another warning, like `badfilename` or `was-deleted`, with its
+    /// value as JSON.
+    Other { code: String, value: String },
+}
 
-    fn query<'a, I>(&self, args: I) -> Result<serde_json::Value>
-    where
-        I: Iterator<Item = (&'a str, &'a str)>,
-    {
-        let response_str = self.client.get(&self.base_url(), args)?;
-        let json = serde_json::from_str(&response_str).map_err(Error::JSONError)?;
-        Ok(json)
-    }
+/// Outcome of `Wikipedia::upload`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadResult {
+    /// The file was saved, under `filename` as normalized by the wiki.
+    Success {
+        filename: String,
+        url: Option<String>,
+    },
+    /// The file wasn't saved. It can be saved anyway with
+    /// `Wikipedia::upload_ignoring_warnings`.
+    Warnings(Vec<UploadWarning>),
+}
 
-    /// Searches for a string and returns a list of relevant page titles.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// extern crate wikipedia;
-    ///
-    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
-    /// let results = wiki.search("keyboard").unwrap();
-    /// assert!(results.contains(&"Computer keyboard".to_owned()));
-    /// ```
-    pub fn search(&self, query: &str) -> Result<Vec<String>> {
-        let results = &*format!("{}", self.search_results);
-        let data = self.query(
-            vec![
-                ("list", "search"),
-                ("srprop", ""),
-                ("srlimit", results),
-                ("srsearch", query),
-                ("format", "json"),
-                ("action", "query"),
-            ]
-            .into_iter(),
-        )?;
+/// A page found by a geosearch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoSearchResult {
+    pub title: String,
+    pub pageid: u64,
+    pub lat: f64,
+    pub lon: f64,
+    /// Distance to the searched point in meters.
+    pub dist: f64,
+    /// Whether these are the primary coordinates of the page.
+    pub primary: bool,
+    /// Type of the object, e.g. `city` or `landmark`.
+    pub kind: Option<String>,
+    /// Approximate size of the object in meters.
+    pub dim: Option<u64>,
+}
 
-        Ok(results!(data, "search"))
-    }
+/// Parameters for `Wikipedia::geosearch_opts`. `None` and empty values
+/// keep the defaults used by `geosearch`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoSearchOptions {
+    /// Namespaces to search in, e.g. 6 for files or 2 for user pages.
+    pub namespaces: Vec<u32>,
+    /// Number of results to fetch instead of `search_results`, between 1
+    /// and 500.
+    pub limit: Option<u32>,
+}
 
-    /// Search articles within `radius` meters of `latitude` and `longitude`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// extern crate wikipedia;
-    ///
-    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
-    /// let results = wiki.geosearch(40.750556,-73.993611, 20).unwrap();
-    /// assert!(results.contains(&"Madison Square Garden".to_owned()));
-    /// ```
-    pub fn geosearch(&self, latitude: f64, longitude: f64, radius: u16) -> Result<Vec<String>> {
-        #![allow(clippy::manual_range_contains)]
+/// Whether listings include redirect pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectFilter {
+    All,
+    Redirects,
+    NonRedirects,
+}
 
-        if latitude < -90.0 || latitude > 90.0 {
-            return Err(Error::InvalidParameter("latitude".to_string()));
-        }
-        if longitude < -180.0 || longitude > 180.0 {
-            return Err(Error::InvalidParameter("longitude".to_string()));
-        }
-        if radius < 10 || radius > 10000 {
-            return Err(Error::InvalidParameter("radius".to_string()));
+impl RedirectFilter {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            RedirectFilter::All => "all",
+            RedirectFilter::Redirects => "redirects",
+            RedirectFilter::NonRedirects => "nonredirects",
         }
-        let results = &*format!("{}", self.search_results);
-        let data = self.query(
-            vec![
-                ("list", "geosearch"),
-                ("gsradius", &*format!("{}", radius)),
-                ("gscoord", &*format!("{}|{}", latitude, longitude)),
-                ("gslimit", results),
-                ("format", "json"),
-                ("action", "query"),
-            ]
-            .into_iter(),
-        )?;
-        Ok(results!(data, "geosearch"))
-    }
-
-    /// Fetches `count` random articles' title.
-    pub fn random_count(&self, count: u8) -> Result<Vec<String>> {
-        let data = self.query(
-            vec![
-                ("list", "random"),
-                ("rnnamespace", "0"),
-                ("rnlimit", &*format!("{}", count)),
-                ("format", "json"),
-                ("action", "query"),
-            ]
-            .into_iter(),
-        )?;
-        let r: Vec<String> = results!(data, "random");
-        Ok(r)
     }
+}
 
-    /// Fetches a random article's title.
-    pub fn random(&self) -> Result<Option<String>> {
-        Ok(self.random_count(1)?.into_iter().next())
-    }
+/// Kind of a category member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CategoryMemberType {
+    Page,
+    Subcat,
+    File,
+}
 
-    /// Creates a new `Page` given a `title`.
-    pub fn page_from_title(&self, title: String) -> Page<'_, A> {
-        Page::from_title(self, title)
+impl CategoryMemberType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            CategoryMemberType::Page => "page",
+            CategoryMemberType::Subcat => "subcat",
+            CategoryMemberType::File => "file",
+        }
     }
 
-    /// Creates a new `Page` given a `pageid`.
-    pub fn page_from_pageid(&self, pageid: String) -> Page<'_, A> {
-        Page::from_pageid(self, pageid)
+    fn from_api(kind: &str) -> Option<CategoryMemberType> {
+        match kind {
+            "page" => Some(CategoryMemberType::Page),
+            "subcat" => Some(CategoryMemberType::Subcat),
+            "file" => Some(CategoryMemberType::File),
+            _ => None,
+        }
     }
 }
 
-#[derive(Debug)]
-enum TitlePageId {
-    Title(String),
-    PageId(String),
+/// Parameters for `Wikipedia::category_members_opts`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategoryMembersOptions {
+    /// Kinds of members to list, all if empty.
+    pub types: Vec<CategoryMemberType>,
+    /// List members by the time they were added to the category instead of
+    /// by sort key.
+    pub sort_by_timestamp: bool,
 }
 
-impl TitlePageId {
-    fn query_param(&self) -> (String, String) {
-        match *self {
-            TitlePageId::Title(ref s) => ("titles".to_owned(), s.clone()),
-            TitlePageId::PageId(ref s) => ("pageids".to_owned(), s.clone()),
-        }
-    }
+/// Parameters for `Wikipedia::all_pages_opts`. `None` values list all
+/// pages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AllPagesOptions {
+    pub redirects: Option<RedirectFilter>,
+    /// Minimum page size in bytes.
+    pub min_size: Option<u32>,
+    /// Maximum page size in bytes.
+    pub max_size: Option<u32>,
 }
 
-#[derive(Debug)]
-pub struct Page<'a, A: 'a + http::HttpClient> {
-    wikipedia: &'a Wikipedia<A>,
-    identifier: TitlePageId,
+/// Kind of an entry in the recent changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeType {
+    Edit,
+    New,
+    Log,
+    Categorize,
+    External,
 }
 
-/// A wikipedia article.
-impl<'a, A: http::HttpClient> Page<'a, A> {
-    /// Creates a new `Page` given a `title`.
-    pub fn from_title(wikipedia: &'a Wikipedia<A>, title: String) -> Page<'a, A> {
-        Page {
-            wikipedia,
-            identifier: TitlePageId::Title(title),
+impl ChangeType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ChangeType::Edit => "edit",
+            ChangeType::New => "new",
+            ChangeType::Log => "log",
+            ChangeType::Categorize => "categorize",
+            ChangeType::External => "external",
         }
     }
 
-    /// Creates a new `Page` given a `pageid`.
-    pub fn from_pageid(wikipedia: &'a Wikipedia<A>, pageid: String) -> Page<'a, A> {
-        Page {
-            wikipedia,
-            identifier: TitlePageId::PageId(pageid),
+    fn from_api(kind: &str) -> Option<ChangeType> {
+        match kind {
+            "edit" => Some(ChangeType::Edit),
+            "new" => Some(ChangeType::New),
+            "log" => Some(ChangeType::Log),
+            "categorize" => Some(ChangeType::Categorize),
+            "external" => Some(ChangeType::External),
+            _ => None,
         }
     }
+}
 
-    /// Gets the `Page`'s `pageid`.
-    pub fn get_pageid(&self) -> Result<String> {
-        match self.identifier {
-            TitlePageId::PageId(ref s) => Ok(s.clone()),
-            TitlePageId::Title(_) => {
-                let qp = self.identifier.query_param();
-                let q = self.wikipedia.query(
-                    vec![
-                        ("prop", "info|pageprops"),
-                        ("inprop", "url"),
-                        ("ppprop", "disambiguation"),
-                        ("redirects", ""),
-                        ("format", "json"),
-                        ("action", "query"),
-                        (&*qp.0, &*qp.1),
-                    ]
-                    .into_iter(),
-                )?;
+/// Parameters for `Wikipedia::recent_changes` and `Wikipedia::watchlist`.
+/// `None` and empty values list everything, newest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecentChangesOptions {
+    pub namespaces: Vec<u32>,
+    /// Filters like `!bot|!minor` or `anon`, joined with `|`.
+    pub show: Option<String>,
+    pub types: Vec<ChangeType>,
+    /// Newest timestamp to list, e.g. `2024-01-31T00:00:00Z`.
+    pub start: Option<String>,
+    /// Oldest timestamp to list.
+    pub end: Option<String>,
+}
 
-                if let Some(r) = self.redirect(&q) {
-                    return Page::from_title(self.wikipedia, r).get_pageid();
-                }
-                let pages = q
-                    .as_object()
-                    .and_then(|x| x.get("query"))
-                    .and_then(|x| x.as_object())
-                    .and_then(|x| x.get("pages"))
-                    .and_then(|x| x.as_object())
-                    .ok_or(Error::JSONPathError)?;
-                pages.keys().next().cloned().ok_or(Error::JSONPathError)
-            }
+/// An edit by a user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+    pub title: String,
+    pub ns: i64,
+    pub revid: u64,
+    pub parentid: u64,
+    pub timestamp: String,
+    pub comment: String,
+    /// Size of the page in bytes after the edit.
+    pub size: u64,
+    /// Change of the page size in bytes.
+    pub size_diff: i64,
+}
+
+/// A location marked on a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coordinate {
+    pub lat: f64,
+    pub lon: f64,
+    /// Whether this is the location of the article's subject.
+    pub primary: bool,
+    /// Kind of object, like `city` or `landmark`, if given.
+    pub kind: Option<String>,
+    /// Name of the location, if given.
+    pub name: Option<String>,
+    /// Approximate size of the object in meters, if given.
+    pub dim: Option<u64>,
+    /// Body the coordinates refer to, `earth` for most pages.
+    pub globe: String,
+}
+
+/// A namespace of the wiki.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Namespace {
+    pub id: i64,
+    /// Name in the language of the wiki, empty for the main namespace.
+    pub name: String,
+    /// English name, `None` for the main namespace.
+    pub canonical: Option<String>,
+    /// Whether pages in this namespace count as articles.
+    pub content: bool,
+}
+
+/// Number of members of a category by kind.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategoryInfo {
+    pub pages: u64,
+    pub subcats: u64,
+    pub files: u64,
+}
+
+/// Counters of the wiki.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SiteStatistics {
+    pub pages: u64,
+    pub articles: u64,
+    pub edits: u64,
+    pub images: u64,
+    pub users: u64,
+    /// Users who edited in the last 30 days.
+    pub active_users: u64,
+    pub admins: u64,
+}
+
+/// General information about the wiki.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteInfo {
+    pub sitename: String,
+    pub main_page: String,
+    /// MediaWiki version, e.g. `MediaWiki 1.41.0-wmf.1`.
+    pub generator: String,
+    /// Url of the main page.
+    pub base: String,
+    pub server: String,
+    /// Path of articles with `$1` in place of the title, e.g. `/wiki/$1`.
+    pub article_path: String,
+    pub lang: String,
+    pub namespaces: Vec<Namespace>,
+    pub statistics: SiteStatistics,
+}
+
+/// The user requests are made as, see `Wikipedia::whoami`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserInfo {
+    /// Zero for anonymous users.
+    pub id: u64,
+    /// The user name, or the IP address for anonymous users.
+    pub name: String,
+    pub anonymous: bool,
+    /// Groups like `user` or `bot`.
+    pub groups: Vec<String>,
+    /// Rights like `edit` or `highlimits`, as granted to the groups and
+    /// the OAuth token or bot password.
+    pub rights: Vec<String>,
+}
+
+/// A suggestion returned by the OpenSearch endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenSearchResult {
+    pub title: String,
+    /// Short description, often empty on Wikimedia wikis.
+    pub description: String,
+    pub url: String,
+}
+
+/// Reads a numeric offset (such as `sroffset`) from the `continue` object
+/// of a response.
+fn continue_offset(data: &serde_json::Value, key: &str) -> Option<u32> {
+    data.as_object()
+        .and_then(|x| x.get("continue"))
+        .and_then(|x| x.as_object())
+        .and_then(|x| x.get(key))
+        .and_then(|x| x.as_u64())
+        .map(|x| x as u32)
+}
+
+/// Quotes a value to be used after a CirrusSearch keyword such as
+/// `morelike:` or `incategory:`, escaping backslashes and quotes.
+fn quote_search_value(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
         }
+        quoted.push(c);
     }
+    quoted.push('"');
+    quoted
+}
 
-    /// Gets the `Page`'s `title`.
-    pub fn get_title(&self) -> Result<String> {
-        match self.identifier {
-            TitlePageId::Title(ref s) => Ok(s.clone()),
-            TitlePageId::PageId(_) => {
-                let qp = self.identifier.query_param();
-                let q = self.wikipedia.query(
-                    vec![
-                        ("prop", "info|pageprops"),
-                        ("inprop", "url"),
-                        ("ppprop", "disambiguation"),
-                        ("redirects", ""),
-                        ("format", "json"),
-                        ("action", "query"),
-                        (&*qp.0, &*qp.1),
-                    ]
-                    .into_iter(),
-                )?;
+/// Adds the continuation parameters `cont` of the previous batch to
+/// `params`, or `continue=` to start a query if there was none.
+fn push_continue<'a>(params: &mut Vec<(&'a str, &'a str)>, cont: &'a Option<IterElems>) {
+    match *cont {
+        Some(ref v) => params.extend(v.iter().map(|x| (&*x.0, &*x.1))),
+        None => params.push(("continue", "")),
+    }
+}
 
-                if let Some(r) = self.redirect(&q) {
-                    return Ok(r);
-                }
-                let pages = q
-                    .as_object()
-                    .and_then(|x| x.get("query"))
-                    .and_then(|x| x.as_object())
-                    .and_then(|x| x.get("pages"))
-                    .and_then(|x| x.as_object())
-                    .ok_or(Error::JSONPathError)?;
-                let page = match pages.values().next() {
-                    Some(p) => p,
-                    None => return Err(Error::JSONPathError),
-                };
-                Ok(page
-                    .as_object()
-                    .and_then(|x| x.get("title"))
-                    .and_then(|x| x.as_str())
-                    .ok_or(Error::JSONPathError)?
-                    .to_owned())
-            }
-        }
+/// Receive a json object and extracts any `continue` parameters to be
+/// used when browsing following pages.
+fn parse_continue(q: &serde_json::Value) -> Result<Option<IterElems>> {
+    let cont = match q
+        .as_object()
+        .and_then(|x| x.get("continue"))
+        .and_then(|x| x.as_object())
+    {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let mut cont_v = vec![];
+    for (k, v) in cont.into_iter() {
+        let value = match *v {
+            serde_json::Value::Null => "".to_owned(),
+            serde_json::Value::Bool(b) => if b { "1" } else { "0" }.to_owned(),
+            serde_json::Value::Number(ref f) => format!("{}", f),
+            serde_json::Value::String(ref s) => s.clone(),
+            _ => return Err(Error::JSONPathError("continue")),
+        };
+        cont_v.push((k.clone(), value));
     }
+    Ok(Some(cont_v))
+}
 
-    /// If the `Page` redirects to another one it returns its title, otherwise
-    /// returns None.
-    fn redirect(&self, q: &serde_json::Value) -> Option<String> {
-        q.as_object()
-            .and_then(|x| x.get("query"))
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("redirects"))
-            .and_then(|x| x.as_array())
-            .and_then(|x| x.iter().next())
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("to"))
-            .and_then(|x| x.as_str())
-            .map(|x| x.to_owned())
+/// Joins the values of a multi-value parameter. Values containing `|` are
+/// joined with the alternative separator `U+001F`, which the API accepts
+/// when the parameter starts with it.
+fn join_values(values: &[&str]) -> String {
+    if values.iter().any(|x| x.contains('|')) {
+        values
+            .iter()
+            .fold(String::new(), |acc, x| acc + "\u{1f}" + x)
+    } else {
+        values.join("|")
     }
+}
 
-    /// Given a parsed response, usually we access the first page with the data
-    fn get_first_page<'parsed>(
-        &self,
-        data: &'parsed serde_json::Value,
-    ) -> Option<&'parsed serde_json::Value> {
-        let pages = data
-            .as_object()
-            .and_then(|x| x.get("query"))
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("pages"))
-            .and_then(|x| x.as_object());
-        let pageid = pages?.keys().next()?;
-        pages.unwrap().get(pageid)
+/// Parameters of `list=recentchanges` or `list=watchlist`, named with
+/// `prefix`.
+fn change_params(
+    prefix: &str,
+    limit: Limit,
+    options: RecentChangesOptions,
+) -> Vec<(String, String)> {
+    let mut params = vec![
+        (
+            format!("{}prop", prefix),
+            "title|ids|sizes|timestamp|user|comment".to_owned(),
+        ),
+        (format!("{}limit", prefix), limit.to_string()),
+    ];
+    if !options.namespaces.is_empty() {
+        params.push((
+            format!("{}namespace", prefix),
+            join_namespaces(&options.namespaces),
+        ));
+    }
+    if let Some(show) = options.show {
+        params.push((format!("{}show", prefix), show));
+    }
+    if !options.types.is_empty() {
+        let types: Vec<_> = options.types.iter().map(|x| x.as_str()).collect();
+        params.push((format!("{}type", prefix), types.join("|")));
+    }
+    if let Some(start) = options.start {
+        params.push((format!("{}start", prefix), start));
     }
+    if let Some(end) = options.end {
+        params.push((format!("{}end", prefix), end));
+    }
+    params
+}
 
-    /// Gets the markdown content of the article.
-    pub fn get_content(&self) -> Result<String> {
-        let qp = self.identifier.query_param();
-        let q = self.wikipedia.query(
-            vec![
-                ("prop", "extracts|revisions"),
-                ("explaintext", ""),
-                ("rvprop", "ids"),
-                ("redirects", ""),
-                ("format", "json"),
-                ("action", "query"),
-                (&*qp.0, &*qp.1),
-            ]
-            .into_iter(),
-        )?;
+/// The pages of a `query.pages` response, merged over all continuations.
+struct PageBatch {
+    pages: BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+    normalized: HashMap<String, String>,
+    redirects: HashMap<String, String>,
+    /// Titles which link to other wikis.
+    interwiki: HashSet<String>,
+}
 
-        if let Some(r) = self.redirect(&q) {
-            return Page::from_title(self.wikipedia, r).get_content();
-        }
+impl PageBatch {
+    /// Applies the normalization and redirects the API reported to a
+    /// requested title.
+    fn resolve<'a>(&'a self, title: &'a str) -> &'a str {
+        let title = self.normalized.get(title).map_or(title, |x| &**x);
+        self.redirects.get(title).map_or(title, |x| &**x)
+    }
+}
 
-        Ok(self
-            .get_first_page(&q)
+/// Parses a page object of a `prop=extracts|info|pageprops` response,
+/// `None` for missing and invalid titles.
+fn parse_page_data(page: &serde_json::Map<String, serde_json::Value>) -> Option<PageData> {
+    if page.contains_key("missing") || page.contains_key("invalid") {
+        return None;
+    }
+    Some(PageData {
+        pageid: page.get("pageid")?.as_u64()?,
+        title: page.get("title")?.as_str()?.to_owned(),
+        ns: page.get("ns").and_then(|x| x.as_i64()).unwrap_or(0),
+        extract: page
+            .get("extract")
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()),
+        length: page.get("length").and_then(|x| x.as_u64()).unwrap_or(0),
+        touched: page
+            .get("touched")
+            .and_then(|x| x.as_str())
+            .unwrap_or("")
+            .to_owned(),
+        pageprops: page
+            .get("pageprops")
             .and_then(|x| x.as_object())
-            .and_then(|x| x.get("extract"))
+            .map(|x| {
+                x.iter()
+                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        requested: Vec::new(),
+    })
+}
+
+/// Parses an entry of the `usercontribs` list.
+fn parse_contribution(value: &serde_json::Value) -> Option<Contribution> {
+    let o = value.as_object()?;
+    let number = |name| o.get(name).and_then(|x| x.as_u64()).unwrap_or(0);
+    Some(Contribution {
+        title: o.get("title")?.as_str()?.to_owned(),
+        ns: o.get("ns").and_then(|x| x.as_i64()).unwrap_or(0),
+        revid: o.get("revid")?.as_u64()?,
+        parentid: number("parentid"),
+        timestamp: o
+            .get("timestamp")
             .and_then(|x| x.as_str())
-            .ok_or(Error::JSONPathError)?
-            .to_owned())
-    }
+            .unwrap_or("")
+            .to_owned(),
+        comment: o
+            .get("comment")
+            .and_then(|x| x.as_str())
+            .unwrap_or("")
+            .to_owned(),
+        size: number("size"),
+        size_diff: o.get("sizediff").and_then(|x| x.as_i64()).unwrap_or(0),
+    })
+}
 
-    /// Gets the html content of the article.
-    pub fn get_html_content(&self) -> Result<String> {
-        let qp = self.identifier.query_param();
-        let q = self.wikipedia.query(
-            vec![
-                ("prop", "revisions"),
-                ("rvprop", "content"),
-                ("rvlimit", "1"),
-                ("rvparse", ""),
-                ("redirects", ""),
-                ("format", "json"),
-                ("action", "query"),
-                (&*qp.0, &*qp.1),
-            ]
-            .into_iter(),
-        )?;
+fn parse_coordinate(value: &serde_json::Value) -> Option<Coordinate> {
+    let o = value.as_object()?;
+    let string = |name| o.get(name).and_then(|x| x.as_str()).map(|x| x.to_owned());
+    Some(Coordinate {
+        lat: o.get("lat")?.as_f64()?,
+        lon: o.get("lon")?.as_f64()?,
+        primary: o.contains_key("primary"),
+        kind: string("type"),
+        name: string("name"),
+        dim: o.get("dim").and_then(|x| x.as_u64()),
+        globe: string("globe").unwrap_or_else(|| "earth".to_owned()),
+    })
+}
 
-        if let Some(r) = self.redirect(&q) {
-            return Page::from_title(self.wikipedia, r).get_html_content();
-        }
+/// Parses the `query.namespaces` object of a `meta=siteinfo` response,
+/// ordered by id.
+fn parse_namespaces(query: &serde_json::Map<String, serde_json::Value>) -> Result<Vec<Namespace>> {
+    let mut namespaces: Vec<Namespace> = query
+        .get("namespaces")
+        .and_then(|x| x.as_object())
+        .ok_or(Error::JSONPathError("query.namespaces"))?
+        .values()
+        .filter_map(|x| {
+            let o = x.as_object()?;
+            Some(Namespace {
+                id: o.get("id")?.as_i64()?,
+                name: o.get("*")?.as_str()?.to_owned(),
+                canonical: o
+                    .get("canonical")
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_owned()),
+                content: o.contains_key("content"),
+            })
+        })
+        .collect();
+    namespaces.sort_by_key(|x| x.id);
+    Ok(namespaces)
+}
 
-        Ok(self
-            .get_first_page(&q)
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("revisions"))
-            .and_then(|x| x.as_array())
-            .and_then(|x| x.iter().next())
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("*"))
+/// Turns an `error` object in a response into `Error::Api`.
+fn check_api_error(data: &serde_json::Value) -> Result<()> {
+    let error = match data
+        .as_object()
+        .and_then(|x| x.get("error"))
+        .and_then(|x| x.as_object())
+    {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+    let field = |name| {
+        error
+            .get(name)
             .and_then(|x| x.as_str())
-            .ok_or(Error::JSONPathError)?
-            .to_owned())
+            .unwrap_or("")
+            .to_owned()
+    };
+    match &*field("code") {
+        "ratelimited" => return Err(Error::RateLimited { retry_after: None }),
+        "notloggedin" | "assertuserfailed" => return Err(Error::NotLoggedIn),
+        _ => {}
     }
+    Err(Error::Api {
+        code: field("code"),
+        info: field("info"),
+    })
+}
 
-    /// Gets a summary of the article.
-    pub fn get_summary(&self) -> Result<String> {
-        let qp = self.identifier.query_param();
-        let q = self.wikipedia.query(
-            vec![
-                ("prop", "extracts"),
-                ("explaintext", ""),
-                ("exintro", ""),
-                ("redirects", ""),
-                ("format", "json"),
-                ("action", "query"),
-                (&*qp.0, &*qp.1),
-            ]
-            .into_iter(),
-        )?;
+/// Length of `args` encoded as a query string.
+fn encoded_len(args: &[(&str, &str)]) -> usize {
+    let len = |s: &str| {
+        s.bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => 1,
+                _ => 3,
+            })
+            .sum::<usize>()
+    };
+    args.iter().map(|(k, v)| len(k) + len(v) + 2).sum()
+}
 
-        if let Some(r) = self.redirect(&q) {
-            return Page::from_title(self.wikipedia, r).get_summary();
-        }
+/// Reads the `warnings` object of a response, in either format version.
+fn api_warnings(data: &serde_json::Value) -> Vec<ApiWarning> {
+    let warnings = match data
+        .as_object()
+        .and_then(|x| x.get("warnings"))
+        .and_then(|x| x.as_object())
+    {
+        Some(w) => w,
+        None => return Vec::new(),
+    };
+    warnings
+        .iter()
+        .filter_map(|(module, warning)| {
+            let text = warning
+                .get("*")
+                .or_else(|| warning.get("warnings"))
+                .and_then(|x| x.as_str())?;
+            Some(ApiWarning {
+                module: module.to_owned(),
+                text: text.to_owned(),
+            })
+        })
+        .collect()
+}
 
-        Ok(self
-            .get_first_page(&q)
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("extract"))
+/// Whether the API refused the request because the servers are lagged.
+fn is_maxlag_error(data: &serde_json::Value) -> bool {
+    data.as_object()
+        .and_then(|x| x.get("error"))
+        .and_then(|x| x.get("code"))
+        .and_then(|x| x.as_str())
+        == Some("maxlag")
+}
+
+/// Parses an entry of the `protection` array returned by `prop=info`.
+fn parse_protection(value: &serde_json::Value) -> Option<Protection> {
+    let o = value.as_object()?;
+    Some(Protection {
+        kind: o.get("type")?.as_str()?.to_owned(),
+        level: o.get("level")?.as_str()?.to_owned(),
+        expiry: o
+            .get("expiry")
             .and_then(|x| x.as_str())
-            .ok_or(Error::JSONPathError)?
-            .to_owned())
+            .unwrap_or("infinity")
+            .to_owned(),
+        cascade: o.contains_key("cascade"),
+        source: o
+            .get("source")
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()),
+    })
+}
+
+/// Parses a page returned by `prop=imageinfo`, `None` if the file is
+/// missing.
+fn parse_image_info(value: &serde_json::Value) -> Option<ImageInfo> {
+    let page = value.as_object()?;
+    let info = page
+        .get("imageinfo")?
+        .as_array()?
+        .iter()
+        .next()?
+        .as_object()?;
+    let string = |name| {
+        info.get(name)
+            .and_then(|x| x.as_str())
+            .unwrap_or("")
+            .to_owned()
+    };
+    let number = |name| info.get(name).and_then(|x| x.as_u64()).unwrap_or(0);
+    let metadata = info
+        .get("extmetadata")
+        .and_then(|x| x.as_object())
+        .map(|x| {
+            x.iter()
+                .filter_map(|(k, v)| {
+                    let value = match v.as_object()?.get("value")? {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    Some((k.clone(), value))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(ImageInfo {
+        title: page.get("title")?.as_str()?.to_owned(),
+        url: string("url"),
+        description_url: string("descriptionurl"),
+        width: number("width"),
+        height: number("height"),
+        size: number("size"),
+        mime: string("mime"),
+        metadata,
+        thumb_url: info
+            .get("thumburl")
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()),
+        thumb_width: info.get("thumbwidth").and_then(|x| x.as_u64()),
+        thumb_height: info.get("thumbheight").and_then(|x| x.as_u64()),
+    })
+}
+
+/// Normalizes a title the way MediaWiki does for comparisons: underscores
+/// become spaces and the first letter is uppercased.
+fn normalize_title(title: &str) -> String {
+    let title = title.replace('_', " ");
+    let mut chars = title.trim().chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
+}
 
-    /// Receive a json object and extracts any `continue` parameters to be
-    /// used when browsing following pages.
-    fn parse_cont(&self, q: &serde_json::Value) -> Result<Option<IterElems>> {
-        let cont = match q
-            .as_object()
-            .and_then(|x| x.get("continue"))
-            .and_then(|x| x.as_object())
-        {
-            Some(v) => v,
-            None => return Ok(None),
-        };
-        let mut cont_v = vec![];
-        for (k, v) in cont.into_iter() {
-            let value = match *v {
-                serde_json::Value::Null => "".to_owned(),
-                serde_json::Value::Bool(b) => if b { "1" } else { "0" }.to_owned(),
-                serde_json::Value::Number(ref f) => format!("{}", f),
-                serde_json::Value::String(ref s) => s.clone(),
-                _ => return Err(Error::JSONPathError),
-            };
-            cont_v.push((k.clone(), value));
+/// Parses an entry of the `sections` array returned by `action=parse`.
+fn parse_section(value: &serde_json::Value) -> Option<Section> {
+    let o = value.as_object()?;
+    // depending on the format version numbers may come as strings
+    let number = |name| {
+        o.get(name).and_then(|x| {
+            x.as_u64()
+                .or_else(|| x.as_str().and_then(|s| s.parse().ok()))
+        })
+    };
+    let string = |name| {
+        o.get(name)
+            .and_then(|x| x.as_str())
+            .unwrap_or("")
+            .to_owned()
+    };
+    Some(Section {
+        toclevel: number("toclevel")? as u32,
+        level: number("level").unwrap_or(0) as u32,
+        index: string("index"),
+        number: string("number"),
+        line: o.get("line")?.as_str()?.to_owned(),
+        anchor: string("anchor"),
+        byteoffset: number("byteoffset"),
+        children: Vec::new(),
+    })
+}
+
+/// Nests a flat list of sections according to their `toclevel`.
+fn build_section_tree<I: Iterator<Item = Section>>(sections: I) -> Vec<Section> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Section> = Vec::new();
+    for section in sections {
+        while stack.last().is_some_and(|x| x.toclevel >= section.toclevel) {
+            let done = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(done),
+                None => roots.push(done),
+            }
         }
-        Ok(Some(cont_v))
+        stack.push(section);
     }
-
-    fn request_images(
-        &self,
-        cont: &Option<IterElems>,
-    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
-        cont!(
-            self,
-            cont,
-            ("generator", "images"),
-            ("gimlimit", &*self.wikipedia.images_results),
-            ("prop", "imageinfo"),
-            ("iiprop", "url")
-        )
+    while let Some(done) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(done),
+            None => roots.push(done),
+        }
     }
+    roots
+}
 
-    /// Creates an iterator to view all images in the `Page`.
-    pub fn get_images(&self) -> Result<Iter<A, iter::Image>> {
-        Iter::new(self)
+/// Parses an entry of the `revisions` array.
+fn parse_revision(value: &serde_json::Value) -> Option<Revision> {
+    let o = value.as_object()?;
+    let string = |name| {
+        o.get(name)
+            .and_then(|x| x.as_str())
+            .unwrap_or("")
+            .to_owned()
+    };
+    Some(Revision {
+        revid: o.get("revid")?.as_u64()?,
+        parentid: o.get("parentid").and_then(|x| x.as_u64()).unwrap_or(0),
+        timestamp: string("timestamp"),
+        user: string("user"),
+        comment: string("comment"),
+        size: o.get("size").and_then(|x| x.as_u64()).unwrap_or(0),
+    })
+}
+
+/// Reads the content of a revision, either from the `main` slot or from
+/// the legacy `*` key used by MediaWiki before 1.32.
+fn revision_content(revision: &serde_json::Value) -> Option<String> {
+    let revision = revision.as_object()?;
+    let main = revision
+        .get("slots")
+        .and_then(|x| x.as_object())
+        .and_then(|x| x.get("main"))
+        .and_then(|x| x.as_object());
+    main.and_then(|x| x.get("*").or_else(|| x.get("content")))
+        .or_else(|| revision.get("*"))
+        .and_then(|x| x.as_str())
+        .map(|x| x.to_owned())
+}
+
+/// Validates the position and radius of a geosearch.
+fn check_geosearch_params(latitude: f64, longitude: f64, radius: u16) -> Result<()> {
+    #![allow(clippy::manual_range_contains)]
+
+    if latitude < -90.0 || latitude > 90.0 {
+        return Err(Error::InvalidParameter("latitude".to_string()));
     }
+    if longitude < -180.0 || longitude > 180.0 {
+        return Err(Error::InvalidParameter("longitude".to_string()));
+    }
+    if radius < 10 || radius > 10000 {
+        return Err(Error::InvalidParameter("radius".to_string()));
+    }
+    Ok(())
+}
 
-    fn request_extlinks(
-        &self,
-        cont: &Option<IterElems>,
-    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
-        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
-            self,
-            cont,
-            ("prop", "extlinks"),
-            ("ellimit", &*self.wikipedia.links_results)
-        );
-        a.map(|(pages, cont)| {
-            let page = match pages.into_iter().next() {
-                Some(p) => p,
-                None => return (Vec::new(), None),
-            };
-            (
-                page.as_object()
-                    .and_then(|x| x.get("extlinks"))
-                    .and_then(|x| x.as_array())
-                    .map(|x| x.to_vec())
-                    .unwrap_or(Vec::new()),
-                cont,
-            )
+/// Parses the `query.geosearch` array of a response.
+fn geosearch_results(data: &serde_json::Value) -> Result<Vec<GeoSearchResult>> {
+    Ok(data
+        .as_object()
+        .and_then(|x| x.get("query"))
+        .and_then(|x| x.as_object())
+        .and_then(|x| x.get("geosearch"))
+        .and_then(|x| x.as_array())
+        .ok_or(Error::JSONPathError("query.geosearch"))?
+        .iter()
+        .filter_map(|x| {
+            let o = x.as_object()?;
+            Some(GeoSearchResult {
+                title: o.get("title")?.as_str()?.to_owned(),
+                pageid: o.get("pageid")?.as_u64()?,
+                lat: o.get("lat")?.as_f64()?,
+                lon: o.get("lon")?.as_f64()?,
+                dist: o.get("dist").and_then(|x| x.as_f64()).unwrap_or(0.0),
+                // `primary` is an empty string when set in the legacy format
+                primary: o.get("primary").is_some_and(|x| x.as_bool() != Some(false)),
+                kind: o.get("type").and_then(|x| x.as_str()).map(|x| x.to_owned()),
+                dim: o.get("dim").and_then(|x| x.as_u64()),
+            })
         })
+        .collect())
+}
+
+/// Joins namespace ids with `|` as expected by the `*namespace` parameters.
+fn join_namespaces(namespaces: &[u32]) -> String {
+    namespaces
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Number of items to fetch per request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// As many as the API allows, usually 500.
+    Max,
+    /// Between 1 and 500 items.
+    Count(u32),
+}
+
+impl Limit {
+    /// Creates a `Count`, checking it is between 1 and 500.
+    pub fn count(count: u32) -> Result<Limit> {
+        Limit::Count(count).validate()
     }
 
-    /// Creates an iterator to view all references (external links) in the `Page`.
-    pub fn get_references(&self) -> Result<Iter<A, iter::Reference>> {
-        Iter::new(self)
+    fn validate(self) -> Result<Limit> {
+        match self {
+            Limit::Count(x) if x == 0 || x > 500 => {
+                Err(Error::InvalidParameter("limit".to_string()))
+            }
+            x => Ok(x),
+        }
     }
+}
 
-    fn request_links(
-        &self,
-        cont: &Option<IterElems>,
-    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
-        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
-            self,
-            cont,
-            ("prop", "links"),
-            ("plnamespace", "0"),
-            ("ellimit", &*self.wikipedia.links_results)
-        );
-        a.map(|(pages, cont)| {
-            let page = match pages.into_iter().next() {
-                Some(p) => p,
-                None => return (Vec::new(), None),
-            };
-            (
-                page.as_object()
-                    .and_then(|x| x.get("links"))
-                    .and_then(|x| x.as_array())
-                    .map(|x| x.to_vec())
-                    .unwrap_or(Vec::new()),
-                cont,
-            )
-        })
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Limit::Max => write!(f, "max"),
+            Limit::Count(x) => write!(f, "{}", x),
+        }
     }
+}
 
-    /// Creates an iterator to view all internal links in the `Page`.
-    pub fn get_links(&self) -> Result<Iter<A, iter::Link>> {
-        Iter::new(self)
+impl std::str::FromStr for Limit {
+    type Err = Error;
+
+    /// Parses `max` or a number between 1 and 500.
+    fn from_str(s: &str) -> Result<Limit> {
+        match s {
+            "max" => Ok(Limit::Max),
+            x => x
+                .parse()
+                .map_err(|_| Error::InvalidParameter("limit".to_string()))
+                .and_then(Limit::count),
+        }
     }
+}
 
-    fn request_categories(
-        &self,
-        cont: &Option<IterElems>,
-    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
-        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
-            self,
-            cont,
-            ("prop", "categories"),
-            ("cllimit", &*self.wikipedia.categories_results)
-        );
-        a.map(|(pages, cont)| {
-            let page = match pages.into_iter().next() {
-                Some(p) => p,
-                None => return (Vec::new(), None),
-            };
-            (
-                page.as_object()
-                    .and_then(|x| x.get("categories"))
-                    .and_then(|x| x.as_array())
-                    .map(|x| x.to_vec())
-                    .unwrap_or(Vec::new()),
-                cont,
-            )
-        })
+/// A warning in an API response, like an unrecognized parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiWarning {
+    /// The module that warned, like `main` or `extracts`.
+    pub module: String,
+    pub text: String,
+}
+
+/// Callback receiving the warnings of every API response, see
+/// `Wikipedia::warning_hook`.
+#[derive(Clone)]
+pub struct WarningHook(Arc<dyn Fn(&ApiWarning) + Send + Sync>);
+
+impl WarningHook {
+    pub fn new<F: Fn(&ApiWarning) + Send + Sync + 'static>(hook: F) -> Self {
+        WarningHook(Arc::new(hook))
     }
+}
 
-    /// Creates an iterator to view all categories of the `Page`.
-    pub fn get_categories(&self) -> Result<Iter<A, iter::Category>> {
-        Iter::new(self)
+impl fmt::Debug for WarningHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("WarningHook")
     }
+}
 
-    fn request_langlinks(
-        &self,
-        cont: &Option<IterElems>,
-    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
-        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
-            self,
-            cont,
-            ("prop", "langlinks"),
-            ("lllimit", &*self.wikipedia.links_results)
-        );
-        a.map(|(pages, cont)| {
-            let page = match pages.into_iter().next() {
-                Some(p) => p,
-                None => return (Vec::new(), None),
-            };
-            (
-                page.as_object()
-                    .and_then(|x| x.get("langlinks"))
-                    .and_then(|x| x.as_array())
-                    .map(|x| x.to_vec())
-                    .unwrap_or(Vec::new()),
-                cont,
-            )
-        })
+/// A request sent by `Wikipedia`, passed to `Wikipedia::request_hook` once
+/// the response arrived.
+#[derive(Debug)]
+pub struct RequestInfo<'a> {
+    /// The url, without the parameters for requests to the action API.
+    pub url: &'a str,
+    pub post: bool,
+    /// The parameters, like `("action", "query")` and `("list", "search")`.
+    /// Passwords and tokens are replaced with `***`.
+    pub params: &'a [(&'a str, &'a str)],
+    /// Time until the whole response was read or the request failed.
+    pub elapsed: Duration,
+    /// Length of the response, if it was successful.
+    pub bytes: Option<usize>,
+    /// Why the request failed, `Error::Http` has the status.
+    pub error: Option<&'a Error>,
+}
+
+/// Callback receiving every request sent, see `Wikipedia::request_hook`.
+#[derive(Clone)]
+pub struct RequestHook(Arc<dyn Fn(&RequestInfo) + Send + Sync>);
+
+impl RequestHook {
+    pub fn new<F: Fn(&RequestInfo) + Send + Sync + 'static>(hook: F) -> Self {
+        RequestHook(Arc::new(hook))
     }
+}
 
-    /// Creates an iterator to view all langlinks of the `Page`.
-    /// This iterates over the page titles in all available languages.
-    pub fn get_langlinks(&self) -> Result<Iter<A, iter::LangLink>> {
-        Iter::new(self)
+impl fmt::Debug for RequestHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RequestHook")
     }
+}
 
-    /// Returns the latitude and longitude associated to the `Page` if any.
-    pub fn get_coordinates(&self) -> Result<Option<(f64, f64)>> {
-        let qp = self.identifier.query_param();
-        let params = vec![
-            ("prop", "coordinates"),
-            ("colimit", "max"),
-            ("redirects", ""),
-            ("format", "json"),
-            ("action", "query"),
-            (&*qp.0, &*qp.1),
-        ];
-        let q = self.wikipedia.query(params.into_iter())?;
+#[derive(Debug)]
+pub struct Wikipedia<A: http::HttpClient> {
+    /// HttpClient struct.
+    pub client: A,
+    /// Url is created by concatenating `pre_language_url` + `language` + `post_language_url`.
+    pub pre_language_url: String,
+    pub post_language_url: String,
+    pub language: String,
+    /// Number of results to fetch when searching.
+    pub search_results: Limit,
+    /// Number of images to fetch in each request when calling `get_images`.
+    /// The iterator will go through all of them, fetching pages of this size.
+    pub images_results: Limit,
+    /// Like `images_results`, for links and references.
+    pub links_results: Limit,
+    /// Like `images_results`, for categories.
+    pub categories_results: Limit,
+    /// Namespaces to list links to when calling `get_links`, e.g. `vec![0]`
+    /// for articles only. All if empty, the default.
+    pub links_namespaces: Vec<u32>,
+    /// Whether `get_links` lists links in descending title order.
+    pub links_descending: bool,
+    /// Like `images_results`, for language links.
+    pub langlinks_results: Limit,
+    /// Like `images_results`, for external links.
+    pub extlinks_results: Limit,
+    /// Whether page queries follow redirects to their target page.
+    /// Defaults to true.
+    pub follow_redirects: bool,
+    /// Like `images_results`, for templates.
+    pub templates_results: Limit,
+    /// Namespaces to list templates from when calling `get_templates`, all if
+    /// empty.
+    pub templates_namespaces: Vec<u32>,
+    /// Like `images_results`, for backlinks.
+    pub backlinks_results: Limit,
+    /// Namespaces to list backlinks from when calling `get_backlinks`, all if
+    /// empty.
+    pub backlinks_namespaces: Vec<u32>,
+    /// Like `images_results`, for redirects to a page.
+    pub redirects_results: Limit,
+    /// Whether `get_categories` lists hidden maintenance categories, like
+    /// "Articles with dead external links". Defaults to true.
+    pub hidden_categories: bool,
+    /// Value of the `maxlag` parameter sent with every request, asking the
+    /// servers to refuse it while their replication lag is higher than this
+    /// many seconds. Such requests are repeated after the delay given by
+    /// their `Retry-After` header, see `maxlag_max_wait`. Unset by default;
+    /// bots are asked to send `5`.
+    pub maxlag: Option<u8>,
+    /// Total time to wait for lagged servers before a request fails with the
+    /// `maxlag` `Error::Api`. Defaults to a minute.
+    pub maxlag_max_wait: Duration,
+    /// Limits the rate of requests, continuations of iterators included.
+    /// Unset by default.
+    pub rate_limiter: Option<http::RateLimiter>,
+    /// Length of the encoded parameters above which requests are sent as POST
+    /// instead of GET, as long urls are refused by servers and proxies.
+    /// Defaults to 2000 bytes.
+    pub post_threshold: usize,
+    /// Called with the warnings of every API response, which are dropped by
+    /// default.
+    pub warning_hook: Option<WarningHook>,
+    /// Called after every request, continuations of iterators and retries
+    /// for `maxlag` included, to log or time them. Unset by default.
+    pub request_hook: Option<RequestHook>,
+}
 
-        if let Some(r) = self.redirect(&q) {
-            return Page::from_title(self.wikipedia, r).get_coordinates();
+impl<A: http::HttpClient + Default> Default for Wikipedia<A> {
+    fn default() -> Self {
+        Wikipedia::new(A::default())
+    }
+}
+
+impl<A: http::HttpClient + Clone> Clone for Wikipedia<A> {
+    fn clone(&self) -> Self {
+        self.with_client(self.client.clone())
+    }
+}
+
+/// Builds a `Wikipedia`, checking the settings together in `build`.
+#[derive(Debug)]
+pub struct WikipediaBuilder<A: http::HttpClient> {
+    client: A,
+    language: Option<String>,
+    base_url: Option<String>,
+    search_results: Option<Limit>,
+    images_results: Option<Limit>,
+    links_results: Option<Limit>,
+    categories_results: Option<Limit>,
+    user_agent: Option<String>,
+    maxlag: Option<u8>,
+}
+
+impl<A: http::HttpClient> WikipediaBuilder<A> {
+    pub fn new(client: A) -> Self {
+        WikipediaBuilder {
+            client,
+            language: None,
+            base_url: None,
+            search_results: None,
+            images_results: None,
+            links_results: None,
+            categories_results: None,
+            user_agent: None,
+            maxlag: None,
         }
+    }
 
-        let coord = match self
-            .get_first_page(&q)
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("coordinates"))
-            .and_then(|x| x.as_array())
-            .and_then(|x| x.iter().next())
-            .and_then(|x| x.as_object())
-        {
-            Some(c) => c,
-            None => return Ok(None),
-        };
-        Ok(Some((
-            coord
-                .get("lat")
-                .and_then(|x| x.as_f64())
-                .ok_or(Error::JSONPathError)?,
-            coord
-                .get("lon")
-                .and_then(|x| x.as_f64())
-                .ok_or(Error::JSONPathError)?,
-        )))
+    /// Sets the language, `en` by default.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_owned());
+        self
     }
 
-    /// Fetches all sections of the article.
-    pub fn get_sections(&self) -> Result<Vec<String>> {
-        let pageid = self.get_pageid()?;
-        let params = vec![
-            ("prop", "sections"),
-            ("format", "json"),
-            ("action", "parse"),
-            ("pageid", &*pageid),
-        ];
-        let q = self.wikipedia.query(params.into_iter())?;
+    /// Sets the api url, in which `{language}` is replaced with the
+    /// language, like in `Wikipedia::set_base_url`.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.to_owned());
+        self
+    }
 
-        Ok(q.as_object()
-            .and_then(|x| x.get("parse"))
-            .and_then(|x| x.as_object())
-            .and_then(|x| x.get("sections"))
-            .and_then(|x| x.as_array())
-            .ok_or(Error::JSONPathError)?
-            .iter()
-            .filter_map(|x| {
-                x.as_object()
-                    .and_then(|x| x.get("line"))
-                    .and_then(|x| x.as_str())
-                    .map(|x| x.to_owned())
-            })
-            .collect())
+    pub fn search_results(mut self, limit: Limit) -> Self {
+        self.search_results = Some(limit);
+        self
     }
 
-    /// Fetches the content of a section.
-    pub fn get_section_content(&self, title: &str) -> Result<Option<String>> {
-        let headr = format!("== {} ==", title);
-        let content = self.get_content()?;
-        let index = match content.find(&*headr) {
-            Some(i) => headr.len() + i,
-            None => return Ok(None),
-        };
-        let end = match content[index..].find("==") {
-            Some(i) => index + i,
-            None => content.len(),
-        };
-        Ok(Some(content[index..end].to_owned()))
+    pub fn images_results(mut self, limit: Limit) -> Self {
+        self.images_results = Some(limit);
+        self
     }
-}
 
-impl<'a, A: http::HttpClient> PartialEq<Page<'a, A>> for Page<'a, A> {
-    fn eq(&self, other: &Page<A>) -> bool {
-        match self.identifier {
-            TitlePageId::Title(ref t1) => match other.identifier {
-                TitlePageId::Title(ref t2) => t1 == t2,
-                TitlePageId::PageId(_) => false,
-            },
-            TitlePageId::PageId(ref p1) => match other.identifier {
-                TitlePageId::Title(_) => false,
-                TitlePageId::PageId(ref p2) => p1 == p2,
-            },
-        }
+    pub fn links_results(mut self, limit: Limit) -> Self {
+        self.links_results = Some(limit);
+        self
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::http::HttpClient;
-    use super::iter;
-    use super::Wikipedia;
-    use std::sync::Mutex;
+    pub fn categories_results(mut self, limit: Limit) -> Self {
+        self.categories_results = Some(limit);
+        self
+    }
 
-    const DEFAULT_AGENT: &str = "wikipedia (https://github.com/seppo0010/wikipedia-rs)";
+    /// Sets the user agent of the client.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_owned());
+        self
+    }
 
-    struct MockClient {
-        pub url: Mutex<Vec<String>>,
-        pub user_agent: Option<String>,
-        pub bearer_token: Option<String>,
-        pub arguments: Mutex<Vec<iter::IterElems>>,
-        pub response: Mutex<Vec<String>>,
+    /// Sets `Wikipedia::maxlag`.
+    pub fn maxlag(mut self, maxlag: u8) -> Self {
+        self.maxlag = Some(maxlag);
+        self
     }
 
-    impl Default for MockClient {
-        fn default() -> Self {
-            MockClient {
-                url: Mutex::new(Vec::new()),
-                user_agent: Some(DEFAULT_AGENT.into()),
-                bearer_token: None,
-                arguments: Mutex::new(Vec::new()),
-                response: Mutex::new(Vec::new()),
+    /// Builds the `Wikipedia`, failing with `Error::InvalidParameter` if a
+    /// limit isn't between 1 and 500, the language has no Wikipedia, or a
+    /// language is set along a base url without the `{language}` marker.
+    pub fn build(self) -> Result<Wikipedia<A>> {
+        let mut wikipedia = Wikipedia::new(self.client);
+        if let Some(ref base_url) = self.base_url {
+            if let Some(ref language) = self.language {
+                if !base_url.contains(LANGUAGE_URL_MARKER) {
+                    return Err(Error::InvalidParameter(format!("language: {}", language)));
+                }
             }
+            wikipedia.set_base_url(base_url);
+        }
+        if let Some(ref language) = self.language {
+            wikipedia.set_language(language)?;
+        }
+        if let Some(limit) = self.search_results {
+            wikipedia.set_search_results(limit)?;
         }
+        if let Some(limit) = self.images_results {
+            wikipedia.set_images_results(limit)?;
+        }
+        if let Some(limit) = self.links_results {
+            wikipedia.set_links_results(limit)?;
+        }
+        if let Some(limit) = self.categories_results {
+            wikipedia.set_categories_results(limit)?;
+        }
+        if let Some(user_agent) = self.user_agent {
+            wikipedia.client.user_agent(user_agent);
+        }
+        wikipedia.maxlag = self.maxlag;
+        Ok(wikipedia)
+    }
+}
+
+impl<A: http::HttpClient + Default> Default for WikipediaBuilder<A> {
+    fn default() -> Self {
+        WikipediaBuilder::new(A::default())
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl WikipediaBuilder<http::default::Client> {
+    /// Makes the client fail requests with `Error::Timeout` when they take
+    /// longer than `timeout`, see `http::default::Client::with_request_timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_request_timeout(timeout);
+        self
+    }
+}
+
+/// A `Wikipedia` using another language, which sends its requests
+/// through the client of the `Wikipedia` it borrows. Made by
+/// `Wikipedia::lang`.
+#[derive(Debug)]
+pub struct LanguageScope<'a, A: http::HttpClient> {
+    wikipedia: Wikipedia<http::BorrowedClient<'a, A>>,
+}
+
+impl<'a, A: http::HttpClient> Deref for LanguageScope<'a, A> {
+    type Target = Wikipedia<http::BorrowedClient<'a, A>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.wikipedia
+    }
+}
+
+impl<A: http::HttpClient> Wikipedia<A> {
+    /// Starts building a `Wikipedia` using the provided client.
+    pub fn builder(client: A) -> WikipediaBuilder<A> {
+        WikipediaBuilder::new(client)
+    }
+
+    /// Creates a new object using the provided client and default values.
+    pub fn new(client: A) -> Self {
+        Wikipedia {
+            client,
+            pre_language_url: "https://".to_owned(),
+            post_language_url: ".wikipedia.org/w/api.php".to_owned(),
+            language: "en".to_owned(),
+            search_results: Limit::Count(10),
+            images_results: Limit::Max,
+            links_results: Limit::Max,
+            links_namespaces: Vec::new(),
+            links_descending: false,
+            categories_results: Limit::Max,
+            langlinks_results: Limit::Max,
+            extlinks_results: Limit::Max,
+            follow_redirects: true,
+            templates_results: Limit::Max,
+            templates_namespaces: Vec::new(),
+            backlinks_results: Limit::Max,
+            backlinks_namespaces: Vec::new(),
+            redirects_results: Limit::Max,
+            hidden_categories: true,
+            maxlag: None,
+            maxlag_max_wait: Duration::from_secs(60),
+            rate_limiter: None,
+            post_threshold: 2000,
+            warning_hook: None,
+            request_hook: None,
+        }
+    }
+
+    /// Sets `search_results`, failing if `limit` isn't between 1 and 500.
+    pub fn set_search_results(&mut self, limit: Limit) -> Result<()> {
+        self.search_results = limit.validate()?;
+        Ok(())
+    }
+
+    /// Sets `images_results`, failing if `limit` isn't between 1 and 500.
+    pub fn set_images_results(&mut self, limit: Limit) -> Result<()> {
+        self.images_results = limit.validate()?;
+        Ok(())
+    }
+
+    /// Sets `links_results`, failing if `limit` isn't between 1 and 500.
+    pub fn set_links_results(&mut self, limit: Limit) -> Result<()> {
+        self.links_results = limit.validate()?;
+        Ok(())
+    }
+
+    /// Sets `categories_results`, failing if `limit` isn't between 1 and
+    /// 500.
+    pub fn set_categories_results(&mut self, limit: Limit) -> Result<()> {
+        self.categories_results = limit.validate()?;
+        Ok(())
+    }
+
+    /// Returns the html diff between revisions `from` and `to`, which may
+    /// belong to different pages.
+    pub fn compare_revisions(&self, from: u64, to: u64) -> Result<String> {
+        let fromrev = format!("{}", from);
+        let torev = format!("{}", to);
+        let q = self.query(
+            vec![
+                ("fromrev", &*fromrev),
+                ("torev", &*torev),
+                ("format", "json"),
+                ("action", "compare"),
+            ]
+            .into_iter(),
+        );
+
+        let q = match q {
+            Err(Error::Api { ref code, ref info }) if code == "nosuchrevid" => {
+                // the info names the revision, as in "There is no revision
+                // with ID 123."
+                let named = info
+                    .split(|c: char| !c.is_ascii_digit())
+                    .any(|x| x == fromrev);
+                let bad = if named { from } else { to };
+                return Err(Error::BadRevisionId(bad));
+            }
+            r => r?,
+        };
+        Ok(q.as_object()
+            .and_then(|x| x.get("compare"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("*"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("compare.*"))?
+            .to_owned())
+    }
+
+    /// Returns a list of languages in the form of (`identifier`, `language`),
+    /// for example [("en", "English"), ("es", "Español")]
+    pub fn get_languages(&self) -> Result<IterElems> {
+        let q = self.query(
+            vec![
+                ("meta", "siteinfo"),
+                ("siprop", "languages"),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        Ok(q.as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("languages"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("query.languages"))?
+            .iter()
+            .filter_map(|x| {
+                let o = x.as_object();
+                Some((
+                    match o
+                        .and_then(|x| x.get("code"))
+                        .and_then(|x| x.as_str())
+                        .map(|x| x.to_owned())
+                    {
+                        Some(v) => v,
+                        None => return None,
+                    },
+                    match o
+                        .and_then(|x| x.get("*"))
+                        .and_then(|x| x.as_str())
+                        .map(|x| x.to_owned())
+                    {
+                        Some(v) => v,
+                        None => return None,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns the namespaces of the wiki, ordered by id.
+    pub fn get_namespaces(&self) -> Result<Vec<Namespace>> {
+        let q = self.query(
+            vec![
+                ("meta", "siteinfo"),
+                ("siprop", "namespaces"),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        parse_namespaces(
+            q.as_object()
+                .and_then(|x| x.get("query"))
+                .and_then(|x| x.as_object())
+                .ok_or(Error::JSONPathError("query"))?,
+        )
+    }
+
+    /// Returns general information, namespaces and statistics of the wiki.
+    pub fn get_siteinfo(&self) -> Result<SiteInfo> {
+        let q = self.query(
+            vec![
+                ("meta", "siteinfo"),
+                ("siprop", "namespaces|general|statistics"),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        let query = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query"))?;
+        let general = query
+            .get("general")
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query.general"))?;
+        let field = |name| {
+            general
+                .get(name)
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        let statistics = query.get("statistics").and_then(|x| x.as_object());
+        let counter = |name| {
+            statistics
+                .and_then(|x| x.get(name))
+                .and_then(|x| x.as_u64())
+                .unwrap_or(0)
+        };
+        Ok(SiteInfo {
+            sitename: field("sitename"),
+            main_page: field("mainpage"),
+            generator: field("generator"),
+            base: field("base"),
+            server: field("server"),
+            article_path: field("articlepath"),
+            lang: field("lang"),
+            namespaces: parse_namespaces(query)?,
+            statistics: SiteStatistics {
+                pages: counter("pages"),
+                articles: counter("articles"),
+                edits: counter("edits"),
+                images: counter("images"),
+                users: counter("users"),
+                active_users: counter("activeusers"),
+                admins: counter("admins"),
+            },
+        })
+    }
+
+    /// Returns the api url
+    pub fn base_url(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.pre_language_url, self.language, self.post_language_url
+        )
+    }
+
+    /// Updates the url format. The substring `{language}` will be replaced
+    /// with the selected language.
+    pub fn set_base_url(&mut self, base_url: &str) {
+        let index = match base_url.find(LANGUAGE_URL_MARKER) {
+            Some(i) => i,
+            None => {
+                self.pre_language_url = base_url.to_owned();
+                self.language = "".to_owned();
+                self.post_language_url = "".to_owned();
+                return;
+            }
+        };
+        self.pre_language_url = base_url[0..index].to_owned();
+        self.post_language_url = base_url[index + LANGUAGE_URL_MARKER.len()..].to_owned();
+    }
+
+    /// Sets the language, failing with `Error::InvalidParameter` if there is
+    /// no Wikipedia in it or the base url has no `{language}` marker. The
+    /// codes are checked against a bundled list; `get_languages` returns
+    /// all the languages known to a wiki instead.
+    pub fn set_language(&mut self, language: &str) -> Result<()> {
+        // `set_base_url` keeps a url without the marker whole in
+        // `pre_language_url`, clearing the rest
+        let marker = !self.language.is_empty() || !self.post_language_url.is_empty();
+        if !marker || !languages::is_language(language) {
+            return Err(Error::InvalidParameter(format!("language: {}", language)));
+        }
+        self.language = language.to_owned();
+        Ok(())
+    }
+
+    /// Returns a copy using `language`, see `set_language`. Clients like
+    /// `http::default::Client` share their connections between copies.
+    pub fn with_language(&self, language: &str) -> Result<Self>
+    where
+        A: Clone,
+    {
+        let mut wikipedia = self.clone();
+        wikipedia.set_language(language)?;
+        Ok(wikipedia)
+    }
+
+    /// Returns a `LanguageScope` whose requests use `language`, leaving this
+    /// `Wikipedia` unchanged, e.g. `wikipedia.lang("de")?.search("Berlin")`.
+    /// Fails like `set_language`, also if the base url has no `{language}`
+    /// marker, as the requests would go to another host.
+    pub fn lang(&self, language: &str) -> Result<LanguageScope<'_, A>> {
+        let mut wikipedia = self.with_client(http::BorrowedClient(&self.client));
+        wikipedia.set_language(language)?;
+        Ok(LanguageScope { wikipedia })
+    }
+
+    /// Copies the settings to a `Wikipedia` using `client`.
+    fn with_client<B: http::HttpClient>(&self, client: B) -> Wikipedia<B> {
+        Wikipedia {
+            client,
+            pre_language_url: self.pre_language_url.clone(),
+            post_language_url: self.post_language_url.clone(),
+            language: self.language.clone(),
+            search_results: self.search_results,
+            images_results: self.images_results,
+            links_results: self.links_results,
+            links_namespaces: self.links_namespaces.clone(),
+            links_descending: self.links_descending,
+            categories_results: self.categories_results,
+            langlinks_results: self.langlinks_results,
+            extlinks_results: self.extlinks_results,
+            follow_redirects: self.follow_redirects,
+            templates_results: self.templates_results,
+            templates_namespaces: self.templates_namespaces.clone(),
+            backlinks_results: self.backlinks_results,
+            backlinks_namespaces: self.backlinks_namespaces.clone(),
+            redirects_results: self.redirects_results,
+            hidden_categories: self.hidden_categories,
+            maxlag: self.maxlag,
+            maxlag_max_wait: self.maxlag_max_wait,
+            rate_limiter: self.rate_limiter.clone(),
+            post_threshold: self.post_threshold,
+            warning_hook: self.warning_hook.clone(),
+            request_hook: self.request_hook.clone(),
+        }
+    }
+
+    /// Sends a request with the `maxlag` parameter added, repeating it
+    /// while the servers are lagged, for up to `maxlag_max_wait`. Retries
+    /// wait as long as the `Retry-After` header of the response asks, or
+    /// as many seconds as `maxlag` without it. An
+    /// `error` object in the response is returned as `Error::Api` and
+    /// `warnings` are passed to `warning_hook`. GET
+    /// requests with parameters longer than `post_threshold` are sent as
+    /// POST, and requests with a `file` as a multipart POST. `headers` are
+    /// sent with GET and POST requests, multipart requests with `headers`
+    /// fail with `Error::InvalidParameter`. Every request is passed to
+    /// `request_hook`.
+    fn request(
+        &self,
+        post: bool,
+        args: Vec<(&str, &str)>,
+        file: Option<&http::FilePart>,
+        headers: &[(&str, &str)],
+    ) -> Result<serde_json::Value> {
+        if file.is_some() && !headers.is_empty() {
+            return Err(Error::InvalidParameter("headers".to_string()));
+        }
+        let maxlag = self.maxlag.map(|x| x.to_string());
+        let mut args = args;
+        if let Some(ref maxlag) = maxlag {
+            args.push(("maxlag", maxlag));
+        }
+        let post = post || file.is_some() || encoded_len(&args) > self.post_threshold;
+        let base_url = self.base_url();
+        let mut waited = Duration::ZERO;
+        loop {
+            if let Some(ref limiter) = self.rate_limiter {
+                limiter.acquire();
+            }
+            let start = Instant::now();
+            let response = match file {
+                Some(file) => self
+                    .client
+                    .post_multipart(&base_url, args.iter().cloned(), file)
+                    .map(|body| http::Response {
+                        status: 200,
+                        headers: Vec::new(),
+                        body,
+                    }),
+                None if post => self
+                    .client
+                    .post_response(&base_url, args.iter().cloned(), headers),
+                None => self
+                    .client
+                    .get_response(&base_url, args.iter().cloned(), headers),
+            }
+            .and_then(|x| match x.status {
+                304 => Err(Error::Http {
+                    status: 304,
+                    body_snippet: String::new(),
+                    retry_after: None,
+                }),
+                _ => Ok(x),
+            });
+            let body = response.as_ref().map(|x| &*x.body);
+            self.report_request(&base_url, post, &args, start, body);
+            let response = response?;
+            let json = serde_json::from_str(&response.body).map_err(Error::JSONError)?;
+            let lag = match self.maxlag {
+                Some(lag) if is_maxlag_error(&json) => lag,
+                _ => {
+                    check_api_error(&json)?;
+                    if let Some(ref hook) = self.warning_hook {
+                        api_warnings(&json).iter().for_each(|x| (hook.0)(x));
+                    }
+                    return Ok(json);
+                }
+            };
+            let remaining = self.maxlag_max_wait.saturating_sub(waited);
+            if remaining.is_zero() {
+                check_api_error(&json)?;
+            }
+            // without the header, wait as many seconds as `maxlag`, but at
+            // least 5
+            let retry_after = response
+                .header("Retry-After")
+                .and_then(|x| x.trim().parse().ok())
+                .unwrap_or_else(|| lag.max(5).into());
+            let wait = Duration::from_secs(retry_after).min(remaining);
+            thread::sleep(wait);
+            waited += wait;
+        }
+    }
+
+    /// Passes a finished request to `request_hook`.
+    fn report_request(
+        &self,
+        url: &str,
+        post: bool,
+        params: &[(&str, &str)],
+        start: Instant,
+        response: result::Result<&str, &Error>,
+    ) {
+        if let Some(ref hook) = self.request_hook {
+            let params: Vec<_> = params
+                .iter()
+                .map(|&(k, v)| (k, if SECRET_PARAMS.contains(&k) { "***" } else { v }))
+                .collect();
+            (hook.0)(&RequestInfo {
+                url,
+                post,
+                params: &params,
+                elapsed: start.elapsed(),
+                bytes: response.ok().map(|x| x.len()),
+                error: response.err(),
+            });
+        }
+    }
+
+    /// Sends the arguments as given through `request`, as a GET unless they
+    /// exceed `post_threshold`. Unlike `query`, a `redirects` argument is
+    /// kept even when `follow_redirects` is unset.
+    fn client_query<'a, I>(&self, args: I) -> Result<serde_json::Value>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.request(false, args.collect(), None, &[])
+    }
+
+    /// Like `query`, sending the arguments in the body of a POST request.
+    fn post_query<'a, I>(&self, args: I) -> Result<serde_json::Value>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        self.request(true, args.collect(), None, &[])
+    }
+
+    /// Sends the arguments through `request` like `client_query`, leaving
+    /// out `redirects` unless `follow_redirects` is set.
+    fn query<'a, I>(&self, args: I) -> Result<serde_json::Value>
+    where
+        I: Iterator<Item = (&'a str, &'a str)>,
+    {
+        let follow_redirects = self.follow_redirects;
+        self.request(
+            false,
+            args.filter(|x| follow_redirects || x.0 != "redirects")
+                .collect(),
+            None,
+            &[],
+        )
+    }
+
+    /// Searches for a string and returns a list of relevant page titles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let results = wiki.search("keyboard").unwrap();
+    /// assert!(results.contains(&"Computer keyboard".to_owned()));
+    /// ```
+    pub fn search(&self, query: &str) -> Result<Vec<String>> {
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "search"),
+                ("srprop", ""),
+                ("srlimit", results),
+                ("srsearch", query),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        Ok(results!(data, "search"))
+    }
+
+    /// Searches for a string skipping the first `offset` results. The
+    /// returned page holds the offset for the next request, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let first = wiki.search_offset("keyboard", 0).unwrap();
+    /// let second = wiki.search_offset("keyboard", first.next_offset.unwrap()).unwrap();
+    /// assert!(!second.results.is_empty());
+    /// ```
+    pub fn search_offset(&self, query: &str, offset: u32) -> Result<SearchPage> {
+        self.search_opts(
+            query,
+            &SearchOptions {
+                offset: Some(offset),
+                ..SearchOptions::default()
+            },
+        )
+    }
+
+    /// Returns the total number of pages matching the query without
+    /// fetching their titles.
+    pub fn search_count(&self, query: &str) -> Result<u64> {
+        let data = self.query(
+            vec![
+                ("list", "search"),
+                ("srprop", ""),
+                ("srinfo", "totalhits"),
+                ("srlimit", "1"),
+                ("srsearch", query),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        data.as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("searchinfo"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("totalhits"))
+            .and_then(|x| x.as_u64())
+            .ok_or(Error::JSONPathError("query.searchinfo.totalhits"))
+    }
+
+    /// Searches for a string with the given options. The returned page
+    /// holds the offset for the next request, if any.
+    ///
+    /// Non-relevance sort orders can't be combined with
+    /// `SearchMode::NearMatch` and return `Error::InvalidParameter`.
+    pub fn search_opts(&self, query: &str, options: &SearchOptions) -> Result<SearchPage> {
+        if options.mode == Some(SearchMode::NearMatch)
+            && options.sort.is_some_and(|x| x != SearchSort::Relevance)
+        {
+            return Err(Error::InvalidParameter("sort".to_string()));
+        }
+        let results = format!("{}", self.search_results);
+        let offset = options.offset.map(|x| format!("{}", x));
+        let namespaces = join_namespaces(&options.namespaces);
+        let mut params = vec![("list", "search"), ("srprop", ""), ("srlimit", &*results)];
+        if let Some(ref offset) = offset {
+            params.push(("sroffset", offset));
+        }
+        if !namespaces.is_empty() {
+            params.push(("srnamespace", &*namespaces));
+        }
+        if let Some(mode) = options.mode {
+            params.push(("srwhat", mode.as_str()));
+        }
+        if let Some(sort) = options.sort {
+            params.push(("srsort", sort.as_str()));
+        }
+        params.push(("srsearch", query));
+        params.push(("format", "json"));
+        params.push(("action", "query"));
+        let data = self.query(params.into_iter())?;
+
+        Ok(SearchPage {
+            results: results!(data, "search"),
+            next_offset: continue_offset(&data, "sroffset"),
+        })
+    }
+
+    /// Searches for a string using the given `mode`. With
+    /// `SearchMode::NearMatch` the result is either empty or contains a
+    /// single title.
+    pub fn search_mode(&self, query: &str, mode: SearchMode) -> Result<Vec<String>> {
+        Ok(self
+            .search_opts(
+                query,
+                &SearchOptions {
+                    mode: Some(mode),
+                    ..SearchOptions::default()
+                },
+            )?
+            .results)
+    }
+
+    /// Returns the titles of articles related to `title`, ranked by
+    /// relevance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let results = wiki.search_related("Computer keyboard").unwrap();
+    /// assert!(!results.is_empty());
+    /// ```
+    pub fn search_related(&self, title: &str) -> Result<Vec<String>> {
+        self.search(&format!("morelike:{}", quote_search_value(title)))
+    }
+
+    /// Searches for a string among the pages in `category`. The
+    /// `Category:` prefix is optional.
+    pub fn search_in_category(&self, query: &str, category: &str) -> Result<Vec<String>> {
+        let category = category.strip_prefix("Category:").unwrap_or(category);
+        self.search(&format!(
+            "{} incategory:{}",
+            query,
+            quote_search_value(category)
+        ))
+    }
+
+    /// Searches for a string only in the given namespaces (e.g. 14 for
+    /// categories, 10 for templates).
+    pub fn search_in_namespaces(&self, query: &str, namespaces: &[u32]) -> Result<Vec<String>> {
+        if namespaces.is_empty() {
+            return Err(Error::InvalidParameter("namespaces".to_string()));
+        }
+        Ok(self
+            .search_opts(
+                query,
+                &SearchOptions {
+                    namespaces: namespaces.to_vec(),
+                    ..SearchOptions::default()
+                },
+            )?
+            .results)
+    }
+
+    /// Returns the titles starting with `prefix`, in the order ranked by the
+    /// API. Useful for autocompletion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let results = wiki.prefix_search("Computer key").unwrap();
+    /// assert!(results.contains(&"Computer keyboard".to_owned()));
+    /// ```
+    pub fn prefix_search(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self.prefix_search_offset(prefix, 0)?.results)
+    }
+
+    /// Like `prefix_search`, skipping the first `offset` results. The
+    /// returned page holds the offset for the next request, if any.
+    pub fn prefix_search_offset(&self, prefix: &str, offset: u32) -> Result<SearchPage> {
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "prefixsearch"),
+                ("pslimit", results),
+                ("psoffset", &*format!("{}", offset)),
+                ("pssearch", prefix),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        Ok(SearchPage {
+            results: results!(data, "prefixsearch"),
+            next_offset: continue_offset(&data, "psoffset"),
+        })
+    }
+
+    /// Queries the OpenSearch endpoint, suited for suggestion dropdowns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let results = wiki.open_search("Computer key").unwrap();
+    /// assert!(results.iter().any(|r| r.title == "Computer keyboard"));
+    /// ```
+    pub fn open_search(&self, query: &str) -> Result<Vec<OpenSearchResult>> {
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("search", query),
+                ("limit", results),
+                ("format", "json"),
+                ("action", "opensearch"),
+            ]
+            .into_iter(),
+        )?;
+
+        // The response is `[query, [titles], [descriptions], [urls]]`
+        let array = data.as_array().ok_or(Error::JSONPathError("(root)"))?;
+        let column = |i: usize, path| {
+            array
+                .get(i)
+                .and_then(|x| x.as_array())
+                .ok_or(Error::JSONPathError(path))
+        };
+        let titles = column(1, "[1]")?;
+        let descriptions = column(2, "[2]")?;
+        let urls = column(3, "[3]")?;
+        Ok(titles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, title)| {
+                Some(OpenSearchResult {
+                    title: title.as_str()?.to_owned(),
+                    description: descriptions
+                        .get(i)
+                        .and_then(|x| x.as_str())
+                        .unwrap_or("")
+                        .to_owned(),
+                    url: urls
+                        .get(i)
+                        .and_then(|x| x.as_str())
+                        .unwrap_or("")
+                        .to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    /// Searches for a string and returns the matching titles along with
+    /// the "did you mean" suggestion, if the API offers one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let (_, suggestion) = wiki.search_with_suggestion("keybaord").unwrap();
+    /// assert_eq!(suggestion, Some("keyboard".to_owned()));
+    /// ```
+    pub fn search_with_suggestion(&self, query: &str) -> Result<(Vec<String>, Option<String>)> {
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "search"),
+                ("srprop", ""),
+                ("srinfo", "suggestion"),
+                ("srlimit", results),
+                ("srsearch", query),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        let suggestion = data
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("searchinfo"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("suggestion"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned());
+        Ok((results!(data, "search"), suggestion))
+    }
+
+    /// Searches for a string and returns the matching pages along with
+    /// a snippet, word count, size and last edit timestamp.
+    pub fn search_detailed(&self, query: &str) -> Result<Vec<SearchResult>> {
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "search"),
+                ("srprop", "snippet|wordcount|size|timestamp"),
+                ("srlimit", results),
+                ("srsearch", query),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        let search = data
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("search"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("query.search"))?;
+        Ok(serde_json::from_value(serde_json::Value::Array(
+            search.clone(),
+        ))?)
+    }
+
+    /// Search articles within `radius` meters of `latitude` and `longitude`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate wikipedia;
+    ///
+    /// let wiki = wikipedia::Wikipedia::<wikipedia::http::default::Client>::default();
+    /// let results = wiki.geosearch(40.750556,-73.993611, 20).unwrap();
+    /// assert!(results.contains(&"Madison Square Garden".to_owned()));
+    /// ```
+    pub fn geosearch(&self, latitude: f64, longitude: f64, radius: u16) -> Result<Vec<String>> {
+        self.geosearch_opts(latitude, longitude, radius, &GeoSearchOptions::default())
+    }
+
+    /// Like `geosearch`, using the namespaces and result limit in `options`.
+    /// Fails with `Error::InvalidParameter` if the limit isn't between 1 and
+    /// 500.
+    pub fn geosearch_opts(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius: u16,
+        options: &GeoSearchOptions,
+    ) -> Result<Vec<String>> {
+        check_geosearch_params(latitude, longitude, radius)?;
+        let results = match options.limit {
+            Some(limit) => Limit::count(limit)?,
+            None => self.search_results,
+        };
+        let results = format!("{}", results);
+        let radius = format!("{}", radius);
+        let coord = format!("{}|{}", latitude, longitude);
+        let namespaces = join_namespaces(&options.namespaces);
+        let mut params = vec![
+            ("list", "geosearch"),
+            ("gsradius", &*radius),
+            ("gscoord", &*coord),
+            ("gslimit", &*results),
+        ];
+        if !namespaces.is_empty() {
+            params.push(("gsnamespace", &*namespaces));
+        }
+        params.push(("format", "json"));
+        params.push(("action", "query"));
+        let data = self.query(params.into_iter())?;
+        Ok(results!(data, "geosearch"))
+    }
+
+    /// Like `geosearch`, but returns the coordinates and distance of every
+    /// result.
+    pub fn geosearch_detailed(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius: u16,
+    ) -> Result<Vec<GeoSearchResult>> {
+        check_geosearch_params(latitude, longitude, radius)?;
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "geosearch"),
+                ("gsradius", &*format!("{}", radius)),
+                ("gscoord", &*format!("{}|{}", latitude, longitude)),
+                ("gslimit", results),
+                ("gsprop", "type|dim"),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        geosearch_results(&data)
+    }
+
+    /// Search articles within `radius` meters of the coordinates of the
+    /// page titled `title`. If that page has no coordinates the API error
+    /// is returned as `Error::Api`.
+    pub fn geosearch_near_page(&self, title: &str, radius: u16) -> Result<Vec<String>> {
+        #![allow(clippy::manual_range_contains)]
+
+        if radius < 10 || radius > 10000 {
+            return Err(Error::InvalidParameter("radius".to_string()));
+        }
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "geosearch"),
+                ("gsradius", &*format!("{}", radius)),
+                ("gspage", title),
+                ("gslimit", results),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        Ok(results!(data, "geosearch"))
+    }
+
+    /// Search articles inside the bounding box delimited by the `top` and
+    /// `bottom` latitudes and the `left` and `right` longitudes.
+    pub fn geosearch_bbox(
+        &self,
+        top: f64,
+        left: f64,
+        bottom: f64,
+        right: f64,
+    ) -> Result<Vec<GeoSearchResult>> {
+        #![allow(clippy::manual_range_contains)]
+
+        for (name, latitude) in [("top", top), ("bottom", bottom)] {
+            if latitude < -90.0 || latitude > 90.0 {
+                return Err(Error::InvalidParameter(name.to_string()));
+            }
+        }
+        for (name, longitude) in [("left", left), ("right", right)] {
+            if longitude < -180.0 || longitude > 180.0 {
+                return Err(Error::InvalidParameter(name.to_string()));
+            }
+        }
+        if top <= bottom || left == right {
+            return Err(Error::InvalidParameter("bbox".to_string()));
+        }
+        let results = &*format!("{}", self.search_results);
+        let data = self.query(
+            vec![
+                ("list", "geosearch"),
+                ("gsbbox", &*format!("{}|{}|{}|{}", top, left, bottom, right)),
+                ("gslimit", results),
+                ("gsprop", "type|dim"),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        geosearch_results(&data)
+    }
+
+    /// Fetches `count` random articles' title.
+    pub fn random_count(&self, count: u8) -> Result<Vec<String>> {
+        let data = self.query(
+            vec![
+                ("list", "random"),
+                ("rnnamespace", "0"),
+                ("rnlimit", &*format!("{}", count)),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        let r: Vec<String> = results!(data, "random");
+        Ok(r)
+    }
+
+    /// Fetches `count` random page titles from the given namespaces.
+    /// `count` must be between 1 and 500, although the API caps it at 50
+    /// for clients without the bot right.
+    pub fn random_in_namespaces(
+        &self,
+        count: u16,
+        namespaces: &[u32],
+        filter: RedirectFilter,
+    ) -> Result<Vec<String>> {
+        if count == 0 || count > 500 {
+            return Err(Error::InvalidParameter("count".to_string()));
+        }
+        if namespaces.is_empty() {
+            return Err(Error::InvalidParameter("namespaces".to_string()));
+        }
+        let data = self.query(
+            vec![
+                ("list", "random"),
+                ("rnnamespace", &*join_namespaces(namespaces)),
+                ("rnfilterredir", filter.as_str()),
+                ("rnlimit", &*format!("{}", count)),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        Ok(results!(data, "random"))
+    }
+
+    /// Fetches `count` random articles as `Page`s with both their title and
+    /// pageid already known.
+    pub fn random_count_pages(&self, count: u8) -> Result<Vec<Page<'_, A>>> {
+        let data = self.query(
+            vec![
+                ("list", "random"),
+                ("rnnamespace", "0"),
+                ("rnlimit", &*format!("{}", count)),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+        Ok(data
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("random"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("query.random"))?
+            .iter()
+            .filter_map(|x| {
+                let o = x.as_object()?;
+                Some(Page {
+                    wikipedia: self,
+                    identifier: TitlePageId::Both {
+                        title: o.get("title")?.as_str()?.to_owned(),
+                        pageid: format!("{}", o.get("id")?.as_u64()?),
+                    },
+                    resolved: Mutex::new(None),
+                })
+            })
+            .collect())
+    }
+
+    /// Fetches a random article as a `Page`.
+    pub fn random_page(&self) -> Result<Page<'_, A>> {
+        self.random_count_pages(1)?
+            .into_iter()
+            .next()
+            .ok_or(Error::JSONPathError("query.random[]"))
+    }
+
+    /// Fetches a random article's title.
+    pub fn random(&self) -> Result<Option<String>> {
+        Ok(self.random_count(1)?.into_iter().next())
+    }
+
+    /// Fetches the url, size, mime type and extended metadata of a file,
+    /// given its title including the `File:` prefix.
+    pub fn image_info(&self, file_title: &str) -> Result<ImageInfo> {
+        self.single_image_info(file_title, None, None)
+    }
+
+    /// Like `image_info`, also returning the url of a thumbnail scaled to
+    /// `width` pixels.
+    pub fn image_info_scaled(&self, file_title: &str, width: u32) -> Result<ImageInfo> {
+        self.single_image_info(file_title, Some(width), None)
+    }
+
+    /// Like `image_info`, also returning the url of a thumbnail fitting into
+    /// `width` x `height` pixels.
+    pub fn image_info_bounded(
+        &self,
+        file_title: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<ImageInfo> {
+        self.single_image_info(file_title, Some(width), Some(height))
+    }
+
+    fn single_image_info(
+        &self,
+        file_title: &str,
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> Result<ImageInfo> {
+        self.fetch_image_infos(&[file_title], width, height)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::PageNotFound {
+                title: file_title.to_owned(),
+            })
+    }
+
+    /// Like `image_info`, for several files at once. Missing files are left
+    /// out, the others are returned in the order they were given.
+    pub fn image_infos(&self, file_titles: &[&str]) -> Result<Vec<ImageInfo>> {
+        self.fetch_image_infos(file_titles, None, None)
+    }
+
+    fn fetch_image_infos(
+        &self,
+        file_titles: &[&str],
+        width: Option<u32>,
+        height: Option<u32>,
+    ) -> Result<Vec<ImageInfo>> {
+        let width = width.map(|x| format!("{}", x));
+        let height = height.map(|x| format!("{}", x));
+        let mut infos = Vec::new();
+        // the API accepts up to 50 titles per request
+        for chunk in file_titles.chunks(50) {
+            let titles = chunk.join("|");
+            let mut params = vec![
+                ("prop", "imageinfo"),
+                ("iiprop", "url|size|mime|extmetadata"),
+            ];
+            if let Some(ref width) = width {
+                params.push(("iiurlwidth", width));
+            }
+            if let Some(ref height) = height {
+                params.push(("iiurlheight", height));
+            }
+            params.extend(vec![
+                ("format", "json"),
+                ("action", "query"),
+                ("titles", &*titles),
+            ]);
+            let q = self.query(params.into_iter())?;
+            if let Some(pages) = q
+                .as_object()
+                .and_then(|x| x.get("query"))
+                .and_then(|x| x.as_object())
+                .and_then(|x| x.get("pages"))
+                .and_then(|x| x.as_object())
+            {
+                infos.extend(pages.values().filter_map(parse_image_info));
+            }
+        }
+        let position = |info: &ImageInfo| {
+            file_titles
+                .iter()
+                .position(|x| normalize_title(x) == info.title)
+                .unwrap_or(file_titles.len())
+        };
+        infos.sort_by_key(position);
+        Ok(infos)
+    }
+
+    /// Fetches the data of the pages given by `titles`, requesting them in
+    /// batches of 50, the most the API accepts at once. The result is
+    /// keyed by the title after normalization and redirects, missing and
+    /// invalid titles are left out. `PageData::requested` lists the inputs
+    /// for each page.
+    pub fn get_pages(&self, titles: &[&str]) -> Result<HashMap<String, PageData>> {
+        let mut pages: HashMap<String, PageData> = HashMap::new();
+        for chunk in titles.chunks(50) {
+            let batch = self.query_page_data("titles", &join_values(chunk))?;
+            for page in batch.pages.values().filter_map(parse_page_data) {
+                let requested: Vec<_> = chunk
+                    .iter()
+                    .filter(|x| batch.resolve(x) == page.title)
+                    .map(|x| (*x).to_owned())
+                    .collect();
+                pages
+                    .entry(page.title.clone())
+                    .or_insert(page)
+                    .requested
+                    .extend(requested);
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Like `get_pages`, for pages given by id. The result is keyed by
+    /// page id.
+    pub fn get_pages_by_id(&self, pageids: &[u64]) -> Result<HashMap<u64, PageData>> {
+        let mut pages = HashMap::new();
+        for chunk in pageids.chunks(50) {
+            let ids: Vec<_> = chunk.iter().map(|x| format!("{}", x)).collect();
+            let batch = self.query_page_data("pageids", &ids.join("|"))?;
+            for mut page in batch.pages.values().filter_map(parse_page_data) {
+                page.requested = vec![format!("{}", page.pageid)];
+                pages.insert(page.pageid, page);
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Checks which of `titles` exist. The result is keyed by the titles
+    /// as given, redirects count as existing pages.
+    pub fn pages_exist(&self, titles: &[&str]) -> Result<HashMap<String, TitleStatus>> {
+        let mut status = HashMap::new();
+        for chunk in titles.chunks(50) {
+            let joined = join_values(chunk);
+            let batch = self.query_pages(vec![
+                ("prop", "info"),
+                ("format", "json"),
+                ("action", "query"),
+                ("titles", &*joined),
+            ])?;
+            for title in chunk {
+                let resolved = batch.resolve(title);
+                let page = batch
+                    .pages
+                    .values()
+                    .find(|x| x.get("title").and_then(|x| x.as_str()) == Some(resolved));
+                let s = match page {
+                    Some(x) if x.contains_key("invalid") => TitleStatus::Invalid,
+                    Some(x) if x.contains_key("missing") => TitleStatus::Missing,
+                    Some(_) => TitleStatus::Exists,
+                    None if batch.interwiki.contains(resolved) => TitleStatus::Invalid,
+                    None => TitleStatus::Missing,
+                };
+                status.insert((*title).to_owned(), s);
+            }
+        }
+        Ok(status)
+    }
+
+    /// Sends an `action=purge` request for the page given by `param`,
+    /// returning the entries of the `purge` array and the whole response.
+    fn purge(
+        &self,
+        param: (&str, &str),
+        forcelinkupdate: bool,
+    ) -> Result<(Vec<serde_json::Value>, serde_json::Value)> {
+        let mut params = vec![param];
+        if forcelinkupdate {
+            params.push(("forcelinkupdate", "1"));
+        }
+        params.extend([("format", "json"), ("action", "purge")]);
+        let q = self.post_query(params.into_iter())?;
+        let purged = q
+            .as_object()
+            .and_then(|x| x.get("purge"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("purge"))?
+            .clone();
+        Ok((purged, q))
+    }
+
+    /// Purges the server side caches of `titles`, so they are rendered
+    /// again, reporting the outcome for each of them keyed by the titles as
+    /// given. Sends a request per 50 titles.
+    pub fn purge_pages(&self, titles: &[&str]) -> Result<HashMap<String, PurgeStatus>> {
+        let mut status = HashMap::new();
+        for chunk in titles.chunks(50) {
+            let joined = join_values(chunk);
+            let (purged, q) = self.purge(("titles", &joined), false)?;
+            let normalized = q
+                .as_object()
+                .and_then(|x| x.get("normalized"))
+                .and_then(|x| x.as_array());
+            for title in chunk {
+                let resolved = normalized
+                    .and_then(|x| {
+                        x.iter()
+                            .find(|x| x.get("from").and_then(|x| x.as_str()) == Some(title))
+                    })
+                    .and_then(|x| x.get("to"))
+                    .and_then(|x| x.as_str())
+                    .unwrap_or(title);
+                let entry = purged
+                    .iter()
+                    .filter_map(|x| x.as_object())
+                    .find(|x| x.get("title").and_then(|x| x.as_str()) == Some(resolved));
+                let s = match entry {
+                    Some(x) if x.contains_key("invalid") => PurgeStatus::Invalid,
+                    Some(x) if x.contains_key("missing") => PurgeStatus::Missing,
+                    Some(x) if x.contains_key("purged") => PurgeStatus::Purged,
+                    Some(_) => PurgeStatus::RateLimited,
+                    None => PurgeStatus::Invalid,
+                };
+                status.insert((*title).to_owned(), s);
+            }
+        }
+        Ok(status)
+    }
+
+    /// Gets the plain text intros of `titles`, keyed by the titles as
+    /// given. Missing pages are left out, redirects are followed.
+    pub fn get_summaries(&self, titles: &[&str]) -> Result<HashMap<String, String>> {
+        let mut summaries = HashMap::new();
+        for chunk in titles.chunks(50) {
+            let joined = join_values(chunk);
+            // the API returns only some extracts per request, `query_pages`
+            // continues until all pages have one
+            let batch = self.query_pages(vec![
+                ("prop", "extracts"),
+                ("exintro", ""),
+                ("explaintext", ""),
+                ("exlimit", "max"),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                ("titles", &*joined),
+            ])?;
+            for title in chunk {
+                let resolved = batch.resolve(title);
+                let extract = batch
+                    .pages
+                    .values()
+                    .find(|x| x.get("title").and_then(|x| x.as_str()) == Some(resolved))
+                    .and_then(|x| x.get("extract"))
+                    .and_then(|x| x.as_str());
+                if let Some(extract) = extract {
+                    summaries.insert((*title).to_owned(), extract.to_owned());
+                }
+            }
+        }
+        Ok(summaries)
+    }
+
+    fn query_page_data(&self, key: &str, value: &str) -> Result<PageBatch> {
+        self.query_pages(vec![
+            ("prop", "extracts|info|pageprops"),
+            ("exintro", ""),
+            ("explaintext", ""),
+            ("exlimit", "max"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (key, value),
+        ])
+    }
+
+    /// Runs a query for several pages, following continuations until all
+    /// properties of all pages are returned, and merges the results.
+    fn query_pages(&self, params: Vec<(&str, &str)>) -> Result<PageBatch> {
+        let mut batch = PageBatch {
+            pages: BTreeMap::new(),
+            normalized: HashMap::new(),
+            redirects: HashMap::new(),
+            interwiki: HashSet::new(),
+        };
+        let mut cont: Option<IterElems> = None;
+        loop {
+            let mut params = params.clone();
+            push_continue(&mut params, &cont);
+            let q = self.query(params.into_iter())?;
+            let query = q
+                .as_object()
+                .and_then(|x| x.get("query"))
+                .and_then(|x| x.as_object())
+                .ok_or(Error::JSONPathError("query"))?;
+            for (name, map) in [
+                ("normalized", &mut batch.normalized),
+                ("redirects", &mut batch.redirects),
+            ] {
+                if let Some(list) = query.get(name).and_then(|x| x.as_array()) {
+                    map.extend(list.iter().filter_map(|x| {
+                        Some((
+                            x.get("from")?.as_str()?.to_owned(),
+                            x.get("to")?.as_str()?.to_owned(),
+                        ))
+                    }));
+                }
+            }
+            if let Some(list) = query.get("interwiki").and_then(|x| x.as_array()) {
+                batch.interwiki.extend(
+                    list.iter()
+                        .filter_map(|x| Some(x.get("title")?.as_str()?.to_owned())),
+                );
+            }
+            if let Some(pages) = query.get("pages").and_then(|x| x.as_object()) {
+                for (key, page) in pages {
+                    let page = match page.as_object() {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let merged = batch.pages.entry(key.clone()).or_default();
+                    for (k, v) in page {
+                        merged.entry(k.clone()).or_insert_with(|| v.clone());
+                    }
+                }
+            }
+            cont = parse_continue(&q)?;
+            if cont.is_none() {
+                break;
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Like the `cont!` macro, for `list` modules which return their
+    /// results in `query.<list>` instead of `query.pages`.
+    fn request_list(
+        &self,
+        list: &str,
+        params: Vec<(&str, &str)>,
+        cont: &Option<IterElems>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let mut params = params;
+        params.insert(0, ("list", list));
+        params.push(("format", "json"));
+        params.push(("action", "query"));
+        push_continue(&mut params, cont);
+        let q = self.query(params.into_iter())?;
+
+        let results = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get(list))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("query.<list>"))?
+            .to_vec();
+        Ok((results, parse_continue(&q)?))
+    }
+
+    /// Creates an iterator over the pages embedding the file `file_title`,
+    /// with or without the `File:` prefix.
+    pub fn image_usage(&self, file_title: &str) -> Result<ListIter<'_, A, iter::ImageUsage>> {
+        let title = if file_title.starts_with("File:") || file_title.starts_with("Image:") {
+            file_title.to_owned()
+        } else {
+            format!("File:{}", file_title)
+        };
+        ListIter::new(
+            self,
+            vec![
+                ("iutitle".to_owned(), title),
+                ("iulimit".to_owned(), "max".to_owned()),
+            ],
+        )
+    }
+
+    /// Creates an iterator over the members of `category`, with or without
+    /// the `Category:` prefix.
+    pub fn category_members(
+        &self,
+        category: &str,
+    ) -> Result<ListIter<'_, A, iter::CategoryMember>> {
+        self.category_members_opts(category, &CategoryMembersOptions::default())
+    }
+
+    /// Like `category_members`, filtering and sorting as given by `options`.
+    pub fn category_members_opts(
+        &self,
+        category: &str,
+        options: &CategoryMembersOptions,
+    ) -> Result<ListIter<'_, A, iter::CategoryMember>> {
+        let title = if category.starts_with("Category:") {
+            category.to_owned()
+        } else {
+            format!("Category:{}", category)
+        };
+        let mut params = vec![
+            ("cmtitle".to_owned(), title),
+            ("cmprop".to_owned(), "ids|title|type|timestamp".to_owned()),
+            ("cmlimit".to_owned(), "max".to_owned()),
+        ];
+        if !options.types.is_empty() {
+            let types: Vec<_> = options.types.iter().map(|x| x.as_str()).collect();
+            params.push(("cmtype".to_owned(), types.join("|")));
+        }
+        if options.sort_by_timestamp {
+            params.push(("cmsort".to_owned(), "timestamp".to_owned()));
+        }
+        ListIter::new(self, params)
+    }
+
+    /// Gets the number of members of `category`, with or without the
+    /// `Category:` prefix. Categories without members return zeros.
+    pub fn category_info(&self, category: &str) -> Result<CategoryInfo> {
+        let title = if category.starts_with("Category:") {
+            category.to_owned()
+        } else {
+            format!("Category:{}", category)
+        };
+        let q = self.query(
+            vec![
+                ("prop", "categoryinfo"),
+                ("format", "json"),
+                ("action", "query"),
+                ("titles", &*title),
+            ]
+            .into_iter(),
+        )?;
+        let info = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.values().next())
+            .and_then(|x| x.get("categoryinfo"))
+            .and_then(|x| x.as_object());
+        let count = |name| {
+            info.and_then(|x| x.get(name))
+                .and_then(|x| x.as_u64())
+                .unwrap_or(0)
+        };
+        Ok(CategoryInfo {
+            pages: count("pages"),
+            subcats: count("subcats"),
+            files: count("files"),
+        })
+    }
+
+    /// Like `category_members`, also listing the members of subcategories
+    /// up to `max_depth` levels deep (0 lists only direct members). The tree
+    /// is walked breadth-first, every member is returned once even if the
+    /// categories form a cycle, and the iterator stops after `max_pages`
+    /// members. Subcategories are fetched as the iterator advances.
+    pub fn category_members_recursive(
+        &self,
+        category: &str,
+        max_depth: u8,
+        max_pages: usize,
+    ) -> iter::CategoryTraversal<'_, A> {
+        iter::CategoryTraversal::new(self, category, max_depth, max_pages)
+    }
+
+    /// Creates an iterator over all pages in `namespace` in alphabetical
+    /// order, only those starting with `prefix` if given.
+    pub fn all_pages(
+        &self,
+        prefix: Option<&str>,
+        namespace: u32,
+    ) -> Result<ListIter<'_, A, iter::AllPage>> {
+        self.all_pages_opts(prefix, namespace, &AllPagesOptions::default())
+    }
+
+    /// Like `all_pages`, filtering as given by `options`.
+    pub fn all_pages_opts(
+        &self,
+        prefix: Option<&str>,
+        namespace: u32,
+        options: &AllPagesOptions,
+    ) -> Result<ListIter<'_, A, iter::AllPage>> {
+        let mut params = vec![
+            ("apnamespace".to_owned(), format!("{}", namespace)),
+            ("aplimit".to_owned(), "max".to_owned()),
+        ];
+        if let Some(prefix) = prefix {
+            params.push(("apprefix".to_owned(), prefix.to_owned()));
+        }
+        if let Some(redirects) = options.redirects {
+            params.push(("apfilterredir".to_owned(), redirects.as_str().to_owned()));
+        }
+        if let Some(min_size) = options.min_size {
+            params.push(("apminsize".to_owned(), format!("{}", min_size)));
+        }
+        if let Some(max_size) = options.max_size {
+            params.push(("apmaxsize".to_owned(), format!("{}", max_size)));
+        }
+        ListIter::new(self, params)
+    }
+
+    /// Creates an iterator over the recent changes of the wiki, newest
+    /// first.
+    pub fn recent_changes(
+        &self,
+        options: RecentChangesOptions,
+    ) -> Result<ListIter<'_, A, iter::RecentChange>> {
+        ListIter::new(self, change_params("rc", Limit::Max, options))
+    }
+
+    /// Creates an iterator over the changes to the pages watched by the
+    /// logged in user, newest first, fetching `limit` changes per request.
+    /// Fails with `Error::NotLoggedIn` without a login.
+    pub fn watchlist(
+        &self,
+        limit: Limit,
+        options: RecentChangesOptions,
+    ) -> Result<ListIter<'_, A, iter::WatchlistChange>> {
+        ListIter::new(self, change_params("wl", limit, options))
+    }
+
+    /// Returns the latest `limit` edits of `user` (a user name or an IP
+    /// address), newest first. Limits above 500 are fetched in several
+    /// requests.
+    pub fn user_contributions(&self, user: &str, limit: u32) -> Result<Vec<Contribution>> {
+        self.user_contributions_in_namespaces(user, limit, &[])
+    }
+
+    /// Like `user_contributions`, only listing edits in `namespaces`.
+    pub fn user_contributions_in_namespaces(
+        &self,
+        user: &str,
+        limit: u32,
+        namespaces: &[u32],
+    ) -> Result<Vec<Contribution>> {
+        let namespaces = join_namespaces(namespaces);
+        let mut contributions = Vec::new();
+        let mut cont: Option<IterElems> = None;
+        while (contributions.len() as u32) < limit {
+            let uclimit = format!("{}", (limit - contributions.len() as u32).min(500));
+            let mut params = vec![
+                ("ucuser", user),
+                ("ucprop", "ids|title|timestamp|comment|size|sizediff"),
+                ("uclimit", &*uclimit),
+            ];
+            if !namespaces.is_empty() {
+                params.push(("ucnamespace", &*namespaces));
+            }
+            let (results, next) = self.request_list("usercontribs", params, &cont)?;
+            contributions.extend(results.iter().filter_map(parse_contribution));
+            cont = next;
+            if cont.is_none() {
+                break;
+            }
+        }
+        contributions.truncate(limit as usize);
+        Ok(contributions)
+    }
+
+    /// Renders `wikitext` to html, as if it was the content of the page
+    /// `title` (which matters for magic words like `{{PAGENAME}}`). The
+    /// text is sent in a POST request, so it can be arbitrarily long.
+    pub fn render_wikitext(&self, wikitext: &str, title: Option<&str>) -> Result<String> {
+        let mut params = vec![
+            ("prop", "text"),
+            ("contentmodel", "wikitext"),
+            ("disablelimitreport", ""),
+            ("text", wikitext),
+        ];
+        if let Some(title) = title {
+            params.push(("title", title));
+        }
+        params.extend(vec![("format", "json"), ("action", "parse")]);
+        let q = self.post_query(params.into_iter())?;
+
+        Ok(q.as_object()
+            .and_then(|x| x.get("parse"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("text"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("*"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("parse.text.*"))?
+            .to_owned())
+    }
+
+    /// Fetches the token to send with edits and other changes, which is
+    /// tied to the session of `login`.
+    pub fn get_csrf_token(&self) -> Result<String> {
+        self.token("csrf")
+    }
+
+    /// Uploads `bytes` as the file `filename` (without the `File:`
+    /// prefix), with the upload `comment` and `text` as the description
+    /// page of a new file. The file is sent in a single request, so it
+    /// should be at most a few megabytes. Warnings like an existing file
+    /// with the same name are returned as `UploadResult::Warnings`.
+    pub fn upload(
+        &self,
+        filename: &str,
+        bytes: &[u8],
+        comment: &str,
+        text: &str,
+    ) -> Result<UploadResult> {
+        self.upload_file(filename, bytes, comment, text, false)
+    }
+
+    /// Like `upload`, saving the file despite warnings, e.g. to upload a
+    /// new version of an existing file.
+    pub fn upload_ignoring_warnings(
+        &self,
+        filename: &str,
+        bytes: &[u8],
+        comment: &str,
+        text: &str,
+    ) -> Result<UploadResult> {
+        self.upload_file(filename, bytes, comment, text, true)
+    }
+
+    fn upload_file(
+        &self,
+        filename: &str,
+        bytes: &[u8],
+        comment: &str,
+        text: &str,
+        ignore_warnings: bool,
+    ) -> Result<UploadResult> {
+        let token = self.get_csrf_token()?;
+        let mut params = vec![("filename", filename), ("comment", comment), ("text", text)];
+        if ignore_warnings {
+            params.push(("ignorewarnings", "1"));
+        }
+        params.extend([("format", "json"), ("action", "upload"), ("token", &*token)]);
+        let file = http::FilePart {
+            name: "file",
+            filename,
+            content: bytes,
+        };
+        let q = self.request(true, params, Some(&file), &[])?;
+
+        let upload = q
+            .as_object()
+            .and_then(|x| x.get("upload"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("upload"))?;
+        match upload.get("result").and_then(|x| x.as_str()) {
+            Some("Success") => Ok(UploadResult::Success {
+                filename: upload
+                    .get("filename")
+                    .and_then(|x| x.as_str())
+                    .ok_or(Error::JSONPathError("upload.filename"))?
+                    .to_owned(),
+                url: upload
+                    .get("imageinfo")
+                    .and_then(|x| x.get("url"))
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_owned()),
+            }),
+            Some("Warning") => {
+                let warnings = upload
+                    .get("warnings")
+                    .and_then(|x| x.as_object())
+                    .ok_or(Error::JSONPathError("upload.warnings"))?;
+                Ok(UploadResult::Warnings(
+                    warnings
+                        .iter()
+                        .map(|(code, value)| match (&**code, value) {
+                            ("exists", serde_json::Value::String(x)) => {
+                                UploadWarning::Exists(x.clone())
+                            }
+                            ("duplicate", serde_json::Value::Array(x)) => UploadWarning::Duplicate(
+                                x.iter()
+                                    .filter_map(|x| x.as_str().map(|x| x.to_owned()))
+                                    .collect(),
+                            ),
+                            _ => UploadWarning::Other {
+                                code: code.clone(),
+                                value: value.to_string(),
+                            },
+                        })
+                        .collect(),
+                ))
+            }
+            result => Err(Error::Api {
+                code: result.unwrap_or("").to_lowercase(),
+                info: "The upload was not saved".to_owned(),
+            }),
+        }
+    }
+
+    /// Like `query`, asking a caching client like `http::CachedClient` for
+    /// a fresh response, for answers that change with the session.
+    fn uncached_query(&self, args: Vec<(&str, &str)>) -> Result<serde_json::Value> {
+        self.request(false, args, None, &[("Cache-Control", "no-cache")])
+    }
+
+    /// Fetches a token of the given `kind`, like `login` or `csrf`. Tokens
+    /// are never taken from a cache, as they change with the session.
+    fn token(&self, kind: &str) -> Result<String> {
+        let q = self.uncached_query(vec![
+            ("meta", "tokens"),
+            ("type", kind),
+            ("format", "json"),
+            ("action", "query"),
+        ])?;
+        let field = format!("{}token", kind);
+        Ok(q.as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("tokens"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get(&field))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.tokens"))?
+            .to_owned())
+    }
+
+    /// Logs in with a bot password, created in `Special:BotPasswords`,
+    /// where `username` looks like `User@bot`. The session is kept by the
+    /// client in cookies, so later requests are made as that user. Fails
+    /// with `Error::LoginFailed` if the wiki refuses the credentials.
+    pub fn login(&self, username: &str, bot_password: &str) -> Result<()> {
+        let token = self.token("login")?;
+        let q = self.post_query(
+            vec![
+                ("lgname", username),
+                ("lgpassword", bot_password),
+                ("lgtoken", &*token),
+                ("format", "json"),
+                ("action", "login"),
+            ]
+            .into_iter(),
+        )?;
+        let login = q
+            .as_object()
+            .and_then(|x| x.get("login"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("login"))?;
+        match login.get("result").and_then(|x| x.as_str()) {
+            Some("Success") => Ok(()),
+            result => Err(Error::LoginFailed(
+                login
+                    .get("reason")
+                    .and_then(|x| x.as_str())
+                    .or(result)
+                    .unwrap_or("")
+                    .to_owned(),
+            )),
+        }
+    }
+
+    /// Ends the session started by `login`.
+    pub fn logout(&self) -> Result<()> {
+        let token = self.get_csrf_token()?;
+        self.post_query(
+            vec![("token", &*token), ("format", "json"), ("action", "logout")].into_iter(),
+        )?;
+        Ok(())
+    }
+
+    /// Whether requests are made as a logged in user.
+    pub fn is_logged_in(&self) -> Result<bool> {
+        Ok(!self.whoami()?.anonymous)
+    }
+
+    /// Sends `token`, an OAuth 2.0 access token like an owner-only one, as
+    /// `Authorization: Bearer` header with every request. While it's set,
+    /// the default client doesn't send the cookies of `login`.
+    pub fn set_oauth_token(&mut self, token: String) {
+        self.client.bearer_token(token);
+    }
+
+    /// Returns the user requests are made as, to check a login or an OAuth
+    /// token works and which rights it grants.
+    pub fn whoami(&self) -> Result<UserInfo> {
+        let q = self.uncached_query(vec![
+            ("meta", "userinfo"),
+            ("uiprop", "groups|rights"),
+            ("format", "json"),
+            ("action", "query"),
+        ])?;
+        let userinfo = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("userinfo"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query.userinfo"))?;
+        let strings = |name| {
+            userinfo
+                .get(name)
+                .and_then(|x| x.as_array())
+                .map(|x| {
+                    x.iter()
+                        .filter_map(|x| x.as_str().map(|x| x.to_owned()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Ok(UserInfo {
+            id: userinfo.get("id").and_then(|x| x.as_u64()).unwrap_or(0),
+            name: userinfo
+                .get("name")
+                .and_then(|x| x.as_str())
+                .ok_or(Error::JSONPathError("query.userinfo.name"))?
+                .to_owned(),
+            anonymous: userinfo.contains_key("anon"),
+            groups: strings("groups"),
+            rights: strings("rights"),
+        })
+    }
+
+    /// Creates a new `Page` given a `title`.
+    pub fn page_from_title(&self, title: String) -> Page<'_, A> {
+        Page::from_title(self, title)
+    }
+
+    /// Creates a new `Page` given a `pageid`.
+    pub fn page_from_pageid(&self, pageid: String) -> Page<'_, A> {
+        Page::from_pageid(self, pageid)
+    }
+}
+
+/// An entry of the reference list of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    /// Id of the entry, like `cite_note-1`, which is also the anchor the
+    /// reference markers link to.
+    pub id: String,
+    /// Plain text of the entry.
+    pub text: String,
+    /// The first external link that isn't a web archive.
+    pub url: Option<String>,
+    /// Text of the link to `url`, usually the title of the source.
+    pub title: Option<String>,
+    /// The first link to a web archive like `web.archive.org`.
+    pub archive_url: Option<String>,
+}
+
+impl Citation {
+    /// Whether the source has an archived copy.
+    pub fn is_archived(&self) -> bool {
+        self.archive_url.is_some()
+    }
+}
+
+/// A protection applied to a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Protection {
+    /// The protected action, e.g. `edit` or `move`.
+    pub kind: String,
+    /// Group allowed to perform the action, e.g. `autoconfirmed` or `sysop`.
+    pub level: String,
+    /// Expiry timestamp or `infinity`.
+    pub expiry: String,
+    /// Whether the protection cascades to pages transcluded by this page.
+    pub cascade: bool,
+    /// The page this protection cascades from, `None` if it was applied
+    /// to this page directly.
+    pub source: Option<String>,
+}
+
+impl Protection {
+    /// Gets the expiry timestamp, `None` if the protection is indefinite.
+    pub fn expires(&self) -> Option<&str> {
+        match &*self.expiry {
+            "infinity" | "infinite" | "indefinite" => None,
+            x => Some(x),
+        }
+    }
+}
+
+/// Basic facts about a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageInfo {
+    pub pageid: u64,
+    pub title: String,
+    /// Size of the page in bytes.
+    pub length: u64,
+    /// Timestamp of the last time the page was touched, in RFC3339 format.
+    pub touched: String,
+    pub is_redirect: bool,
+    pub full_url: String,
+    /// Number of users watching the page, hidden by the API for pages with
+    /// few watchers.
+    pub watchers: Option<u64>,
+    pub protection: Vec<Protection>,
+}
+
+/// Data of a page fetched by `Wikipedia::get_pages`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageData {
+    pub pageid: u64,
+    /// Title after normalization and redirects.
+    pub title: String,
+    pub ns: i64,
+    /// Plain text intro of the page.
+    pub extract: Option<String>,
+    /// Size of the page in bytes.
+    pub length: u64,
+    pub touched: String,
+    /// Page properties like `wikibase_item` or `disambiguation`.
+    pub pageprops: BTreeMap<String, String>,
+    /// The titles or page ids that were requested and resolved to this
+    /// page.
+    pub requested: Vec<String>,
+}
+
+/// Whether a title names a page, as returned by `Wikipedia::pages_exist`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleStatus {
+    Exists,
+    Missing,
+    /// The title can't name a page of this wiki, e.g. because it contains
+    /// `#` or `|`, or is an interwiki link.
+    Invalid,
+}
+
+/// Outcome of purging a title, as returned by `Wikipedia::purge_pages`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeStatus {
+    Purged,
+    Missing,
+    Invalid,
+    /// The page exists but wasn't purged, as the user made too many purges.
+    RateLimited,
+}
+
+/// An entry in the revision history of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    pub revid: u64,
+    /// Id of the previous revision, 0 for the first revision of a page.
+    pub parentid: u64,
+    pub timestamp: String,
+    /// Author of the revision, empty if it was hidden.
+    pub user: String,
+    /// Edit summary, empty if none or hidden.
+    pub comment: String,
+    /// Size of the page in bytes after this revision.
+    pub size: u64,
+}
+
+/// A registered user who edited a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contributor {
+    pub userid: u64,
+    pub name: String,
+}
+
+/// The editors of a page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Contributors {
+    /// Registered users, ordered by user id.
+    pub users: Vec<Contributor>,
+    /// Number of anonymous editors, not affected by group filters.
+    pub anonymous: u64,
+}
+
+/// Parameters for `Page::get_contributors_opts`. Only one of `groups` and
+/// `exclude_groups` may be set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContributorsOptions {
+    /// Only list users in these groups, like `sysop`.
+    pub groups: Vec<String>,
+    /// Leave out users in these groups, like `bot`.
+    pub exclude_groups: Vec<String>,
+}
+
+/// Quality class given to an article by a WikiProject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QualityClass {
+    /// `FA`
+    Featured,
+    A,
+    /// `GA`
+    Good,
+    B,
+    C,
+    Start,
+    Stub,
+    /// `FL`
+    FeaturedList,
+    List,
+    /// No class given yet.
+    Unassessed,
+    /// Classes specific to some projects, like `Redirect` or `Template`.
+    Other(String),
+}
+
+impl QualityClass {
+    fn from_api(class: &str) -> QualityClass {
+        match class {
+            "FA" => QualityClass::Featured,
+            "A" => QualityClass::A,
+            "GA" => QualityClass::Good,
+            "B" => QualityClass::B,
+            "C" => QualityClass::C,
+            "Start" => QualityClass::Start,
+            "Stub" => QualityClass::Stub,
+            "FL" => QualityClass::FeaturedList,
+            "List" => QualityClass::List,
+            "" | "Unassessed" => QualityClass::Unassessed,
+            x => QualityClass::Other(x.to_owned()),
+        }
+    }
+}
+
+/// Importance of an article to a WikiProject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Importance {
+    Top,
+    High,
+    Mid,
+    Low,
+    /// The project doesn't rate the importance of this page, `NA`.
+    NotApplicable,
+    /// No importance given yet.
+    Unknown,
+    Other(String),
+}
+
+impl Importance {
+    fn from_api(importance: &str) -> Importance {
+        match importance {
+            "Top" => Importance::Top,
+            "High" => Importance::High,
+            "Mid" => Importance::Mid,
+            "Low" => Importance::Low,
+            "NA" => Importance::NotApplicable,
+            "" | "Unknown" => Importance::Unknown,
+            x => Importance::Other(x.to_owned()),
+        }
+    }
+}
+
+/// Rating of an article by a WikiProject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assessment {
+    pub class: QualityClass,
+    pub importance: Importance,
+}
+
+/// A section of a page and its subsections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Depth in the table of contents, starting at 1.
+    pub toclevel: u32,
+    /// Heading level, 2 for `== Heading ==`.
+    pub level: u32,
+    /// Section index, numeric for sections of the page itself and like
+    /// `T-1` for sections coming from templates.
+    pub index: String,
+    /// Number in the table of contents, e.g. `2.1`.
+    pub number: String,
+    /// The heading text.
+    pub line: String,
+    pub anchor: String,
+    /// Offset of the heading in the wikitext, `None` for template sections.
+    pub byteoffset: Option<u64>,
+    pub children: Vec<Section>,
+}
+
+/// A scaled version of the representative image of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    /// Url of the scaled image.
+    pub source: String,
+    pub width: u32,
+    pub height: u32,
+    /// Name of the original file, without the `File:` prefix.
+    pub file: String,
+}
+
+/// Metadata of an uploaded file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInfo {
+    /// Title of the file page, including the `File:` prefix.
+    pub title: String,
+    /// Url of the original file.
+    pub url: String,
+    pub description_url: String,
+    pub width: u64,
+    pub height: u64,
+    /// Size of the file in bytes.
+    pub size: u64,
+    pub mime: String,
+    /// The `extmetadata` of the file (license, author, description, ...)
+    /// keyed by field name. Some values contain html.
+    pub metadata: BTreeMap<String, String>,
+    /// Url of a scaled version, only requested by `image_info_scaled` and
+    /// `image_info_bounded`. For documents like PDFs this is a rendering
+    /// of the first page.
+    pub thumb_url: Option<String>,
+    pub thumb_width: Option<u64>,
+    pub thumb_height: Option<u64>,
+}
+
+impl ImageInfo {
+    /// Short name of the license, e.g. `CC BY-SA 4.0`.
+    pub fn license(&self) -> Option<&str> {
+        self.metadata.get("LicenseShortName").map(|x| &**x)
+    }
+
+    /// Author of the file, may contain html.
+    pub fn artist(&self) -> Option<&str> {
+        self.metadata.get("Artist").map(|x| &**x)
+    }
+}
+
+#[derive(Debug)]
+enum TitlePageId {
+    Title(String),
+    PageId(String),
+    /// Both are known, e.g. when listed by the API. Queries use the pageid.
+    Both {
+        title: String,
+        pageid: String,
+    },
+}
+
+impl TitlePageId {
+    fn query_param(&self) -> (String, String) {
+        match *self {
+            TitlePageId::Title(ref s) => ("titles".to_owned(), s.clone()),
+            TitlePageId::PageId(ref s) => ("pageids".to_owned(), s.clone()),
+            TitlePageId::Both { ref pageid, .. } => ("pageids".to_owned(), pageid.clone()),
+        }
+    }
+
+    /// Parameter identifying the page in lists like `list=backlinks`,
+    /// e.g. `bltitle` or `blpageid` for the prefix `bl`.
+    fn list_param(&self, prefix: &str) -> (String, String) {
+        match *self {
+            TitlePageId::Title(ref s) => (format!("{}title", prefix), s.clone()),
+            TitlePageId::PageId(ref s) | TitlePageId::Both { pageid: ref s, .. } => {
+                (format!("{}pageid", prefix), s.clone())
+            }
+        }
+    }
+
+    fn title(&self) -> Option<&str> {
+        match *self {
+            TitlePageId::Title(ref s) | TitlePageId::Both { title: ref s, .. } => Some(s),
+            TitlePageId::PageId(_) => None,
+        }
+    }
+
+    fn pageid(&self) -> Option<&str> {
+        match *self {
+            TitlePageId::PageId(ref s) | TitlePageId::Both { pageid: ref s, .. } => Some(s),
+            TitlePageId::Title(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<'a, A: 'a + http::HttpClient> {
+    wikipedia: &'a Wikipedia<A>,
+    identifier: TitlePageId,
+    /// Canonical title once a redirect or normalization was seen.
+    resolved: Mutex<Option<String>>,
+}
+
+/// A wikipedia article.
+impl<'a, A: http::HttpClient> Page<'a, A> {
+    /// Creates a new `Page` given a `title`.
+    pub fn from_title(wikipedia: &'a Wikipedia<A>, title: String) -> Page<'a, A> {
+        Page {
+            wikipedia,
+            identifier: TitlePageId::Title(title),
+            resolved: Mutex::new(None),
+        }
+    }
+
+    /// Creates a new `Page` given a `pageid`.
+    pub fn from_pageid(wikipedia: &'a Wikipedia<A>, pageid: String) -> Page<'a, A> {
+        Page {
+            wikipedia,
+            identifier: TitlePageId::PageId(pageid),
+            resolved: Mutex::new(None),
+        }
+    }
+
+    /// Gets the `Page`'s `pageid`.
+    pub fn get_pageid(&self) -> Result<String> {
+        match self.identifier {
+            TitlePageId::PageId(ref s) | TitlePageId::Both { pageid: ref s, .. } => Ok(s.clone()),
+            TitlePageId::Title(_) => {
+                let qp = self.identifier.query_param();
+                let q = self.wikipedia.query(
+                    vec![
+                        ("prop", "info|pageprops"),
+                        ("inprop", "url"),
+                        ("ppprop", "disambiguation"),
+                        ("redirects", ""),
+                        ("format", "json"),
+                        ("action", "query"),
+                        (&*qp.0, &*qp.1),
+                    ]
+                    .into_iter(),
+                )?;
+
+                if let Some(r) = self.redirect(&q) {
+                    return Page::from_title(self.wikipedia, r).get_pageid();
+                }
+                let pages = q
+                    .as_object()
+                    .and_then(|x| x.get("query"))
+                    .and_then(|x| x.as_object())
+                    .and_then(|x| x.get("pages"))
+                    .and_then(|x| x.as_object())
+                    .ok_or(Error::JSONPathError("query.pages"))?;
+                if let Some(page) = pages.values().next() {
+                    self.check_missing(page)?;
+                }
+                pages
+                    .keys()
+                    .next()
+                    .cloned()
+                    .ok_or(Error::JSONPathError("query.pages[]"))
+            }
+        }
+    }
+
+    /// Gets the `Page`'s `title`.
+    pub fn get_title(&self) -> Result<String> {
+        if let Some(ref title) = *self.resolved.lock().unwrap() {
+            return Ok(title.clone());
+        }
+        match self.identifier {
+            TitlePageId::Title(ref s) | TitlePageId::Both { title: ref s, .. } => Ok(s.clone()),
+            TitlePageId::PageId(_) => {
+                let qp = self.identifier.query_param();
+                let q = self.wikipedia.query(
+                    vec![
+                        ("prop", "info|pageprops"),
+                        ("inprop", "url"),
+                        ("ppprop", "disambiguation"),
+                        ("redirects", ""),
+                        ("format", "json"),
+                        ("action", "query"),
+                        (&*qp.0, &*qp.1),
+                    ]
+                    .into_iter(),
+                )?;
+
+                if let Some(r) = self.redirect(&q) {
+                    return Ok(r);
+                }
+                let pages = q
+                    .as_object()
+                    .and_then(|x| x.get("query"))
+                    .and_then(|x| x.as_object())
+                    .and_then(|x| x.get("pages"))
+                    .and_then(|x| x.as_object())
+                    .ok_or(Error::JSONPathError("query.pages"))?;
+                let page = match pages.values().next() {
+                    Some(p) => p,
+                    None => return Err(Error::JSONPathError("query.pages[]")),
+                };
+                self.check_missing(page)?;
+                Ok(page
+                    .as_object()
+                    .and_then(|x| x.get("title"))
+                    .and_then(|x| x.as_str())
+                    .ok_or(Error::JSONPathError("query.pages[].title"))?
+                    .to_owned())
+            }
+        }
+    }
+
+    /// If the `Page` redirects to another one it returns its title, otherwise
+    /// returns None.
+    /// The target title is remembered and returned by `get_title`.
+    fn redirect(&self, q: &serde_json::Value) -> Option<String> {
+        let to = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("redirects"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("to"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned())?;
+        *self.resolved.lock().unwrap() = Some(to.clone());
+        Some(to)
+    }
+
+    /// Resolves title normalization and redirects, returning the canonical
+    /// title if it differs from the one the `Page` was created with (or, for
+    /// pages created by pageid, if the page is a redirect). Afterwards
+    /// `get_title` returns the canonical title. Fragments of redirects to a
+    /// section are not part of the title.
+    pub fn resolve_redirect(&self) -> Result<Option<String>> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.client_query(
+            vec![
+                ("prop", "info"),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        let page = self.get_existing_page(&q)?;
+        let title = page
+            .as_object()
+            .and_then(|x| x.get("title"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.pages[].title"))?
+            .to_owned();
+        let redirected = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .is_some_and(|x| x.contains_key("redirects"));
+        let changed = match self.identifier.title() {
+            Some(original) => original != title,
+            None => redirected,
+        };
+        *self.resolved.lock().unwrap() = Some(title.clone());
+        Ok(if changed { Some(title) } else { None })
+    }
+
+    /// Given a parsed response, usually we access the first page with the data
+    fn get_first_page<'parsed>(
+        &self,
+        data: &'parsed serde_json::Value,
+    ) -> Option<&'parsed serde_json::Value> {
+        let pages = data
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("pages"))
+            .and_then(|x| x.as_object());
+        let pageid = pages?.keys().next()?;
+        pages.unwrap().get(pageid)
+    }
+
+    /// Like `get_first_page`, but returns `Error::PageNotFound` if the page
+    /// is missing.
+    fn get_existing_page<'parsed>(
+        &self,
+        data: &'parsed serde_json::Value,
+    ) -> Result<&'parsed serde_json::Value> {
+        let page = self
+            .get_first_page(data)
+            .ok_or(Error::JSONPathError("query.pages[]"))?;
+        self.check_missing(page)?;
+        Ok(page)
+    }
+
+    /// Like `get_existing_page`, but returns `None` instead of failing when
+    /// the response has no page data, as for getters of optional fields.
+    fn get_optional_page<'parsed>(
+        &self,
+        data: &'parsed serde_json::Value,
+    ) -> Result<Option<&'parsed serde_json::Map<String, serde_json::Value>>> {
+        let page = match self.get_first_page(data) {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        self.check_missing(page)?;
+        Ok(page.as_object())
+    }
+
+    /// Returns `Error::PageNotFound` if `page` is marked as missing or
+    /// invalid.
+    fn check_missing(&self, page: &serde_json::Value) -> Result<()> {
+        let page = match page.as_object() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if page.contains_key("missing") || page.contains_key("invalid") {
+            let title = page
+                .get("title")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_owned())
+                .or_else(|| self.identifier.title().map(|x| x.to_owned()))
+                .unwrap_or_default();
+            return Err(Error::PageNotFound { title });
+        }
+        Ok(())
+    }
+
+    /// Gets the length, last touched timestamp, url, protections and
+    /// number of watchers of the `Page`.
+    pub fn get_info(&self) -> Result<PageInfo> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "info"),
+                ("inprop", "watchers|protection|url"),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        let page = self.get_existing_page(&q)?;
+        let page = page
+            .as_object()
+            .ok_or(Error::JSONPathError("query.pages[]"))?;
+        Ok(PageInfo {
+            pageid: page
+                .get("pageid")
+                .and_then(|x| x.as_u64())
+                .ok_or(Error::JSONPathError("query.pages[].pageid"))?,
+            title: page
+                .get("title")
+                .and_then(|x| x.as_str())
+                .ok_or(Error::JSONPathError("query.pages[].title"))?
+                .to_owned(),
+            length: page.get("length").and_then(|x| x.as_u64()).unwrap_or(0),
+            touched: page
+                .get("touched")
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned(),
+            is_redirect: page.contains_key("redirect"),
+            full_url: page
+                .get("fullurl")
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned(),
+            watchers: page.get("watchers").and_then(|x| x.as_u64()),
+            protection: page
+                .get("protection")
+                .and_then(|x| x.as_array())
+                .map(|x| x.iter().filter_map(parse_protection).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Gets the protections of the page, including those cascading from
+    /// other pages. An empty list means anyone can edit and move it.
+    pub fn get_protection(&self) -> Result<Vec<Protection>> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "info"),
+                ("inprop", "protection"),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_protection();
+        }
+
+        Ok(self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("protection"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("query.pages[].protection"))?
+            .iter()
+            .filter_map(parse_protection)
+            .collect())
+    }
+
+    /// Returns whether the `Page` exists, without fetching any content.
+    pub fn exists(&self) -> Result<bool> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "info"),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        match self.get_existing_page(&q) {
+            Ok(_) => Ok(true),
+            Err(Error::PageNotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the markdown content of the article.
+    pub fn get_content(&self) -> Result<String> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "extracts|revisions"),
+                ("explaintext", ""),
+                ("rvprop", "ids"),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_content();
+        }
+
+        Ok(self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("extract"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.pages[].extract"))?
+            .to_owned())
+    }
+
+    /// Gets the html content of the article.
+    pub fn get_html_content(&self) -> Result<String> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "revisions"),
+                ("rvprop", "content"),
+                ("rvlimit", "1"),
+                ("rvparse", ""),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_html_content();
+        }
+
+        Ok(self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("revisions"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("*"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.pages[].revisions[].*"))?
+            .to_owned())
+    }
+
+    /// Gets the wikitext source of the article.
+    pub fn get_wikitext(&self) -> Result<String> {
+        self.fetch_wikitext(None)
+    }
+
+    /// Gets the wikitext source of a single section of the article, by
+    /// index (0 is the lead).
+    pub fn get_section_wikitext(&self, section: u32) -> Result<String> {
+        self.fetch_wikitext(Some(format!("{}", section)))
+    }
+
+    fn fetch_wikitext(&self, section: Option<String>) -> Result<String> {
+        let qp = self.identifier.query_param();
+        let mut params = vec![
+            ("prop", "revisions"),
+            ("rvprop", "content"),
+            ("rvslots", "main"),
+        ];
+        if let Some(ref section) = section {
+            params.push(("rvsection", section));
+        }
+        params.extend(vec![
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ]);
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).fetch_wikitext(section);
+        }
+
+        self.get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("revisions"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(revision_content)
+            .ok_or(Error::JSONPathError("query.pages[].revisions[]"))
+    }
+
+    /// Parses the named parameters of the first `{{Infobox ...}}` template
+    /// in the wikitext of the article, or returns `None` if it has none.
+    /// Values are returned as raw wikitext. The parser is best-effort:
+    /// nested templates and links are handled, tables inside values are
+    /// not.
+    pub fn get_infobox(&self) -> Result<Option<HashMap<String, String>>> {
+        Ok(wikitext::parse_infobox(&self.get_wikitext()?))
+    }
+
+    /// Gets the latest `limit` revisions of the article, newest first.
+    /// Limits above 500 are fetched in several requests.
+    pub fn get_revisions(&self, limit: u32) -> Result<Vec<Revision>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let qp = self.identifier.query_param();
+        let rvlimit = format!("{}", limit.min(500));
+        let params = vec![
+            ("prop", "revisions"),
+            ("rvprop", "ids|timestamp|user|comment|size"),
+            ("rvlimit", &*rvlimit),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let mut revisions = Vec::new();
+        let redirect = self.query_continued(params, |page| {
+            revisions.extend(
+                page.as_object()
+                    .and_then(|x| x.get("revisions"))
+                    .and_then(|x| x.as_array())
+                    .ok_or(Error::JSONPathError("query.pages[].revisions"))?
+                    .iter()
+                    .filter_map(parse_revision),
+            );
+            Ok((revisions.len() as u32) < limit)
+        })?;
+        if let Some(r) = redirect {
+            return Page::from_title(self.wikipedia, r).get_revisions(limit);
+        }
+        revisions.truncate(limit as usize);
+        Ok(revisions)
+    }
+
+    /// Gets the registered users who edited the page and the number of
+    /// anonymous editors.
+    pub fn get_contributors(&self) -> Result<Contributors> {
+        self.get_contributors_opts(&ContributorsOptions::default())
+    }
+
+    /// Like `get_contributors`, filtering users by group as given by
+    /// `options`.
+    pub fn get_contributors_opts(&self, options: &ContributorsOptions) -> Result<Contributors> {
+        if !options.groups.is_empty() && !options.exclude_groups.is_empty() {
+            return Err(Error::InvalidParameter("exclude_groups".to_string()));
+        }
+        let qp = self.identifier.query_param();
+        let groups = options.groups.join("|");
+        let exclude_groups = options.exclude_groups.join("|");
+        let mut params = vec![
+            ("prop", "contributors"),
+            ("pclimit", "max"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        if !groups.is_empty() {
+            params.push(("pcgroup", &*groups));
+        }
+        if !exclude_groups.is_empty() {
+            params.push(("pcexcludegroup", &*exclude_groups));
+        }
+        let mut contributors = Contributors::default();
+        let redirect = self.query_continued(params, |page| {
+            let page = page
+                .as_object()
+                .ok_or(Error::JSONPathError("query.pages[]"))?;
+            // only the first batch counts the anonymous editors
+            if let Some(anonymous) = page.get("anoncontributors").and_then(|x| x.as_u64()) {
+                contributors.anonymous = anonymous;
+            }
+            if let Some(users) = page.get("contributors").and_then(|x| x.as_array()) {
+                contributors.users.extend(users.iter().filter_map(|x| {
+                    Some(Contributor {
+                        userid: x.get("userid")?.as_u64()?,
+                        name: x.get("name")?.as_str()?.to_owned(),
+                    })
+                }));
+            }
+            Ok(true)
+        })?;
+        if let Some(r) = redirect {
+            return Page::from_title(self.wikipedia, r).get_contributors_opts(options);
+        }
+        Ok(contributors)
+    }
+
+    /// Gets the ratings of the article by the WikiProjects it belongs to,
+    /// keyed by project name. Only wikis with the PageAssessments
+    /// extension, like the English Wikipedia, return any.
+    pub fn get_assessments(&self) -> Result<HashMap<String, Assessment>> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "pageassessments"),
+            ("palimit", "max"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let mut assessments = HashMap::new();
+        let redirect = self.query_continued(params, |page| {
+            if let Some(projects) = page
+                .as_object()
+                .and_then(|x| x.get("pageassessments"))
+                .and_then(|x| x.as_object())
+            {
+                for (project, rating) in projects {
+                    let field = |name| rating.get(name).and_then(|x| x.as_str()).unwrap_or("");
+                    assessments.insert(
+                        project.clone(),
+                        Assessment {
+                            class: QualityClass::from_api(field("class")),
+                            importance: Importance::from_api(field("importance")),
+                        },
+                    );
+                }
+            }
+            Ok(true)
+        })?;
+        if let Some(r) = redirect {
+            return Page::from_title(self.wikipedia, r).get_assessments();
+        }
+        Ok(assessments)
+    }
+
+    /// Checks that the page object in a response is this `Page`.
+    fn is_same_page(&self, page: &serde_json::Map<String, serde_json::Value>) -> bool {
+        if let Some(pageid) = self.identifier.pageid() {
+            return page
+                .get("pageid")
+                .and_then(|x| x.as_u64())
+                .is_some_and(|x| x.to_string() == pageid);
+        }
+        match (
+            self.identifier.title(),
+            page.get("title").and_then(|x| x.as_str()),
+        ) {
+            (Some(t1), Some(t2)) => normalize_title(t1) == normalize_title(t2),
+            _ => false,
+        }
+    }
+
+    /// Gets the wikitext of the article as of revision `revid`. Returns
+    /// `Error::BadRevisionId` if the revision does not exist or belongs to
+    /// another page.
+    pub fn get_revision_content(&self, revid: u64) -> Result<String> {
+        let revids = format!("{}", revid);
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "revisions"),
+                ("rvprop", "content"),
+                ("rvslots", "main"),
+                ("revids", &*revids),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        let query = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query"))?;
+        if query.contains_key("badrevids") {
+            return Err(Error::BadRevisionId(revid));
+        }
+        let page = self
+            .get_first_page(&q)
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query.pages[]"))?;
+        if !self.is_same_page(page) {
+            return Err(Error::BadRevisionId(revid));
+        }
+        page.get("revisions")
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(revision_content)
+            .ok_or(Error::JSONPathError("query.pages[].revisions[]"))
+    }
+
+    /// Gets the rendered html of the article as of revision `revid`.
+    pub fn get_revision_html(&self, revid: u64) -> Result<String> {
+        let oldid = format!("{}", revid);
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "text"),
+                ("oldid", &*oldid),
+                ("format", "json"),
+                ("action", "parse"),
+            ]
+            .into_iter(),
+        );
+
+        let q = match q {
+            Err(Error::Api { ref code, .. }) if code == "nosuchrevid" => {
+                return Err(Error::BadRevisionId(revid))
+            }
+            r => r?,
+        };
+        let parse = q
+            .as_object()
+            .and_then(|x| x.get("parse"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("parse"))?;
+        if !self.is_same_page(parse) {
+            return Err(Error::BadRevisionId(revid));
+        }
+        Ok(parse
+            .get("text")
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("*"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("parse.text.*"))?
+            .to_owned())
+    }
+
+    /// Returns the html diff introduced by revision `revid` of the
+    /// article, compared to the revision before it.
+    pub fn diff_to_previous(&self, revid: u64) -> Result<String> {
+        let revids = format!("{}", revid);
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "revisions"),
+                ("rvprop", "ids"),
+                ("revids", &*revids),
+                ("format", "json"),
+                ("action", "query"),
+            ]
+            .into_iter(),
+        )?;
+
+        let query = q
+            .as_object()
+            .and_then(|x| x.get("query"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query"))?;
+        if query.contains_key("badrevids") {
+            return Err(Error::BadRevisionId(revid));
+        }
+        let page = self
+            .get_first_page(&q)
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("query.pages[]"))?;
+        if !self.is_same_page(page) {
+            return Err(Error::BadRevisionId(revid));
+        }
+        let parentid = page
+            .get("revisions")
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("parentid"))
+            .and_then(|x| x.as_u64())
+            .ok_or(Error::JSONPathError("query.pages[].revisions[].parentid"))?;
+        if parentid == 0 {
+            // the revision created the page, there is nothing to compare to
+            return Err(Error::InvalidParameter("revid".to_string()));
+        }
+        self.wikipedia.compare_revisions(parentid, revid)
+    }
+
+    /// Gets a summary of the article.
+    pub fn get_summary(&self) -> Result<String> {
+        self.get_intro_extract(None)
+    }
+
+    /// Gets the first `sentences` sentences of the article's summary, up
+    /// to 10.
+    pub fn get_summary_sentences(&self, sentences: u8) -> Result<String> {
+        if sentences == 0 || sentences > 10 {
+            return Err(Error::InvalidParameter("sentences".to_string()));
+        }
+        self.get_intro_extract(Some(("exsentences", format!("{}", sentences))))
+    }
+
+    /// Gets the first `chars` characters of the article's summary, up to
+    /// 1200. The API may return slightly more to avoid cutting words.
+    pub fn get_summary_chars(&self, chars: u32) -> Result<String> {
+        if chars == 0 || chars > 1200 {
+            return Err(Error::InvalidParameter("chars".to_string()));
+        }
+        self.get_intro_extract(Some(("exchars", format!("{}", chars))))
+    }
+
+    /// Gets the first paragraph of the article as plain text, with
+    /// reference markers like `[1]` and parenthesized pronunciations
+    /// removed. Returns an empty string if the intro has no text.
+    pub fn get_first_paragraph(&self) -> Result<String> {
+        let qp = self.identifier.query_param();
+        let q = self.wikipedia.query(
+            vec![
+                ("prop", "extracts"),
+                ("exintro", ""),
+                ("redirects", ""),
+                ("format", "json"),
+                ("action", "query"),
+                (&*qp.0, &*qp.1),
+            ]
+            .into_iter(),
+        )?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_first_paragraph();
+        }
+
+        let extract = self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("extract"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.pages[].extract"))?;
+        Ok(html::first_paragraph(extract).unwrap_or_default())
+    }
+
+    /// Fetches the plain text intro, optionally limited by `exsentences`
+    /// or `exchars`.
+    fn get_intro_extract(&self, limit: Option<(&str, String)>) -> Result<String> {
+        let qp = self.identifier.query_param();
+        let mut params = vec![("prop", "extracts"), ("explaintext", ""), ("exintro", "")];
+        if let Some((k, ref v)) = limit {
+            params.push((k, v));
+        }
+        params.extend(vec![
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ]);
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_intro_extract(limit);
+        }
+
+        Ok(self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("extract"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("query.pages[].extract"))?
+            .to_owned())
+    }
+
+    /// See `parse_continue`.
+    fn parse_cont(&self, q: &serde_json::Value) -> Result<Option<IterElems>> {
+        parse_continue(q)
+    }
+
+    /// Sends `params`, following continuations and calling `f` with the
+    /// page of every batch until it returns `false`. Returns the target
+    /// instead if the page is a redirect.
+    fn query_continued<F>(&self, params: Vec<(&str, &str)>, mut f: F) -> Result<Option<String>>
+    where
+        F: FnMut(&serde_json::Value) -> Result<bool>,
+    {
+        let mut cont: Option<IterElems> = None;
+        loop {
+            let mut batch = params.clone();
+            push_continue(&mut batch, &cont);
+            let q = self.wikipedia.query(batch.into_iter())?;
+
+            if let Some(r) = self.redirect(&q) {
+                return Ok(Some(r));
+            }
+            if !f(self.get_existing_page(&q)?)? {
+                return Ok(None);
+            }
+            cont = self.parse_cont(&q)?;
+            if cont.is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn request_images(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit.unwrap_or(self.wikipedia.images_results).to_string();
+        cont!(
+            self,
+            cont,
+            ("generator", "images"),
+            ("gimlimit", &*limit),
+            ("prop", "imageinfo"),
+            ("iiprop", "url")
+        )
+    }
+
+    /// Creates an iterator to view all images in the `Page`.
+    pub fn get_images(&self) -> Result<Iter<'_, A, iter::Image>> {
+        Iter::new(self)
+    }
+
+    /// Like `get_images`, with the page size and number of items given by
+    /// `options`.
+    pub fn get_images_opts(&self, options: &IterOptions) -> Result<Iter<'_, A, iter::Image>> {
+        Iter::with_options(self, options)
+    }
+
+    /// Like `get_images`, resuming at a token returned by
+    /// `Iter::continue_token`.
+    pub fn get_images_from(&self, token: &str) -> Result<Iter<'_, A, iter::Image>> {
+        Iter::from_token(self, token)
+    }
+
+    /// Like `get_images`, returning errors of later requests as items instead
+    /// of ending the iterator.
+    pub fn try_get_images(&self) -> Result<TryIter<'_, A, iter::Image>> {
+        Ok(Iter::new(self)?.fallible())
+    }
+
+    fn request_images_detailed(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit.unwrap_or(self.wikipedia.images_results).to_string();
+        cont!(
+            self,
+            cont,
+            ("generator", "images"),
+            ("gimlimit", &*limit),
+            ("prop", "imageinfo"),
+            ("iiprop", "url|mime|size")
+        )
+    }
+
+    /// Like `get_images`, also getting the mime type and dimensions of every
+    /// file in the same requests.
+    pub fn get_images_detailed(&self) -> Result<Iter<'_, A, iter::DetailedImage>> {
+        Iter::new(self)
+    }
+
+    fn request_extlinks(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit.unwrap_or(self.wikipedia.extlinks_results).to_string();
+        let a: Result<(Vec<serde_json::Value>, _)> =
+            cont!(self, cont, ("prop", "extlinks"), ("ellimit", &*limit));
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("extlinks"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    /// Creates an iterator to view all references (external links) in the `Page`.
+    pub fn get_references(&self) -> Result<Iter<'_, A, iter::Reference>> {
+        Iter::new(self)
+    }
+
+    /// Gets the entries of the reference lists of the page, parsed from its
+    /// html. Pages without `<references />` have no entries, their external
+    /// links are still listed by `get_references`.
+    pub fn get_citations(&self) -> Result<Vec<Citation>> {
+        let html = self.get_html_content()?;
+        Ok(html::references(&html)
+            .into_iter()
+            .map(|(id, body)| {
+                let (archives, links): (Vec<_>, Vec<_>) = html::external_links(&body)
+                    .into_iter()
+                    .partition(|(href, _)| html::is_archive(href));
+                let (url, title) = links.into_iter().next().unzip();
+                Citation {
+                    id,
+                    text: html::text(&body),
+                    url,
+                    title: title.filter(|x| !x.is_empty()),
+                    archive_url: archives.into_iter().next().map(|(href, _)| href),
+                }
+            })
+            .collect())
+    }
+
+    /// Creates an iterator over the urls of all external links in the
+    /// `Page`. Same as `get_references`.
+    pub fn get_external_links(&self) -> Result<Iter<'_, A, iter::Reference>> {
+        Iter::new(self)
+    }
+
+    fn request_links(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit.unwrap_or(self.wikipedia.links_results).to_string();
+        let namespaces = join_namespaces(&self.wikipedia.links_namespaces);
+        let mut extra = Vec::new();
+        if !namespaces.is_empty() {
+            extra.push(("plnamespace", &*namespaces));
+        }
+        extra.push(("pllimit", &*limit));
+        if self.wikipedia.links_descending {
+            extra.push(("pldir", "descending"));
+        }
+        let a: Result<(Vec<serde_json::Value>, _)> = cont!(self, cont, ("prop", "links"); extra);
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("links"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    /// Creates an iterator to view all internal links in the `Page`.
+    pub fn get_links(&self) -> Result<Iter<'_, A, iter::Link>> {
+        Iter::new(self)
+    }
+
+    /// Like `get_links`, with the page size and number of items given by
+    /// `options`.
+    pub fn get_links_opts(&self, options: &IterOptions) -> Result<Iter<'_, A, iter::Link>> {
+        Iter::with_options(self, options)
+    }
+
+    /// Like `get_links`, resuming at a token returned by
+    /// `Iter::continue_token`.
+    pub fn get_links_from(&self, token: &str) -> Result<Iter<'_, A, iter::Link>> {
+        Iter::from_token(self, token)
+    }
+
+    /// Like `get_links`, returning errors of later requests as items instead
+    /// of ending the iterator.
+    pub fn try_get_links(&self) -> Result<TryIter<'_, A, iter::Link>> {
+        Ok(Iter::new(self)?.fallible())
+    }
+
+    fn request_categories(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit
+            .unwrap_or(self.wikipedia.categories_results)
+            .to_string();
+        let a: Result<(Vec<serde_json::Value>, _)> = if self.wikipedia.hidden_categories {
+            cont!(
+                self,
+                cont,
+                ("prop", "categories"),
+                ("clprop", "hidden|sortkey"),
+                ("cllimit", &*limit)
+            )
+        } else {
+            cont!(
+                self,
+                cont,
+                ("prop", "categories"),
+                ("clprop", "hidden|sortkey"),
+                ("cllimit", &*limit),
+                ("clshow", "!hidden")
+            )
+        };
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("categories"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    /// Creates an iterator to view all categories of the `Page`.
+    pub fn get_categories(&self) -> Result<Iter<'_, A, iter::Category>> {
+        Iter::new(self)
+    }
+
+    /// Like `get_categories`, with the page size and number of items given by
+    /// `options`.
+    pub fn get_categories_opts(
+        &self,
+        options: &IterOptions,
+    ) -> Result<Iter<'_, A, iter::Category>> {
+        Iter::with_options(self, options)
+    }
+
+    /// Like `get_categories`, resuming at a token returned by
+    /// `Iter::continue_token`.
+    pub fn get_categories_from(&self, token: &str) -> Result<Iter<'_, A, iter::Category>> {
+        Iter::from_token(self, token)
+    }
+
+    /// Like `get_categories`, returning errors of later requests as items
+    /// instead of ending the iterator.
+    pub fn try_get_categories(&self) -> Result<TryIter<'_, A, iter::Category>> {
+        Ok(Iter::new(self)?.fallible())
+    }
+
+    fn request_templates(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit
+            .unwrap_or(self.wikipedia.templates_results)
+            .to_string();
+        let namespaces = join_namespaces(&self.wikipedia.templates_namespaces);
+        let a: Result<(Vec<serde_json::Value>, _)> = if namespaces.is_empty() {
+            cont!(self, cont, ("prop", "templates"), ("tllimit", &*limit))
+        } else {
+            cont!(
+                self,
+                cont,
+                ("prop", "templates"),
+                ("tllimit", &*limit),
+                ("tlnamespace", &*namespaces)
+            )
+        };
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("templates"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    fn request_backlinks(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit
+            .unwrap_or(self.wikipedia.backlinks_results)
+            .to_string();
+        let lp = self.identifier.list_param("bl");
+        let namespaces = join_namespaces(&self.wikipedia.backlinks_namespaces);
+        let mut params = vec![(&*lp.0, &*lp.1), ("bllimit", &*limit)];
+        if !namespaces.is_empty() {
+            params.push(("blnamespace", &*namespaces));
+        }
+        params.push(("blredirect", ""));
+        let (links, cont) = self.wikipedia.request_list("backlinks", params, cont)?;
+
+        // with `blredirect` pages linking through a redirect are nested in
+        // the redirect's `redirlinks`
+        let mut flat = Vec::new();
+        for mut link in links {
+            let redirlinks = link
+                .as_object_mut()
+                .and_then(|x| x.remove("redirlinks"))
+                .and_then(|x| match x {
+                    serde_json::Value::Array(a) => Some(a),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            let via = link.as_object().and_then(|x| x.get("title")).cloned();
+            flat.push(link);
+            for mut redirlink in redirlinks {
+                if let (Some(o), Some(via)) = (redirlink.as_object_mut(), via.clone()) {
+                    o.insert("via".to_owned(), via);
+                }
+                flat.push(redirlink);
+            }
+        }
+        Ok((flat, cont))
+    }
+
+    /// Creates an iterator over the pages linking to the `Page`, restricted
+    /// to `Wikipedia::backlinks_namespaces` if set. Pages linking through a
+    /// redirect are included after the redirect itself.
+    pub fn get_backlinks(&self) -> Result<Iter<'_, A, iter::Backlink>> {
+        Iter::new(self)
+    }
+
+    fn request_redirects(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit
+            .unwrap_or(self.wikipedia.redirects_results)
+            .to_string();
+        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
+            self,
+            cont,
+            ("prop", "redirects"),
+            ("rdprop", "pageid|title|fragment"),
+            ("rdlimit", &*limit)
+        );
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("redirects"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    /// Creates an iterator over the redirects pointing to the `Page`.
+    pub fn get_redirects_here(&self) -> Result<Iter<'_, A, iter::Redirect>> {
+        Iter::new(self)
+    }
+
+    /// Creates an iterator to view all templates transcluded on the `Page`,
+    /// restricted to `Wikipedia::templates_namespaces` if set.
+    pub fn get_templates(&self) -> Result<Iter<'_, A, iter::Template>> {
+        Iter::new(self)
+    }
+
+    /// Returns whether the `Page` transcludes the template `name`, given
+    /// with or without the `Template:` prefix, which is understood by every
+    /// wiki. Only that template is requested, regardless of
+    /// `Wikipedia::templates_namespaces`.
+    pub fn has_template(&self, name: &str) -> Result<bool> {
+        let name = normalize_title(name.strip_prefix("Template:").unwrap_or(name));
+        let title = format!("Template:{}", name);
+        let a: Result<(Vec<serde_json::Value>, _)> =
+            cont!(self, &None, ("prop", "templates"), ("tltemplates", &*title));
+        let (pages, _) = a?;
+        Ok(pages
+            .first()
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("templates"))
+            .and_then(|x| x.as_array())
+            .is_some_and(|x| x.iter().any(|x| x.get("ns") == Some(&10.into()))))
+    }
+
+    fn request_langlinks(
+        &self,
+        cont: &Option<IterElems>,
+        limit: Option<Limit>,
+    ) -> Result<(Vec<serde_json::Value>, Option<IterElems>)> {
+        let limit = limit
+            .unwrap_or(self.wikipedia.langlinks_results)
+            .to_string();
+        let a: Result<(Vec<serde_json::Value>, _)> = cont!(
+            self,
+            cont,
+            ("prop", "langlinks"),
+            ("llprop", "url"),
+            ("lllimit", &*limit)
+        );
+        a.map(|(pages, cont)| {
+            let page = match pages.into_iter().next() {
+                Some(p) => p,
+                None => return (Vec::new(), None),
+            };
+            (
+                page.as_object()
+                    .and_then(|x| x.get("langlinks"))
+                    .and_then(|x| x.as_array())
+                    .map(|x| x.to_vec())
+                    .unwrap_or(Vec::new()),
+                cont,
+            )
+        })
+    }
+
+    /// Creates an iterator to view all langlinks of the `Page`.
+    /// This iterates over the page titles and urls in all available
+    /// languages.
+    pub fn get_langlinks(&self) -> Result<Iter<'_, A, iter::LangLink>> {
+        Iter::new(self)
+    }
+
+    /// Returns a thumbnail of the representative image of the `Page`,
+    /// scaled to `width` pixels, or `None` if the page has no such image.
+    pub fn get_thumbnail(&self, width: u32) -> Result<Option<Thumbnail>> {
+        let qp = self.identifier.query_param();
+        let size = format!("{}", width);
+        let params = vec![
+            ("prop", "pageimages"),
+            ("piprop", "thumbnail|name"),
+            ("pithumbsize", &*size),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_thumbnail(width);
+        }
+
+        let page = match self.get_optional_page(&q)? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        let thumbnail = match page.get("thumbnail").and_then(|x| x.as_object()) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        let source = match thumbnail.get("source").and_then(|x| x.as_str()) {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        Ok(Some(Thumbnail {
+            source: source.to_owned(),
+            width: thumbnail.get("width").and_then(|x| x.as_u64()).unwrap_or(0) as u32,
+            height: thumbnail
+                .get("height")
+                .and_then(|x| x.as_u64())
+                .unwrap_or(0) as u32,
+            file: page
+                .get("pageimage")
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned(),
+        }))
+    }
+
+    /// Returns the short description of the `Page` (the text shown under the
+    /// title in the apps), or `None` if it has none.
+    pub fn get_description(&self) -> Result<Option<String>> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "description|pageprops"),
+            ("ppprop", "wikibase-shortdesc"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        // `redirects` makes the API return the target page already
+        let page = match self.get_optional_page(&q)? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        Ok(page
+            .get("description")
+            .or_else(|| {
+                page.get("pageprops")
+                    .and_then(|x| x.as_object())
+                    .and_then(|x| x.get("wikibase-shortdesc"))
+            })
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()))
+    }
+
+    /// Returns the Wikidata item id (e.g. `Q42`) of the `Page`, following
+    /// redirects even when `follow_redirects` is unset, or `None` if it isn't
+    /// linked to an item.
+    pub fn get_wikidata_id(&self) -> Result<Option<String>> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "pageprops"),
+            ("ppprop", "wikibase_item"),
+            ("redirects", "1"),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let q = self.wikipedia.client_query(params.into_iter())?;
+
+        let page = self.get_existing_page(&q)?;
+        Ok(page
+            .as_object()
+            .and_then(|x| x.get("pageprops"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("wikibase_item"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()))
+    }
+
+    /// Returns whether the `Page` is a disambiguation page.
+    pub fn is_disambiguation(&self) -> Result<bool> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "pageprops"),
+            ("ppprop", "disambiguation"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        let page = self.get_existing_page(&q)?;
+        Ok(page
+            .as_object()
+            .and_then(|x| x.get("pageprops"))
+            .and_then(|x| x.as_object())
+            .is_some_and(|x| x.contains_key("disambiguation")))
+    }
+
+    /// Returns the titles of the articles a disambiguation page links to,
+    /// or an empty `Vec` if the `Page` is not a disambiguation page.
+    pub fn get_disambiguation_links(&self) -> Result<Vec<String>> {
+        if !self.is_disambiguation()? {
+            return Ok(Vec::new());
+        }
+        Ok(self.get_links()?.map(|x| x.title).collect())
+    }
+
+    /// Like `get_summary`, but fails with `Error::Disambiguation` listing the
+    /// candidate titles if the `Page` is a disambiguation page.
+    pub fn get_article_summary(&self) -> Result<String> {
+        let links = self.get_disambiguation_links()?;
+        if !links.is_empty() {
+            return Err(Error::Disambiguation(links));
+        }
+        self.get_summary()
+    }
+
+    /// Returns the latitude and longitude associated to the `Page` if any.
+    pub fn get_coordinates(&self) -> Result<Option<(f64, f64)>> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "coordinates"),
+            ("colimit", "max"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        if let Some(r) = self.redirect(&q) {
+            return Page::from_title(self.wikipedia, r).get_coordinates();
+        }
+
+        let coord = match self
+            .get_existing_page(&q)?
+            .as_object()
+            .and_then(|x| x.get("coordinates"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(|x| x.as_object())
+        {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        Ok(Some((
+            coord
+                .get("lat")
+                .and_then(|x| x.as_f64())
+                .ok_or(Error::JSONPathError("query.pages[].coordinates[].lat"))?,
+            coord
+                .get("lon")
+                .and_then(|x| x.as_f64())
+                .ok_or(Error::JSONPathError("query.pages[].coordinates[].lon"))?,
+        )))
+    }
+
+    /// Gets all locations marked on the page, primary and secondary, in
+    /// the order the API returns them.
+    pub fn get_all_coordinates(&self) -> Result<Vec<Coordinate>> {
+        let qp = self.identifier.query_param();
+        let params = vec![
+            ("prop", "coordinates"),
+            ("coprop", "type|name|dim|globe"),
+            ("coprimary", "all"),
+            ("colimit", "max"),
+            ("redirects", ""),
+            ("format", "json"),
+            ("action", "query"),
+            (&*qp.0, &*qp.1),
+        ];
+        let mut coordinates = Vec::new();
+        let redirect = self.query_continued(params, |page| {
+            if let Some(c) = page
+                .as_object()
+                .and_then(|x| x.get("coordinates"))
+                .and_then(|x| x.as_array())
+            {
+                coordinates.extend(c.iter().filter_map(parse_coordinate));
+            }
+            Ok(true)
+        })?;
+        if let Some(r) = redirect {
+            return Page::from_title(self.wikipedia, r).get_all_coordinates();
+        }
+        Ok(coordinates)
+    }
+
+    /// Fetches all sections of the article.
+    pub fn get_sections(&self) -> Result<Vec<String>> {
+        let pageid = self.get_pageid()?;
+        let params = vec![
+            ("prop", "sections"),
+            ("format", "json"),
+            ("action", "parse"),
+            ("pageid", &*pageid),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        Ok(q.as_object()
+            .and_then(|x| x.get("parse"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("sections"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("parse.sections"))?
+            .iter()
+            .filter_map(|x| {
+                x.as_object()
+                    .and_then(|x| x.get("line"))
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_owned())
+            })
+            .collect())
+    }
+
+    /// Fetches the sections of the article nested as in its table of
+    /// contents.
+    pub fn get_section_tree(&self) -> Result<Vec<Section>> {
+        let pageid = self.get_pageid()?;
+        let params = vec![
+            ("prop", "sections"),
+            ("format", "json"),
+            ("action", "parse"),
+            ("pageid", &*pageid),
+        ];
+        let q = self.wikipedia.query(params.into_iter())?;
+
+        let flat = q
+            .as_object()
+            .and_then(|x| x.get("parse"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("sections"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("parse.sections"))?
+            .iter()
+            .filter_map(parse_section);
+        Ok(build_section_tree(flat))
+    }
+
+    /// Fetches the rendered html of a single section, by the index given
+    /// in `Section::index` (template sections like `T-2` included).
+    pub fn get_section_html(&self, index: &str) -> Result<String> {
+        let pageid = self.get_pageid()?;
+        let params = vec![
+            ("prop", "text"),
+            ("section", index),
+            ("format", "json"),
+            ("action", "parse"),
+            ("pageid", &*pageid),
+        ];
+        let q = match self.wikipedia.query(params.into_iter()) {
+            Err(Error::Api { ref code, .. }) if code == "nosuchsection" => {
+                return Err(Error::SectionNotFound(index.to_owned()))
+            }
+            r => r?,
+        };
+        Ok(q.as_object()
+            .and_then(|x| x.get("parse"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("text"))
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("*"))
+            .and_then(|x| x.as_str())
+            .ok_or(Error::JSONPathError("parse.text.*"))?
+            .to_owned())
+    }
+
+    /// Replaces the text of the page, or of a section if
+    /// `options.section` is set, by `text`. Needs a session with the right
+    /// to edit, see `Wikipedia::login`. A csrf token is fetched for the
+    /// edit, and fetched again once if the wiki refuses it.
+    pub fn edit(&self, text: &str, summary: &str, options: &EditOptions) -> Result<EditResult> {
+        if options.create_only && options.no_create {
+            return Err(Error::InvalidParameter("createonly".to_owned()));
+        }
+        let id = self.identifier.list_param("");
+        let mut params = vec![(&*id.0, &*id.1), ("text", text), ("summary", summary)];
+        for (name, set) in [
+            ("bot", options.bot),
+            ("minor", options.minor),
+            ("createonly", options.create_only),
+            ("nocreate", options.no_create),
+        ] {
+            if set {
+                params.push((name, "1"));
+            }
+        }
+        if let Some(ref section) = options.section {
+            params.push(("section", section));
+        }
+        if let Some(ref section_title) = options.section_title {
+            params.push(("sectiontitle", section_title));
+        }
+        if let Some(ref base_timestamp) = options.base_timestamp {
+            params.push(("basetimestamp", base_timestamp));
+        }
+        if options.require_login {
+            params.push(("assert", "user"));
+        }
+        params.extend([("format", "json"), ("action", "edit")]);
+
+        let mut token = self.wikipedia.get_csrf_token()?;
+        let mut refreshed = false;
+        let q = loop {
+            let mut args = params.clone();
+            // last, so a truncated request lacks the token
+            args.push(("token", &token));
+            match self.wikipedia.post_query(args.into_iter()) {
+                Err(Error::Api { ref code, .. }) if code == "badtoken" && !refreshed => {
+                    token = self.wikipedia.get_csrf_token()?;
+                    refreshed = true;
+                }
+                Err(Error::Api { ref code, .. }) if code == "badtoken" => {
+                    return Err(Error::BadToken)
+                }
+                Err(Error::Api { ref code, .. }) if code == "editconflict" => {
+                    return Err(Error::EditConflict)
+                }
+                r => break r?,
+            }
+        };
+
+        let edit = q
+            .as_object()
+            .and_then(|x| x.get("edit"))
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("edit"))?;
+        match edit.get("result").and_then(|x| x.as_str()) {
+            Some("Success") => {}
+            result => {
+                return Err(Error::Api {
+                    code: result.unwrap_or("").to_lowercase(),
+                    info: "The edit was not saved".to_owned(),
+                })
+            }
+        }
+        Ok(EditResult {
+            pageid: edit
+                .get("pageid")
+                .and_then(|x| x.as_u64())
+                .ok_or(Error::JSONPathError("edit.pageid"))?,
+            title: edit
+                .get("title")
+                .and_then(|x| x.as_str())
+                .ok_or(Error::JSONPathError("edit.title"))?
+                .to_owned(),
+            old_revid: edit.get("oldrevid").and_then(|x| x.as_u64()),
+            new_revid: edit.get("newrevid").and_then(|x| x.as_u64()),
+            new_timestamp: edit
+                .get("newtimestamp")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_owned()),
+            no_change: edit.contains_key("nochange"),
+        })
+    }
+
+    /// Purges the server side cache of the page, so it's rendered again,
+    /// e.g. after editing a template it uses. With `forcelinkupdate`, the
+    /// links and categories are updated too. Fails with
+    /// `Error::RateLimited` if the user made too many purges.
+    pub fn purge(&self, forcelinkupdate: bool) -> Result<()> {
+        let qp = self.identifier.query_param();
+        let (purged, _) = self.wikipedia.purge((&*qp.0, &*qp.1), forcelinkupdate)?;
+        let entry = purged.first().ok_or(Error::JSONPathError("purge[]"))?;
+        self.check_missing(entry)?;
+        if entry.get("purged").is_none() {
+            return Err(Error::RateLimited { retry_after: None });
+        }
+        Ok(())
+    }
+
+    /// Adds the page to the watchlist of the logged in user.
+    pub fn watch(&self) -> Result<()> {
+        self.set_watched(true)
+    }
+
+    /// Removes the page from the watchlist of the logged in user.
+    pub fn unwatch(&self) -> Result<()> {
+        self.set_watched(false)
+    }
+
+    fn set_watched(&self, watched: bool) -> Result<()> {
+        let token = self.wikipedia.token("watch")?;
+        let qp = self.identifier.query_param();
+        let mut params = vec![(&*qp.0, &*qp.1)];
+        if !watched {
+            params.push(("unwatch", "1"));
+        }
+        params.extend([("format", "json"), ("action", "watch"), ("token", &*token)]);
+        let q = self.wikipedia.post_query(params.into_iter())?;
+        let entry = q
+            .as_object()
+            .and_then(|x| x.get("watch"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.first())
+            .and_then(|x| x.as_object())
+            .ok_or(Error::JSONPathError("watch[]"))?;
+        if entry.contains_key("invalid") {
+            return Err(Error::PageNotFound {
+                title: self.identifier.title().unwrap_or_default().to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds a section with `heading` and `text` at the end of the page,
+    /// which can't conflict with other edits. Fails with
+    /// `Error::NotLoggedIn` rather than adding it anonymously.
+    pub fn add_section(&self, heading: &str, text: &str, summary: &str) -> Result<EditResult> {
+        self.edit(
+            text,
+            summary,
+            &EditOptions {
+                section: Some("new".to_owned()),
+                section_title: Some(heading.to_owned()),
+                require_login: true,
+                ..EditOptions::default()
+            },
+        )
+    }
+
+    /// Fetches the content of a section.
+    pub fn get_section_content(&self, title: &str) -> Result<Option<String>> {
+        let headr = format!("== {} ==", title);
+        let content = self.get_content()?;
+        let index = match content.find(&*headr) {
+            Some(i) => headr.len() + i,
+            None => return Ok(None),
+        };
+        let end = match content[index..].find("==") {
+            Some(i) => index + i,
+            None => content.len(),
+        };
+        Ok(Some(content[index..end].to_owned()))
+    }
+}
+
+impl<'a, A: http::HttpClient> PartialEq<Page<'a, A>> for Page<'a, A> {
+    fn eq(&self, other: &Page<A>) -> bool {
+        match (self.identifier.pageid(), other.identifier.pageid()) {
+            (Some(p1), Some(p2)) => p1 == p2,
+            _ => match (self.identifier.title(), other.identifier.title()) {
+                (Some(t1), Some(t2)) => t1 == t2,
+                _ => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::http::HttpClient;
+    use super::iter;
+    use super::IterOptions;
+    use super::Wikipedia;
+    use std::sync::{Arc, Mutex};
+
+    const DEFAULT_AGENT: &str = "wikipedia (https://github.com/seppo0010/wikipedia-rs)";
+
+    struct MockClient {
+        pub url: Mutex<Vec<String>>,
+        pub user_agent: Option<String>,
+        pub bearer_token: Option<String>,
+        pub arguments: Mutex<Vec<iter::IterElems>>,
+        pub response: Mutex<Vec<String>>,
+        pub method: Mutex<Vec<&'static str>>,
+        /// Errors returned before any of the responses.
+        pub failures: Mutex<Vec<super::Error>>,
+        /// Headers passed to `get_with_headers` and `post_with_headers`, one
+        /// list per request.
+        pub headers: Mutex<Vec<iter::IterElems>>,
+        /// Name, filename and content of the files of `post_multipart`.
+        pub files: Mutex<Vec<(String, String, Vec<u8>)>>,
+        /// Headers of the responses of `get_response` and `post_response`,
+        /// in order. Responses without an entry have no headers.
+        pub response_headers: Mutex<Vec<Vec<(String, String)>>>,
+    }
+
+    impl Default for MockClient {
+        fn default() -> Self {
+            MockClient {
+                url: Mutex::new(Vec::new()),
+                user_agent: Some(DEFAULT_AGENT.into()),
+                bearer_token: None,
+                arguments: Mutex::new(Vec::new()),
+                response: Mutex::new(Vec::new()),
+                method: Mutex::new(Vec::new()),
+                failures: Mutex::new(Vec::new()),
+                headers: Mutex::new(Vec::new()),
+                files: Mutex::new(Vec::new()),
+                response_headers: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl MockClient {
+        /// Pops the next response, failing like a dropped connection once
+        /// there are none left.
+        fn next_response(&self) -> Result<String, super::http::Error> {
+            let mut failures = self.failures.lock().unwrap();
+            if !failures.is_empty() {
+                return Err(failures.remove(0));
+            }
+            let mut response = self.response.lock().unwrap();
+            if response.is_empty() {
+                return Err(super::Error::IOError(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "no more responses",
+                )));
+            }
+            Ok(response.remove(0))
+        }
+
+        /// Wraps `body` with the next headers of `response_headers`.
+        fn with_headers(&self, body: String) -> super::http::Response {
+            let mut headers = self.response_headers.lock().unwrap();
+            super::http::Response {
+                status: 200,
+                headers: if headers.is_empty() {
+                    Vec::new()
+                } else {
+                    headers.remove(0)
+                },
+                body,
+            }
+        }
+    }
+
+    impl super::http::HttpClient for MockClient {
+        fn user_agent(&mut self, user_agent: String) {
+            self.user_agent = Some(user_agent)
+        }
+
+        fn bearer_token(&mut self, bearer_token: String) {
+            self.bearer_token = Some(bearer_token)
+        }
+
+        fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.method.lock().unwrap().push("GET");
+            self.url.lock().unwrap().push(base_url.to_owned());
+            self.arguments
+                .lock()
+                .unwrap()
+                .push(args.map(|x| (x.0.to_owned(), x.1.to_owned())).collect());
+            self.next_response()
+        }
+
+        fn post<'a, I>(&self, base_url: &str, args: I) -> Result<String, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.method.lock().unwrap().push("POST");
+            self.url.lock().unwrap().push(base_url.to_owned());
+            self.arguments
+                .lock()
+                .unwrap()
+                .push(args.map(|x| (x.0.to_owned(), x.1.to_owned())).collect());
+            self.next_response()
+        }
+
+        fn post_multipart<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            file: &super::http::FilePart,
+        ) -> Result<String, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.files.lock().unwrap().push((
+                file.name.to_owned(),
+                file.filename.to_owned(),
+                file.content.to_vec(),
+            ));
+            self.post(base_url, args)
+        }
+
+        fn get_with_headers<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<String, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.headers.lock().unwrap().push(
+                headers
+                    .iter()
+                    .map(|x| (x.0.to_owned(), x.1.to_owned()))
+                    .collect(),
+            );
+            self.get(base_url, args)
+        }
+
+        fn post_with_headers<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<String, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            self.headers.lock().unwrap().push(
+                headers
+                    .iter()
+                    .map(|x| (x.0.to_owned(), x.1.to_owned()))
+                    .collect(),
+            );
+            self.post(base_url, args)
+        }
+
+        fn get_response<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<super::http::Response, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let body = self.get_with_headers(base_url, args, headers)?;
+            Ok(self.with_headers(body))
+        }
+
+        fn post_response<'a, I>(
+            &self,
+            base_url: &str,
+            args: I,
+            headers: &[(&str, &str)],
+        ) -> Result<super::http::Response, super::http::Error>
+        where
+            I: Iterator<Item = (&'a str, &'a str)>,
+        {
+            let body = self.post_with_headers(base_url, args, headers)?;
+            Ok(self.with_headers(body))
+        }
+    }
+
+    #[test]
+    fn base_url() {
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        assert_eq!(wikipedia.base_url(), "https://en.wikipedia.org/w/api.php");
+        wikipedia.language = "es".to_owned();
+        assert_eq!(wikipedia.base_url(), "https://es.wikipedia.org/w/api.php");
+
+        wikipedia.set_base_url("https://hello.{language}.world/");
+        assert_eq!(wikipedia.base_url(), "https://hello.es.world/");
+
+        wikipedia.set_base_url("https://hello.world/");
+        assert_eq!(wikipedia.base_url(), "https://hello.world/");
+    }
+
+    #[test]
+    fn builder() {
+        use super::{Error, Limit, WikipediaBuilder};
+        let wikipedia = Wikipedia::builder(MockClient::default())
+            .language("de")
+            .search_results(Limit::Count(20))
+            .user_agent("hello world")
+            .maxlag(5)
+            .build()
+            .unwrap();
+        assert_eq!(wikipedia.base_url(), "https://de.wikipedia.org/w/api.php");
+        assert_eq!(wikipedia.search_results, Limit::Count(20));
+        assert_eq!(wikipedia.images_results, Limit::Max);
+        assert_eq!(wikipedia.maxlag, Some(5));
+        assert_eq!(&*wikipedia.client.user_agent.unwrap(), "hello world");
+
+        let wikipedia = WikipediaBuilder::<MockClient>::default()
+            .base_url("https://hello.{language}.world/")
+            .language("es")
+            .build()
+            .unwrap();
+        assert_eq!(wikipedia.base_url(), "https://hello.es.world/");
+        let wikipedia = WikipediaBuilder::<MockClient>::default()
+            .base_url("https://hello.world/")
+            .build()
+            .unwrap();
+        assert_eq!(wikipedia.base_url(), "https://hello.world/");
+
+        for builder in [
+            WikipediaBuilder::<MockClient>::default()
+                .base_url("https://hello.world/")
+                .language("es"),
+            WikipediaBuilder::<MockClient>::default().language(""),
+            WikipediaBuilder::<MockClient>::default().images_results(Limit::Count(0)),
+            WikipediaBuilder::<MockClient>::default().links_results(Limit::Count(501)),
+        ] {
+            match builder.build() {
+                Err(Error::InvalidParameter(_)) => {}
+                x => panic!("{:?}", x.map(|_| ())),
+            }
+        }
+        match WikipediaBuilder::<MockClient>::default()
+            .base_url("https://hello.world/")
+            .language("es")
+            .build()
+        {
+            Err(Error::InvalidParameter(x)) => assert_eq!(x, "language: es"),
+            x => panic!("{:?}", x.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn set_language() {
+        use super::Error;
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.set_language("de").unwrap();
+        assert_eq!(wikipedia.base_url(), "https://de.wikipedia.org/w/api.php");
+        for language in ["zh-min-nan", "simple", "be-x-old"] {
+            wikipedia.set_language(language).unwrap();
+        }
+        for language in ["klingon", "", "EN", "en.wikipedia.org"] {
+            match wikipedia.set_language(language) {
+                Err(Error::InvalidParameter(x)) => assert_eq!(x, format!("language: {}", language)),
+                x => panic!("{:?}", x),
+            }
+        }
+        assert_eq!(wikipedia.language, "be-x-old");
+
+        wikipedia.set_base_url("https://hello.world/");
+        match wikipedia.set_language("de") {
+            Err(Error::InvalidParameter(x)) => assert_eq!(x, "language: de"),
+            x => panic!("{:?}", x),
+        }
+        assert_eq!(wikipedia.base_url(), "https://hello.world/");
+        wikipedia.set_base_url("https://{language}.hello.world/");
+        wikipedia.set_language("de").unwrap();
+        assert_eq!(wikipedia.base_url(), "https://de.hello.world/");
+    }
+
+    #[test]
+    #[cfg(feature = "http-client")]
+    fn with_language() {
+        use super::{http, Limit};
+        let wikipedia = Wikipedia::<http::default::Client> {
+            search_results: Limit::Count(3),
+            ..Default::default()
+        };
+        let french = wikipedia.with_language("fr").unwrap();
+        assert_eq!(french.base_url(), "https://fr.wikipedia.org/w/api.php");
+        assert_eq!(french.search_results, Limit::Count(3));
+        assert_eq!(wikipedia.language, "en");
+        assert!(wikipedia.with_language("klingon").is_err());
+    }
+
+    #[test]
+    fn lang() {
+        use super::Limit;
+        let wikipedia = Wikipedia::<MockClient> {
+            search_results: Limit::Count(3),
+            ..Default::default()
+        };
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"search\":[{\"title\":\"Berlin\"}]}}".to_owned(),
+            "{\"query\":{\"pages\":{\"1\":{\"extract\":\"Berlin ist die Hauptstadt\"}}}}"
+                .to_owned(),
+        ]);
+        let german = wikipedia.lang("de").unwrap();
+        assert_eq!(german.search("Berlin").unwrap(), vec!["Berlin".to_owned()]);
+        assert_eq!(
+            german
+                .page_from_title("Berlin".to_owned())
+                .get_summary()
+                .unwrap(),
+            "Berlin ist die Hauptstadt"
+        );
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://de.wikipedia.org/w/api.php"; 2]
+        );
+        assert!(wikipedia.client.arguments.lock().unwrap()[0]
+            .contains(&("srlimit".to_owned(), "3".to_owned())));
+        assert_eq!(wikipedia.language, "en");
+        assert!(wikipedia.lang("klingon").is_err());
+
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.set_base_url("https://my.wiki/w/api.php");
+        match wikipedia.lang("de") {
+            Err(super::Error::InvalidParameter(x)) => assert_eq!(x, "language: de"),
+            x => panic!("{:?}", x.map(|_| ())),
+        }
+        assert!(wikipedia.client.url.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn user_agent() {
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{}".to_owned());
+        wikipedia.search("hello world").unwrap_err();
+        assert_eq!(&*wikipedia.client.user_agent.unwrap(), DEFAULT_AGENT);
+
+        let mut client = MockClient::default();
+        client.user_agent("hello world".to_owned());
+        client.response.lock().unwrap().push("{}".to_owned());
+        wikipedia.client = client;
+        wikipedia.search("hello world").unwrap_err();
+        assert_eq!(&*wikipedia.client.user_agent.unwrap(), "hello world");
+    }
+
+    #[test]
+    fn search() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"search\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.search("hello world").unwrap(),
+            vec!["hello".to_owned(), "world".to_owned(),]
+        );
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("srsearch".to_owned(), "hello world".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn search_detailed() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"search\":[{\"ns\":0,\"title\":\"hello\",\"pageid\":1,\"size\":120,\"wordcount\":20,\"snippet\":\"<span class=\\\"searchmatch\\\">hello</span> world\",\"timestamp\":\"2024-01-01T00:00:00Z\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.search_detailed("hello").unwrap(),
+            vec![super::SearchResult {
+                title: "hello".to_owned(),
+                snippet: "<span class=\"searchmatch\">hello</span> world".to_owned(),
+                word_count: 20,
+                size_bytes: 120,
+                timestamp: "2024-01-01T00:00:00Z".to_owned(),
+            }]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "search".to_owned()),
+                (
+                    "srprop".to_owned(),
+                    "snippet|wordcount|size|timestamp".to_owned()
+                ),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("srsearch".to_owned(), "hello".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn search_offset() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"sroffset\":20,\"continue\":\"-||\"},\"query\":{\"search\":[{\"title\":\"hello\"}]}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[{\"title\":\"world\"}]}}".to_owned());
+        assert_eq!(
+            wikipedia.search_offset("hello world", 10).unwrap(),
+            super::SearchPage {
+                results: vec!["hello".to_owned()],
+                next_offset: Some(20),
+            }
+        );
+        assert_eq!(
+            wikipedia.search_offset("hello world", 20).unwrap(),
+            super::SearchPage {
+                results: vec!["world".to_owned()],
+                next_offset: None,
+            }
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("sroffset".to_owned(), "10".to_owned()),
+                ("srsearch".to_owned(), "hello world".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn search_with_suggestion() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"searchinfo\":{\"suggestion\":\"keyboard\"},\"search\":[{\"title\":\"hello\"}]}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"searchinfo\":{},\"search\":[]}}".to_owned());
+        assert_eq!(
+            wikipedia.search_with_suggestion("keybaord").unwrap(),
+            (vec!["hello".to_owned()], Some("keyboard".to_owned()))
+        );
+        assert_eq!(
+            wikipedia.search_with_suggestion("keyboard").unwrap(),
+            (vec![], None)
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srinfo".to_owned(), "suggestion".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("srsearch".to_owned(), "keybaord".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn search_in_namespaces() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[{\"title\":\"Category:Hello\"}]}}".to_owned());
+        assert_eq!(
+            wikipedia.search_in_namespaces("hello", &[14, 10]).unwrap(),
+            vec!["Category:Hello".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("srnamespace".to_owned(), "14|10".to_owned()),
+                ("srsearch".to_owned(), "hello".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+        match wikipedia.search_in_namespaces("hello", &[]) {
+            Err(super::Error::InvalidParameter(p)) => assert_eq!(p, "namespaces"),
+            _ => panic!("expected an invalid parameter error"),
+        }
+    }
+
+    #[test]
+    fn prefix_search() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"psoffset\":2,\"continue\":\"-||\"},\"query\":{\"prefixsearch\":[{\"ns\":0,\"title\":\"Hello\",\"pageid\":1},{\"ns\":0,\"title\":\"Hell\",\"pageid\":2}]}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"prefixsearch\":[{\"ns\":0,\"title\":\"Helium\",\"pageid\":3}]}}"
+                .to_owned(),
+        );
+        assert_eq!(
+            wikipedia.prefix_search("He").unwrap(),
+            vec!["Hello".to_owned(), "Hell".to_owned()]
+        );
+        assert_eq!(
+            wikipedia.prefix_search_offset("He", 2).unwrap(),
+            super::SearchPage {
+                results: vec!["Helium".to_owned()],
+                next_offset: None,
+            }
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("list".to_owned(), "prefixsearch".to_owned()),
+                    ("pslimit".to_owned(), "10".to_owned()),
+                    ("psoffset".to_owned(), "0".to_owned()),
+                    ("pssearch".to_owned(), "He".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned())
+                ],
+                vec![
+                    ("list".to_owned(), "prefixsearch".to_owned()),
+                    ("pslimit".to_owned(), "10".to_owned()),
+                    ("psoffset".to_owned(), "2".to_owned()),
+                    ("pssearch".to_owned(), "He".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned())
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn open_search() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "[\"Computer key\",[\"Computer keyboard\",\"Computer key (disambiguation)\"],[\"\",\"\"],[\"https://en.wikipedia.org/wiki/Computer_keyboard\",\"https://en.wikipedia.org/wiki/Computer_key_(disambiguation)\"]]".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.open_search("Computer key").unwrap(),
+            vec![
+                super::OpenSearchResult {
+                    title: "Computer keyboard".to_owned(),
+                    description: "".to_owned(),
+                    url: "https://en.wikipedia.org/wiki/Computer_keyboard".to_owned(),
+                },
+                super::OpenSearchResult {
+                    title: "Computer key (disambiguation)".to_owned(),
+                    description: "".to_owned(),
+                    url: "https://en.wikipedia.org/wiki/Computer_key_(disambiguation)".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("search".to_owned(), "Computer key".to_owned()),
+                ("limit".to_owned(), "10".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "opensearch".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn open_search_malformed() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("[\"hello\",[\"world\"]]".to_owned());
+        assert!(matches!(
+            wikipedia.open_search("hello"),
+            Err(super::Error::JSONPathError("[2]"))
+        ));
+    }
+
+    #[test]
+    fn quote_search_value() {
+        assert_eq!(super::quote_search_value("Hello"), "\"Hello\"");
+        assert_eq!(
+            super::quote_search_value("Star Wars: \"A New Hope\""),
+            "\"Star Wars: \\\"A New Hope\\\"\""
+        );
+        assert_eq!(super::quote_search_value("a\\b"), "\"a\\\\b\"");
+    }
+
+    #[test]
+    fn search_related() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"search\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.search_related("Star Wars: Episode IV").unwrap(),
+            vec!["hello".to_owned(), "world".to_owned()]
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][3],
+            (
+                "srsearch".to_owned(),
+                "morelike:\"Star Wars: Episode IV\"".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn search_mode() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[{\"title\":\"Computer keyboard\"}]}}".to_owned());
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[]}}".to_owned());
+        assert_eq!(
+            wikipedia
+                .search_mode("computer keyboard", super::SearchMode::NearMatch)
+                .unwrap(),
+            vec!["Computer keyboard".to_owned()]
+        );
+        assert!(wikipedia
+            .search_mode("computer keybaord", super::SearchMode::NearMatch)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("srwhat".to_owned(), "nearmatch".to_owned()),
+                ("srsearch".to_owned(), "computer keyboard".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn search_opts() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"sroffset\":15,\"continue\":\"-||\"},\"query\":{\"search\":[{\"title\":\"hello\"}]}}".to_owned(),
+        );
+        let options = super::SearchOptions {
+            namespaces: vec![0, 14],
+            mode: Some(super::SearchMode::Text),
+            sort: Some(super::SearchSort::LastEditDesc),
+            offset: Some(5),
+        };
+        assert_eq!(
+            wikipedia.search_opts("hello", &options).unwrap(),
+            super::SearchPage {
+                results: vec!["hello".to_owned()],
+                next_offset: Some(15),
+            }
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srlimit".to_owned(), "10".to_owned()),
+                ("sroffset".to_owned(), "5".to_owned()),
+                ("srnamespace".to_owned(), "0|14".to_owned()),
+                ("srwhat".to_owned(), "text".to_owned()),
+                ("srsort".to_owned(), "last_edit_desc".to_owned()),
+                ("srsearch".to_owned(), "hello".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn search_opts_invalid_sort() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        let options = super::SearchOptions {
+            mode: Some(super::SearchMode::NearMatch),
+            sort: Some(super::SearchSort::CreateTimestampAsc),
+            ..super::SearchOptions::default()
+        };
+        match wikipedia.search_opts("hello", &options) {
+            Err(super::Error::InvalidParameter(p)) => assert_eq!(p, "sort"),
+            _ => panic!("expected an invalid parameter error"),
+        }
+        assert!(wikipedia.client.arguments.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_count() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"searchinfo\":{\"totalhits\":12345},\"search\":[{\"title\":\"hello\"}]}}"
+                .to_owned(),
+        );
+        assert_eq!(wikipedia.search_count("hello").unwrap(), 12345);
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "search".to_owned()),
+                ("srprop".to_owned(), "".to_owned()),
+                ("srinfo".to_owned(), "totalhits".to_owned()),
+                ("srlimit".to_owned(), "1".to_owned()),
+                ("srsearch".to_owned(), "hello".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn search_count_missing() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[]}}".to_owned());
+        assert!(matches!(
+            wikipedia.search_count("hello"),
+            Err(super::Error::JSONPathError("query.searchinfo.totalhits"))
+        ));
+    }
+
+    #[test]
+    fn http_error_source() {
+        let err = super::Error::HTTPError(Box::from("connection refused"));
+        assert_eq!(err.to_string(), "HTTP Error");
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(source.to_string(), "connection refused");
+    }
+
+    #[test]
+    fn json_path_error() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{}}".to_owned());
+        let err = wikipedia.search("hello").unwrap_err();
+        assert!(matches!(err, super::Error::JSONPathError("query.search")));
+        assert_eq!(err.to_string(), "JSON Path Error: query.search");
+
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"1\":{\"pageid\":1}}}}".to_owned());
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        assert!(matches!(
+            page.get_title(),
+            Err(super::Error::JSONPathError("query.pages[].title"))
+        ));
+    }
+
+    #[test]
+    fn search_in_category() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for _ in 0..3 {
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"query\":{\"search\":[{\"title\":\"hello\"}]}}".to_owned());
+        }
+        assert_eq!(
+            wikipedia
+                .search_in_category("river", "Rivers of Argentina")
+                .unwrap(),
+            vec!["hello".to_owned()]
+        );
+        wikipedia
+            .search_in_category("album", "Category:\"Weird Al\" Yankovic (albums)")
+            .unwrap();
+        wikipedia
+            .search_in_category("río", "Ríos de España")
+            .unwrap();
+        let searches = wikipedia
+            .client
+            .arguments
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x[3].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            searches,
+            vec![
+                (
+                    "srsearch".to_owned(),
+                    "river incategory:\"Rivers of Argentina\"".to_owned()
+                ),
+                (
+                    "srsearch".to_owned(),
+                    "album incategory:\"\\\"Weird Al\\\" Yankovic (albums)\"".to_owned()
+                ),
+                (
+                    "srsearch".to_owned(),
+                    "río incategory:\"Ríos de España\"".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn geosearch() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"geosearch\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.geosearch(-34.603333, -58.381667, 10).unwrap(),
+            vec!["hello".to_owned(), "world".to_owned(),]
+        );
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "geosearch".to_owned()),
+                ("gsradius".to_owned(), "10".to_owned()),
+                ("gscoord".to_owned(), "-34.603333|-58.381667".to_owned()),
+                ("gslimit".to_owned(), "10".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn geosearch_detailed() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"geosearch\":[{\"pageid\":1,\"ns\":0,\"title\":\"hello\",\"lat\":-34.6,\"lon\":-58.38,\"dist\":12,\"primary\":\"\",\"type\":\"landmark\",\"dim\":1000},{\"pageid\":2,\"ns\":0,\"title\":\"world\",\"lat\":-34,\"lon\":-58,\"dist\":45.5}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia
+                .geosearch_detailed(-34.603333, -58.381667, 10)
+                .unwrap(),
+            vec![
+                super::GeoSearchResult {
+                    title: "hello".to_owned(),
+                    pageid: 1,
+                    lat: -34.6,
+                    lon: -58.38,
+                    dist: 12.0,
+                    primary: true,
+                    kind: Some("landmark".to_owned()),
+                    dim: Some(1000),
+                },
+                super::GeoSearchResult {
+                    title: "world".to_owned(),
+                    pageid: 2,
+                    lat: -34.0,
+                    lon: -58.0,
+                    dist: 45.5,
+                    primary: false,
+                    kind: None,
+                    dim: None,
+                },
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "geosearch".to_owned()),
+                ("gsradius".to_owned(), "10".to_owned()),
+                ("gscoord".to_owned(), "-34.603333|-58.381667".to_owned()),
+                ("gslimit".to_owned(), "10".to_owned()),
+                ("gsprop".to_owned(), "type|dim".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+        match wikipedia.geosearch_detailed(91.0, 0.0, 10) {
+            Err(super::Error::InvalidParameter(p)) => assert_eq!(p, "latitude"),
+            _ => panic!("expected an invalid parameter error"),
+        }
+    }
+
+    #[test]
+    fn geosearch_bbox() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"geosearch\":[{\"pageid\":1,\"ns\":0,\"title\":\"hello\",\"lat\":37.8,\"lon\":-122.4,\"dist\":0,\"primary\":\"\"}]}}".to_owned(),
+        );
+        let results = wikipedia
+            .geosearch_bbox(37.8, -122.5, 37.7, -122.4)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "hello");
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "geosearch".to_owned()),
+                ("gsbbox".to_owned(), "37.8|-122.5|37.7|-122.4".to_owned()),
+                ("gslimit".to_owned(), "10".to_owned()),
+                ("gsprop".to_owned(), "type|dim".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn geosearch_bbox_invalid() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for (bbox, param) in [
+            ((37.7, -122.5, 37.8, -122.4), "bbox"),
+            ((37.7, -122.5, 37.7, -122.4), "bbox"),
+            ((37.8, -122.5, 37.7, -122.5), "bbox"),
+            ((95.0, -122.5, 37.7, -122.4), "top"),
+            ((37.8, -122.5, -91.0, -122.4), "bottom"),
+            ((37.8, -190.0, 37.7, -122.4), "left"),
+            ((37.8, -122.5, 37.7, 181.0), "right"),
+        ] {
+            match wikipedia.geosearch_bbox(bbox.0, bbox.1, bbox.2, bbox.3) {
+                Err(super::Error::InvalidParameter(p)) => assert_eq!(p, param),
+                _ => panic!("expected an invalid parameter error"),
+            }
+        }
+        assert!(wikipedia.client.arguments.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn geosearch_near_page() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"geosearch\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.geosearch_near_page("Obelisco", 100).unwrap(),
+            vec!["hello".to_owned(), "world".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "geosearch".to_owned()),
+                ("gsradius".to_owned(), "100".to_owned()),
+                ("gspage".to_owned(), "Obelisco".to_owned()),
+                ("gslimit".to_owned(), "10".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn geosearch_near_page_without_coordinates() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"error\":{\"code\":\"_badpage\",\"info\":\"Page \\\"Bikeshedding\\\" has no coordinates.\"}}".to_owned(),
+        );
+        match wikipedia.geosearch_near_page("Bikeshedding", 100) {
+            Err(super::Error::Api { code, info }) => {
+                assert_eq!(code, "_badpage");
+                assert_eq!(info, "Page \"Bikeshedding\" has no coordinates.");
+            }
+            _ => panic!("expected an API error"),
+        }
+    }
+
+    #[test]
+    fn geosearch_opts() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"geosearch\":[{\"title\":\"File:Hello.jpg\"}]}}".to_owned());
+        let options = super::GeoSearchOptions {
+            namespaces: vec![6, 2],
+            limit: Some(50),
+        };
+        assert_eq!(
+            wikipedia
+                .geosearch_opts(-34.603333, -58.381667, 10, &options)
+                .unwrap(),
+            vec!["File:Hello.jpg".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "geosearch".to_owned()),
+                ("gsradius".to_owned(), "10".to_owned()),
+                ("gscoord".to_owned(), "-34.603333|-58.381667".to_owned()),
+                ("gslimit".to_owned(), "50".to_owned()),
+                ("gsnamespace".to_owned(), "6|2".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn geosearch_opts_invalid_limit() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for limit in [0, 501] {
+            let options = super::GeoSearchOptions {
+                limit: Some(limit),
+                ..super::GeoSearchOptions::default()
+            };
+            match wikipedia.geosearch_opts(-34.603333, -58.381667, 10, &options) {
+                Err(super::Error::InvalidParameter(x)) => assert_eq!(x, "limit"),
+                r => panic!("unexpected result {:?}", r),
+            }
+        }
+        assert!(wikipedia.client.arguments.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn random_count() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"random\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.random_count(10).unwrap(),
+            vec!["hello".to_owned(), "world".to_owned(),]
+        );
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "random".to_owned()),
+                ("rnnamespace".to_owned(), "0".to_owned()),
+                ("rnlimit".to_owned(), "10".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn random_in_namespaces() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"random\":[{\"title\":\"Category:Hello\"}, {\"title\":\"File:World.jpg\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia
+                .random_in_namespaces(2, &[14, 6], super::RedirectFilter::NonRedirects)
+                .unwrap(),
+            vec!["Category:Hello".to_owned(), "File:World.jpg".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "random".to_owned()),
+                ("rnnamespace".to_owned(), "14|6".to_owned()),
+                ("rnfilterredir".to_owned(), "nonredirects".to_owned()),
+                ("rnlimit".to_owned(), "2".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+        for (count, namespaces, param) in [
+            (0, &[14][..], "count"),
+            (501, &[14], "count"),
+            (1, &[], "namespaces"),
+        ] {
+            match wikipedia.random_in_namespaces(count, namespaces, super::RedirectFilter::All) {
+                Err(super::Error::InvalidParameter(p)) => assert_eq!(p, param),
+                _ => panic!("expected an invalid parameter error"),
+            }
+        }
+    }
+
+    #[test]
+    fn random_pages() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"random\":[{\"id\":1,\"ns\":0,\"title\":\"hello\"}, {\"id\":2,\"ns\":0,\"title\":\"world\"}]}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"random\":[{\"id\":3,\"ns\":0,\"title\":\"foo\"}]}}".to_owned());
+        let pages = wikipedia.random_count_pages(2).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].get_title().unwrap(), "hello");
+        assert_eq!(pages[0].get_pageid().unwrap(), "1");
+        assert_eq!(pages[1].get_title().unwrap(), "world");
+        assert_eq!(pages[1].get_pageid().unwrap(), "2");
+        assert!(pages[0] == wikipedia.page_from_pageid("1".to_owned()));
+        assert!(pages[0] == wikipedia.page_from_title("hello".to_owned()));
+
+        let page = wikipedia.random_page().unwrap();
+        assert_eq!(page.get_title().unwrap(), "foo");
+        assert_eq!(page.get_pageid().unwrap(), "3");
+        // title and pageid come from the listing, no further requests
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn random() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"random\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        );
+        assert_eq!(wikipedia.random().unwrap(), Some("hello".to_owned()));
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("list".to_owned(), "random".to_owned()),
+                ("rnnamespace".to_owned(), "0".to_owned()),
+                ("rnlimit".to_owned(), "1".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn image_info() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"ns\":6,\"title\":\"File:Einstein 1921.jpg\",\"missing\":\"\",\"imagerepository\":\"shared\",\"imageinfo\":[{\"size\":1204245,\"width\":2400,\"height\":3000,\"url\":\"https://upload.wikimedia.org/e.jpg\",\"descriptionurl\":\"https://commons.wikimedia.org/wiki/File:Einstein_1921.jpg\",\"mime\":\"image/jpeg\",\"extmetadata\":{\"LicenseShortName\":{\"value\":\"Public domain\",\"source\":\"commons-desc-page\"},\"Artist\":{\"value\":\"Ferdinand Schmutzer\",\"source\":\"commons-desc-page\"},\"NonFree\":{\"value\":false,\"source\":\"commons-desc-page\"}}}]}}}}".to_owned(),
+        );
+        let info = wikipedia.image_info("File:Einstein_1921.jpg").unwrap();
+        assert_eq!(info.title, "File:Einstein 1921.jpg");
+        assert_eq!(info.url, "https://upload.wikimedia.org/e.jpg");
+        assert_eq!((info.width, info.height, info.size), (2400, 3000, 1204245));
+        assert_eq!(info.mime, "image/jpeg");
+        assert_eq!(info.license(), Some("Public domain"));
+        assert_eq!(info.artist(), Some("Ferdinand Schmutzer"));
+        assert_eq!(info.metadata.get("NonFree").unwrap(), "false");
+        assert_eq!(info.thumb_url, None);
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "imageinfo".to_owned()),
+                ("iiprop".to_owned(), "url|size|mime|extmetadata".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "File:Einstein_1921.jpg".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn image_info_scaled() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"title\":\"File:Map.svg\",\"imageinfo\":[{\"url\":\"https://upload.wikimedia.org/Map.svg\",\"mime\":\"image/svg+xml\",\"thumburl\":\"https://upload.wikimedia.org/thumb/Map.svg/320px-Map.svg.png\",\"thumbwidth\":320,\"thumbheight\":240}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"title\":\"File:Doc.pdf\",\"imageinfo\":[{\"url\":\"https://upload.wikimedia.org/Doc.pdf\",\"mime\":\"application/pdf\",\"thumburl\":\"https://upload.wikimedia.org/thumb/Doc.pdf/page1-100px-Doc.pdf.jpg\"}]}}}}".to_owned(),
+        );
+        let info = wikipedia.image_info_scaled("File:Map.svg", 320).unwrap();
+        assert_eq!(
+            info.thumb_url.unwrap(),
+            "https://upload.wikimedia.org/thumb/Map.svg/320px-Map.svg.png"
+        );
+        assert_eq!(
+            (info.thumb_width, info.thumb_height),
+            (Some(320), Some(240))
+        );
+        let info = wikipedia
+            .image_info_bounded("File:Doc.pdf", 100, 100)
+            .unwrap();
+        assert!(info.thumb_url.is_some());
+        assert_eq!((info.thumb_width, info.thumb_height), (None, None));
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[0][2], ("iiurlwidth".to_owned(), "320".to_owned()));
+        assert_eq!(
+            arguments[1][2..4],
+            [
+                ("iiurlwidth".to_owned(), "100".to_owned()),
+                ("iiurlheight".to_owned(), "100".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn image_infos() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"title\":\"File:B.png\",\"imageinfo\":[{\"url\":\"b\",\"mime\":\"image/png\"}]},\"-2\":{\"title\":\"File:A.png\",\"imageinfo\":[{\"url\":\"a\",\"mime\":\"image/png\"}]},\"-3\":{\"title\":\"File:Nope.png\",\"missing\":\"\"}}}}".to_owned(),
+        );
+        let infos = wikipedia
+            .image_infos(&["File:A.png", "File:Nope.png", "File:B.png"])
+            .unwrap();
+        assert_eq!(
+            infos.iter().map(|x| &*x.url).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][4],
+            (
+                "titles".to_owned(),
+                "File:A.png|File:Nope.png|File:B.png".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn image_info_missing() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"ns\":6,\"title\":\"File:Nope.png\",\"missing\":\"\"}}}}".to_owned(),
+        );
+        match wikipedia.image_info("File:Nope.png") {
+            Err(super::Error::PageNotFound { title }) => assert_eq!(title, "File:Nope.png"),
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    fn image_usage() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"iucontinue\":\"0|Physics\",\"continue\":\"-||\"},\"query\":{\"imageusage\":[{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"imageusage\":[{\"pageid\":22939,\"ns\":0,\"title\":\"Physics\"},{\"pageid\":9,\"ns\":2,\"title\":\"User:Someone\"}]}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia
+                .image_usage("Einstein 1921.jpg")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![
+                iter::ImageUsage {
+                    title: "Albert Einstein".to_owned(),
+                    pageid: 736,
+                    ns: 0,
+                },
+                iter::ImageUsage {
+                    title: "Physics".to_owned(),
+                    pageid: 22939,
+                    ns: 0,
+                },
+                iter::ImageUsage {
+                    title: "User:Someone".to_owned(),
+                    pageid: 9,
+                    ns: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("list".to_owned(), "imageusage".to_owned()),
+                    ("iutitle".to_owned(), "File:Einstein 1921.jpg".to_owned()),
+                    ("iulimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("continue".to_owned(), "".to_owned()),
+                ],
+                vec![
+                    ("list".to_owned(), "imageusage".to_owned()),
+                    ("iutitle".to_owned(), "File:Einstein 1921.jpg".to_owned()),
+                    ("iulimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("continue".to_owned(), "-||".to_owned()),
+                    ("iucontinue".to_owned(), "0|Physics".to_owned()),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn category_members() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"cmcontinue\":\"page|4e4f|123\",\"continue\":\"-||\"},\"query\":{\"categorymembers\":[{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\",\"type\":\"page\",\"timestamp\":\"2020-01-01T00:00:00Z\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"categorymembers\":[{\"pageid\":99,\"ns\":14,\"title\":\"Category:German physicists\",\"type\":\"subcat\"}]}}".to_owned(),
+        );
+        let members = wikipedia
+            .category_members("Physicists")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            vec![
+                iter::CategoryMember {
+                    title: "Albert Einstein".to_owned(),
+                    pageid: 736,
+                    ns: 0,
+                    kind: super::CategoryMemberType::Page,
+                    timestamp: Some("2020-01-01T00:00:00Z".to_owned()),
+                },
+                iter::CategoryMember {
+                    title: "Category:German physicists".to_owned(),
+                    pageid: 99,
+                    ns: 14,
+                    kind: super::CategoryMemberType::Subcat,
+                    timestamp: None,
+                },
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("list".to_owned(), "categorymembers".to_owned()),
+                ("cmtitle".to_owned(), "Category:Physicists".to_owned()),
+                ("cmprop".to_owned(), "ids|title|type|timestamp".to_owned()),
+                ("cmlimit".to_owned(), "max".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][6],
+            ("cmcontinue".to_owned(), "page|4e4f|123".to_owned())
+        );
+    }
+
+    #[test]
+    fn category_members_opts() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"categorymembers\":[]}}".to_owned());
+        let options = super::CategoryMembersOptions {
+            types: vec![
+                super::CategoryMemberType::Page,
+                super::CategoryMemberType::File,
+            ],
+            sort_by_timestamp: true,
+        };
+        assert_eq!(
+            wikipedia
+                .category_members_opts("Category:Physicists", &options)
+                .unwrap()
+                .count(),
+            0
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][1..6],
+            [
+                ("cmtitle".to_owned(), "Category:Physicists".to_owned()),
+                ("cmprop".to_owned(), "ids|title|type|timestamp".to_owned()),
+                ("cmlimit".to_owned(), "max".to_owned()),
+                ("cmtype".to_owned(), "page|file".to_owned()),
+                ("cmsort".to_owned(), "timestamp".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn category_members_recursive() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        // A contains x and B, B contains y, C and A again, C contains z
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"categorymembers\":[{\"pageid\":1,\"ns\":0,\"title\":\"x\",\"type\":\"page\"},{\"pageid\":2,\"ns\":14,\"title\":\"Category:B\",\"type\":\"subcat\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"categorymembers\":[{\"pageid\":3,\"ns\":0,\"title\":\"y\",\"type\":\"page\"},{\"pageid\":4,\"ns\":14,\"title\":\"Category:C\",\"type\":\"subcat\"},{\"pageid\":5,\"ns\":14,\"title\":\"Category:A\",\"type\":\"subcat\"},{\"pageid\":1,\"ns\":0,\"title\":\"x\",\"type\":\"page\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"categorymembers\":[{\"pageid\":6,\"ns\":0,\"title\":\"z\",\"type\":\"page\"}]}}".to_owned());
+        let titles = wikipedia
+            .category_members_recursive("A", 5, 100)
+            .map(|x| x.title)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            vec!["x", "Category:B", "y", "Category:C", "z"]
+                .into_iter()
+                .map(|x| x.to_owned())
+                .collect::<Vec<_>>()
+        );
+        let cmtitles = wikipedia
+            .client
+            .arguments
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x[1].1.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cmtitles,
+            vec!["Category:A", "Category:B", "Category:C"]
+                .into_iter()
+                .map(|x| x.to_owned())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn category_members_recursive_limits() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"categorymembers\":[{\"pageid\":2,\"ns\":14,\"title\":\"Category:B\",\"type\":\"subcat\"},{\"pageid\":1,\"ns\":0,\"title\":\"x\",\"type\":\"page\"},{\"pageid\":3,\"ns\":0,\"title\":\"y\",\"type\":\"page\"}]}}".to_owned());
+        assert_eq!(wikipedia.category_members_recursive("A", 0, 2).count(), 2);
+        // with depth 0 Category:B is never fetched
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn all_pages() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"apcontinue\":\"List_of_b\",\"continue\":\"-||\"},\"query\":{\"allpages\":[{\"pageid\":1,\"ns\":0,\"title\":\"List of a\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"allpages\":[{\"pageid\":2,\"ns\":0,\"title\":\"List of b\"}]}}"
+                .to_owned(),
+        );
+        assert_eq!(
+            wikipedia
+                .all_pages(Some("List of "), 0)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![
+                iter::AllPage {
+                    title: "List of a".to_owned(),
+                    pageid: 1,
+                    ns: 0,
+                },
+                iter::AllPage {
+                    title: "List of b".to_owned(),
+                    pageid: 2,
+                    ns: 0,
+                },
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("list".to_owned(), "allpages".to_owned()),
+                ("apnamespace".to_owned(), "0".to_owned()),
+                ("aplimit".to_owned(), "max".to_owned()),
+                ("apprefix".to_owned(), "List of ".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][6],
+            ("apcontinue".to_owned(), "List_of_b".to_owned())
+        );
+    }
+
+    #[test]
+    fn all_pages_opts() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"allpages\":[]}}".to_owned());
+        let options = super::AllPagesOptions {
+            redirects: Some(super::RedirectFilter::NonRedirects),
+            min_size: Some(100),
+            max_size: Some(5000),
+        };
+        assert_eq!(
+            wikipedia.all_pages_opts(None, 4, &options).unwrap().count(),
+            0
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][..6],
+            [
+                ("list".to_owned(), "allpages".to_owned()),
+                ("apnamespace".to_owned(), "4".to_owned()),
+                ("aplimit".to_owned(), "max".to_owned()),
+                ("apfilterredir".to_owned(), "nonredirects".to_owned()),
+                ("apminsize".to_owned(), "100".to_owned()),
+                ("apmaxsize".to_owned(), "5000".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_changes() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"rccontinue\":\"20240101000000|123\",\"continue\":\"-||\"},\"query\":{\"recentchanges\":[{\"type\":\"edit\",\"ns\":0,\"title\":\"Physics\",\"pageid\":22939,\"revid\":1002,\"old_revid\":1001,\"rcid\":5,\"user\":\"Someone\",\"oldlen\":100,\"newlen\":120,\"timestamp\":\"2024-01-01T00:00:01Z\",\"comment\":\"typo\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"recentchanges\":[{\"type\":\"new\",\"ns\":0,\"title\":\"Stub\",\"pageid\":9,\"revid\":1000,\"old_revid\":0,\"rcid\":4,\"user\":\"127.0.0.1\",\"anon\":\"\",\"oldlen\":0,\"newlen\":50,\"timestamp\":\"2024-01-01T00:00:00Z\",\"comment\":\"\"}]}}".to_owned());
+        let options = super::RecentChangesOptions {
+            namespaces: vec![0],
+            show: Some("!bot|!minor".to_owned()),
+            types: vec![super::ChangeType::Edit, super::ChangeType::New],
+            start: Some("2024-01-02T00:00:00Z".to_owned()),
+            end: Some("2024-01-01T00:00:00Z".to_owned()),
+        };
+        let changes = wikipedia
+            .recent_changes(options)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes[0],
+            iter::RecentChange {
+                kind: super::ChangeType::Edit,
+                title: "Physics".to_owned(),
+                ns: 0,
+                timestamp: "2024-01-01T00:00:01Z".to_owned(),
+                user: "Someone".to_owned(),
+                comment: "typo".to_owned(),
+                old_len: 100,
+                new_len: 120,
+                revid: 1002,
+                old_revid: 1001,
+            }
+        );
+        assert_eq!(changes[1].kind, super::ChangeType::New);
+        assert_eq!(changes[1].user, "127.0.0.1");
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("list".to_owned(), "recentchanges".to_owned()),
+                (
+                    "rcprop".to_owned(),
+                    "title|ids|sizes|timestamp|user|comment".to_owned()
+                ),
+                ("rclimit".to_owned(), "max".to_owned()),
+                ("rcnamespace".to_owned(), "0".to_owned()),
+                ("rcshow".to_owned(), "!bot|!minor".to_owned()),
+                ("rctype".to_owned(), "edit|new".to_owned()),
+                ("rcstart".to_owned(), "2024-01-02T00:00:00Z".to_owned()),
+                ("rcend".to_owned(), "2024-01-01T00:00:00Z".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][11],
+            ("rccontinue".to_owned(), "20240101000000|123".to_owned())
+        );
+    }
+
+    #[test]
+    fn recent_changes_unknown_type() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"rccontinue\":\"20240101000000|123\",\"continue\":\"-||\"},\"query\":{\"recentchanges\":[{\"type\":\"future\",\"ns\":0,\"title\":\"Physics\"},{\"type\":\"edit\",\"ns\":0,\"title\":\"Chemistry\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"recentchanges\":[{\"type\":\"new\",\"ns\":0,\"title\":\"Stub\"}]}}"
+                .to_owned(),
+        );
+        let mut changes = wikipedia
+            .recent_changes(super::RecentChangesOptions::default())
+            .unwrap();
+        assert_eq!(changes.size_hint(), (1, None));
+        assert_eq!(
+            changes.by_ref().map(|x| x.title).collect::<Vec<_>>(),
+            vec!["Chemistry", "Stub"]
+        );
+        assert_eq!(changes.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn list_iter_error() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        // the second batch fails since there is no response for it
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"apcontinue\":\"B\",\"continue\":\"-||\"},\"query\":{\"allpages\":[{\"pageid\":1,\"ns\":0,\"title\":\"A\"}]}}".to_owned());
+        let mut pages = wikipedia.all_pages(None, 0).unwrap().fallible();
+        assert_eq!(pages.next().unwrap().unwrap().title, "A");
+        assert!(matches!(pages.next(), Some(Err(super::Error::IOError(_)))));
+        assert!(pages.next().is_none());
+        assert_eq!(pages.size_hint(), (0, Some(0)));
+
+        // the failed batch can be retried
+        let token = pages.continue_token().unwrap();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"allpages\":[{\"pageid\":2,\"ns\":0,\"title\":\"B\"}]}}".to_owned(),
+        );
+        let pages: iter::ListIter<'_, _, iter::AllPage> =
+            iter::ListIter::from_token(&wikipedia, &token).unwrap();
+        assert_eq!(pages.map(|x| x.title).collect::<Vec<_>>(), vec!["B"]);
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[2], arguments[1]);
+    }
+
+    #[test]
+    fn user_contributions() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"uccontinue\":\"20240101000000|1001\",\"continue\":\"-||\"},\"query\":{\"usercontribs\":[{\"userid\":0,\"user\":\"127.0.0.1\",\"pageid\":22939,\"revid\":1002,\"parentid\":1000,\"ns\":0,\"title\":\"Physics\",\"timestamp\":\"2024-01-02T00:00:00Z\",\"comment\":\"typo\",\"size\":120,\"sizediff\":-3}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"usercontribs\":[{\"pageid\":9,\"revid\":1001,\"parentid\":0,\"ns\":4,\"title\":\"Wikipedia:Sandbox\",\"timestamp\":\"2024-01-01T00:00:00Z\",\"comment\":\"\",\"size\":50,\"sizediff\":50}]}}".to_owned());
+        let contributions = wikipedia
+            .user_contributions_in_namespaces("127.0.0.1", 600, &[0, 4])
+            .unwrap();
+        assert_eq!(
+            contributions[0],
+            super::Contribution {
+                title: "Physics".to_owned(),
+                ns: 0,
+                revid: 1002,
+                parentid: 1000,
+                timestamp: "2024-01-02T00:00:00Z".to_owned(),
+                comment: "typo".to_owned(),
+                size: 120,
+                size_diff: -3,
+            }
+        );
+        assert_eq!(contributions[1].size_diff, 50);
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("list".to_owned(), "usercontribs".to_owned()),
+                ("ucuser".to_owned(), "127.0.0.1".to_owned()),
+                (
+                    "ucprop".to_owned(),
+                    "ids|title|timestamp|comment|size|sizediff".to_owned()
+                ),
+                ("uclimit".to_owned(), "500".to_owned()),
+                ("ucnamespace".to_owned(), "0|4".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(arguments[1][3], ("uclimit".to_owned(), "500".to_owned()));
+        assert_eq!(
+            arguments[1][8],
+            ("uccontinue".to_owned(), "20240101000000|1001".to_owned())
+        );
+    }
+
+    #[test]
+    fn render_wikitext() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"parse\":{\"title\":\"Sandbox\",\"pageid\":0,\"text\":{\"*\":\"<div class=\\\"mw-parser-output\\\"><p><b>Sandbox</b></p></div>\"}}}".to_owned());
+        assert_eq!(
+            wikipedia
+                .render_wikitext("'''{{PAGENAME}}'''", Some("Sandbox"))
+                .unwrap(),
+            "<div class=\"mw-parser-output\"><p><b>Sandbox</b></p></div>"
+        );
+        assert_eq!(*wikipedia.client.method.lock().unwrap(), vec!["POST"]);
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "text".to_owned()),
+                ("contentmodel".to_owned(), "wikitext".to_owned()),
+                ("disablelimitreport".to_owned(), "".to_owned()),
+                ("text".to_owned(), "'''{{PAGENAME}}'''".to_owned()),
+                ("title".to_owned(), "Sandbox".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "parse".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_info() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\",\"touched\":\"2024-01-01T00:00:00Z\",\"length\":12345,\"protection\":[{\"type\":\"edit\",\"level\":\"autoconfirmed\",\"expiry\":\"infinity\"}],\"watchers\":200,\"fullurl\":\"https://en.wikipedia.org/wiki/Law_of_triviality\"}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        assert_eq!(
+            page.get_info().unwrap(),
+            super::PageInfo {
+                pageid: 4138548,
+                title: "Law of triviality".to_owned(),
+                length: 12345,
+                touched: "2024-01-01T00:00:00Z".to_owned(),
+                is_redirect: false,
+                full_url: "https://en.wikipedia.org/wiki/Law_of_triviality".to_owned(),
+                watchers: Some(200),
+                protection: vec![super::Protection {
+                    kind: "edit".to_owned(),
+                    level: "autoconfirmed".to_owned(),
+                    expiry: "infinity".to_owned(),
+                    cascade: false,
+                    source: None,
+                }],
+            }
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "info".to_owned()),
+                ("inprop".to_owned(), "watchers|protection|url".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("pageids".to_owned(), "4138548".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_protection() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Main Page\",\"protection\":[{\"type\":\"edit\",\"level\":\"sysop\",\"expiry\":\"infinity\",\"cascade\":\"\"},{\"type\":\"move\",\"level\":\"sysop\",\"expiry\":\"2030-01-01T00:00:00Z\"}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"2\":{\"pageid\":2,\"title\":\"Template:Foo\",\"protection\":[{\"type\":\"edit\",\"level\":\"sysop\",\"expiry\":\"infinity\",\"source\":\"Main Page\"}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"3\":{\"pageid\":3,\"title\":\"Sandbox\",\"protection\":[]}}}}"
+                .to_owned(),
+        );
+        let protection = wikipedia
+            .page_from_title("Main Page".to_owned())
+            .get_protection()
+            .unwrap();
+        assert_eq!(protection.len(), 2);
+        assert!(protection[0].cascade);
+        assert_eq!(protection[0].expires(), None);
+        assert_eq!(protection[1].kind, "move");
+        assert!(!protection[1].cascade);
+        assert_eq!(protection[1].expires(), Some("2030-01-01T00:00:00Z"));
+        let protection = wikipedia
+            .page_from_title("Template:Foo".to_owned())
+            .get_protection()
+            .unwrap();
+        assert_eq!(protection[0].source, Some("Main Page".to_owned()));
+        assert!(wikipedia
+            .page_from_title("Sandbox".to_owned())
+            .get_protection()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "info".to_owned()),
+                ("inprop".to_owned(), "protection".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Main Page".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_info_missing() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Hello world 123\",\"missing\":\"\"}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Hello world 123".to_owned());
+        match page.get_info() {
+            Err(super::Error::PageNotFound { title }) => assert_eq!(title, "Hello world 123"),
+            _ => panic!("expected a page not found error"),
+        }
+    }
+
+    #[test]
+    fn page_resolve_redirect() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"normalized\":[{\"from\":\"albert_einstein\",\"to\":\"Albert einstein\"}],\"redirects\":[{\"from\":\"Albert einstein\",\"to\":\"Albert Einstein\"}],\"pages\":{\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\"}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("albert_einstein".to_owned());
+        assert_eq!(
+            page.resolve_redirect().unwrap(),
+            Some("Albert Einstein".to_owned())
+        );
+        assert_eq!(page.get_title().unwrap(), "Albert Einstein".to_owned());
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "info".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "albert_einstein".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_resolve_redirect_fragment() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"redirects\":[{\"from\":\"Bikeshed\",\"to\":\"Law of triviality\",\"tofragment\":\"Argument\"}],\"pages\":{\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\"}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\"}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Bikeshed".to_owned());
+        assert_eq!(
+            page.resolve_redirect().unwrap(),
+            Some("Law of triviality".to_owned())
+        );
+        let page = wikipedia.page_from_title("Law of triviality".to_owned());
+        assert_eq!(page.resolve_redirect().unwrap(), None);
+    }
+
+    #[test]
+    fn page_redirect_updates_title() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"redirects\":[{\"to\":\"hello world\"}]}}".to_owned());
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
+        let page = wikipedia.page_from_title("Hello".to_owned());
+        page.get_content().unwrap();
+        assert_eq!(page.get_title().unwrap(), "hello world".to_owned());
+    }
+
+    #[test]
+    fn no_follow_redirects() {
+        let wikipedia = Wikipedia::<MockClient> {
+            follow_redirects: false,
+            ..Wikipedia::default()
+        };
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"#REDIRECT hello\"}}}}".to_owned());
+        let page = wikipedia.page_from_title("Hello".to_owned());
+        assert_eq!(page.get_content().unwrap(), "#REDIRECT hello".to_owned());
+        assert!(!wikipedia.client.arguments.lock().unwrap()[0]
+            .iter()
+            .any(|x| x.0 == "redirects"));
+    }
+
+    #[test]
+    fn page_exists() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\"}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nope\",\"missing\":\"\"}}}}"
+                .to_owned(),
+        );
+        assert!(wikipedia
+            .page_from_title("Albert Einstein".to_owned())
+            .exists()
+            .unwrap());
+        assert!(!wikipedia
+            .page_from_title("Nope".to_owned())
+            .exists()
+            .unwrap());
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "info".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Albert Einstein".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_missing() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for _ in 0..3 {
+            wikipedia.client.response.lock().unwrap().push(
+                "{\"query\":{\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nope\",\"missing\":\"\"}}}}"
+                    .to_owned(),
+            );
+        }
+        let page = wikipedia.page_from_title("Nope".to_owned());
+        for result in [page.get_content(), page.get_summary(), page.get_pageid()] {
+            match result {
+                Err(super::Error::PageNotFound { title }) => assert_eq!(title, "Nope"),
+                r => panic!("unexpected {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn page_content() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        let html = page.get_content().unwrap();
+        assert_eq!(html, "hello".to_owned());
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "extracts|revisions".to_owned()),
+                ("explaintext".to_owned(), "".to_owned()),
+                ("rvprop".to_owned(), "ids".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("pageids".to_owned(), "4138548".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_html_content() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"revisions\":[{\"*\":\"hello\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        let html = page.get_html_content().unwrap();
+        assert_eq!(html, "hello".to_owned());
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "revisions".to_owned()),
+                ("rvprop".to_owned(), "content".to_owned()),
+                ("rvlimit".to_owned(), "1".to_owned()),
+                ("rvparse".to_owned(), "".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("pageids".to_owned(), "4138548".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_wikitext() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"revisions\":[{\"slots\":{\"main\":{\"contentmodel\":\"wikitext\",\"*\":\"[[hello]]\"}}}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"revisions\":[{\"*\":\"== World ==\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        assert_eq!(page.get_wikitext().unwrap(), "[[hello]]".to_owned());
+        assert_eq!(
+            page.get_section_wikitext(1).unwrap(),
+            "== World ==".to_owned()
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("prop".to_owned(), "revisions".to_owned()),
+                    ("rvprop".to_owned(), "content".to_owned()),
+                    ("rvslots".to_owned(), "main".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("pageids".to_owned(), "4138548".to_owned()),
+                ],
+                vec![
+                    ("prop".to_owned(), "revisions".to_owned()),
+                    ("rvprop".to_owned(), "content".to_owned()),
+                    ("rvslots".to_owned(), "main".to_owned()),
+                    ("rvsection".to_owned(), "1".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("pageids".to_owned(), "4138548".to_owned()),
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn infobox_country() {
+        let wikitext = r#"{{Short description|Country in South America}}
+{{Infobox country
+| conventional_long_name = Argentine Republic
+| common_name = Argentina
+| image_flag = Flag of Argentina.svg
+| capital = [[Buenos Aires]]<br/>{{coord|34|36|S|58|23|W|type:city}}
+| official_languages = [[Spanish language|Spanish]]<ref>{{cite web|url=https://example.com|title=Languages}}</ref>
+| area_km2 = 2,780,400 <!-- 1,073,500 sq mi -->
+| population_estimate = {{increase}} 46,044,703
+| GDP_PPP =
+| currency = [[Argentine peso]] ([[Dollar sign|$]])
+}}
+'''Argentina''' is a country."#;
+        let infobox = super::wikitext::parse_infobox(wikitext).unwrap();
+        assert_eq!(infobox["common_name"], "Argentina");
+        assert_eq!(
+            infobox["capital"],
+            "[[Buenos Aires]]<br/>{{coord|34|36|S|58|23|W|type:city}}"
+        );
+        assert_eq!(
+            infobox["official_languages"],
+            "[[Spanish language|Spanish]]<ref>{{cite web|url=https://example.com|title=Languages}}</ref>"
+        );
+        assert_eq!(infobox["area_km2"], "2,780,400");
+        assert_eq!(
+            infobox["currency"],
+            "[[Argentine peso]] ([[Dollar sign|$]])"
+        );
+        assert!(!infobox.contains_key("GDP_PPP"));
+        assert_eq!(infobox.len(), 8);
+    }
+
+    #[test]
+    fn infobox_person() {
+        let wikitext = r#"{{pp-semi-indef}}
+{{infobox scientist
+| name = Albert Einstein
+| birth_date = {{Birth date|df=yes|1879|3|14}}
+| birth_place = [[Ulm]], [[Kingdom of Württemberg|Württemberg]], [[German Empire]]
+| fields = [[Physics]], [[philosophy]]
+| awards = {{plainlist|
+* [[Barnard Medal for Meritorious Service to Science|Barnard Medal]] (1920)
+* [[Nobel Prize in Physics]] (1921)
+}}
+| signature = Albert Einstein signature 1934.svg
+}}"#;
+        let infobox = super::wikitext::parse_infobox(wikitext).unwrap();
+        assert_eq!(infobox["name"], "Albert Einstein");
+        assert_eq!(infobox["birth_date"], "{{Birth date|df=yes|1879|3|14}}");
+        assert!(infobox["awards"].ends_with("(1921)\n}}"));
+        assert_eq!(
+            infobox["birth_place"],
+            "[[Ulm]], [[Kingdom of Württemberg|Württemberg]], [[German Empire]]"
+        );
+    }
+
+    #[test]
+    fn infobox_film() {
+        let wikitext = r#"{{Infobox film
+| name           = Metropolis
+| director       = [[Fritz Lang]]
+| starring       = {{ubl|[[Alfred Abel]]|[[Brigitte Helm]]}}
+| music          = [[Gottfried Huppertz]]
+| released       = {{film date|1927|01|10|Germany}}
+| runtime        = 153 minutes<ref name="runtime"/>
+| country        = Weimar Republic
+| budget         = 5 million [[Reichsmark|ℛℳ]]
+}}"#;
+        let infobox = super::wikitext::parse_infobox(wikitext).unwrap();
+        assert_eq!(infobox["director"], "[[Fritz Lang]]");
+        assert_eq!(
+            infobox["starring"],
+            "{{ubl|[[Alfred Abel]]|[[Brigitte Helm]]}}"
+        );
+        assert_eq!(infobox["runtime"], "153 minutes<ref name=\"runtime\"/>");
+        assert_eq!(infobox["budget"], "5 million [[Reichsmark|ℛℳ]]");
+    }
+
+    #[test]
+    fn infobox_missing() {
+        assert_eq!(
+            super::wikitext::parse_infobox("{{Short description|x}} No infobox. {{Reflist}}"),
+            None
+        );
+        // unterminated templates are not parsed
+        assert_eq!(
+            super::wikitext::parse_infobox("{{Infobox person | name = x"),
+            None
+        );
+    }
+
+    #[test]
+    fn page_infobox() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"1\":{\"revisions\":[{\"slots\":{\"main\":{\"*\":\"{{Infobox person\\n| name = Ada\\n}}\\nAda was a mathematician.\"}}}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"2\":{\"revisions\":[{\"slots\":{\"main\":{\"*\":\"No infobox here.\"}}}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("Ada Lovelace".to_owned());
+        let infobox = page.get_infobox().unwrap().unwrap();
+        assert_eq!(infobox["name"], "Ada");
+        assert_eq!(page.get_infobox().unwrap(), None);
+    }
+
+    #[test]
+    fn first_paragraph_einstein() {
+        let html = r##"<p class="mw-empty-elt">
+</p>
+<p><b>Albert Einstein</b> (<span><span>/<span title="/ˈ/: primary stress follows">ˈ</span><span>aɪ</span>n<span>s</span>t<span>aɪ</span>n/</span></span> <span><i>EYEN</i>-styne</span>;<sup id="cite_ref-3" class="reference"><a href="#cite_note-3">&#91;3&#93;</a></sup> <small>German:</small> <span>[ˈalbɛʁt ˈʔaɪnʃtaɪn]</span> (<span>listen</span>); 14&#160;March 1879&#160;– 18&#160;April 1955) was a German-born <a href="/wiki/Theoretical_physicist">theoretical physicist</a>.[4] He is best known for developing the <a href="/wiki/Theory_of_relativity">theory of relativity</a>[citation needed].</p>
+<p>Born in the German Empire, Einstein moved to Switzerland in 1895.</p>"##;
+        assert_eq!(
+            super::html::first_paragraph(html).unwrap(),
+            "Albert Einstein was a German-born theoretical physicist. He is best known for developing the theory of relativity."
+        );
+    }
+
+    #[test]
+    fn first_paragraph_keeps_plain_parentheses() {
+        let html = "<p><b>Buenos Aires</b> (<i>Spanish for</i> &quot;fair winds&quot;) is the capital &amp; largest city of <a href=\"/wiki/Argentina\">Argentina</a>.[a] [Note 2]\n\n  It lies on the [western] shore .</p>";
+        assert_eq!(
+            super::html::first_paragraph(html).unwrap(),
+            "Buenos Aires (Spanish for \"fair winds\") is the capital & largest city of Argentina. It lies on the [western] shore."
+        );
+    }
+
+    #[test]
+    fn first_paragraph_keeps_words_containing_markers() {
+        let html = "<p><b>Lisbon</b> (principal city of the <i>municipality of Lisbon</i>) is the capital of Portugal (<span class=\"IPA\">IPA: [liʒˈboɐ̃]</span>).</p>";
+        assert_eq!(
+            super::html::first_paragraph(html).unwrap(),
+            "Lisbon (principal city of the municipality of Lisbon) is the capital of Portugal."
+        );
+    }
+
+    #[test]
+    fn first_paragraph_empty() {
+        assert_eq!(super::html::first_paragraph(""), None);
+        assert_eq!(
+            super::html::first_paragraph("<p class=\"mw-empty-elt\"></p><ul><li>list</li></ul>"),
+            None
+        );
+        assert_eq!(
+            super::html::first_paragraph("<pre>code</pre><p>Text.</p>").unwrap(),
+            "Text."
+        );
+    }
+
+    #[test]
+    fn page_first_paragraph() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Ada Lovelace\",\"extract\":\"<p><b>Ada Lovelace</b> (pronounced <i>LUV-layss</i>) was a mathematician.</p><p>More.</p>\"}}}}".to_owned());
+        let page = wikipedia.page_from_title("Ada Lovelace".to_owned());
+        assert_eq!(
+            page.get_first_paragraph().unwrap(),
+            "Ada Lovelace was a mathematician."
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "extracts".to_owned()),
+                ("exintro".to_owned(), "".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Ada Lovelace".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_contributors() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"pccontinue\":\"1|17\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Foo\",\"anoncontributors\":12,\"contributors\":[{\"userid\":3,\"name\":\"Alice\"},{\"userid\":9,\"name\":\"Bob\"}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Foo\",\"contributors\":[{\"userid\":17,\"name\":\"Carol\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Foo".to_owned());
+        let contributors = page
+            .get_contributors_opts(&super::ContributorsOptions {
+                exclude_groups: vec!["bot".to_owned()],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(contributors.anonymous, 12);
+        assert_eq!(
+            contributors
+                .users
+                .iter()
+                .map(|x| (x.userid, &*x.name))
+                .collect::<Vec<_>>(),
+            vec![(3, "Alice"), (9, "Bob"), (17, "Carol")]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "contributors".to_owned()),
+                ("pclimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Foo".to_owned()),
+                ("pcexcludegroup".to_owned(), "bot".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][8],
+            ("pccontinue".to_owned(), "1|17".to_owned())
+        );
+    }
+
+    #[test]
+    fn page_contributors_conflicting_groups() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        let page = wikipedia.page_from_title("Foo".to_owned());
+        let options = super::ContributorsOptions {
+            groups: vec!["sysop".to_owned()],
+            exclude_groups: vec!["bot".to_owned()],
+        };
+        assert!(matches!(
+            page.get_contributors_opts(&options),
+            Err(super::Error::InvalidParameter(_))
+        ));
+        assert!(wikipedia.client.arguments.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn page_assessments() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"pacontinue\":\"1|Physics\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Albert Einstein\",\"pageassessments\":{\"Biography\":{\"class\":\"FA\",\"importance\":\"\"},\"Germany\":{\"class\":\"FA\",\"importance\":\"Top\"}}}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Albert Einstein\",\"pageassessments\":{\"Physics\":{\"class\":\"GA\",\"importance\":\"NA\"},\"Judaism\":{\"class\":\"Portal\",\"importance\":\"Mid\"}}}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        let assessments = page.get_assessments().unwrap();
+        assert_eq!(assessments.len(), 4);
+        assert_eq!(
+            assessments["Germany"],
+            super::Assessment {
+                class: super::QualityClass::Featured,
+                importance: super::Importance::Top,
+            }
+        );
+        assert_eq!(
+            assessments["Biography"].importance,
+            super::Importance::Unknown
+        );
+        assert_eq!(
+            assessments["Physics"],
+            super::Assessment {
+                class: super::QualityClass::Good,
+                importance: super::Importance::NotApplicable,
+            }
+        );
+        assert_eq!(
+            assessments["Judaism"].class,
+            super::QualityClass::Other("Portal".to_owned())
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "pageassessments".to_owned()),
+                ("palimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Albert Einstein".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][7],
+            ("pacontinue".to_owned(), "1|Physics".to_owned())
+        );
+    }
+
+    #[test]
+    fn get_pages() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"excontinue\":1,\"continue\":\"||info|pageprops\"},\"query\":{\"normalized\":[{\"from\":\"bikeshedding\",\"to\":\"Bikeshedding\"}],\"redirects\":[{\"from\":\"Bikeshedding\",\"to\":\"Law of triviality\"}],\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nonexistent\",\"missing\":\"\"},\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\",\"extract\":\"The law of triviality is...\",\"length\":12345,\"touched\":\"2024-01-01T00:00:00Z\",\"pageprops\":{\"wikibase_item\":\"Q1\"}},\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\",\"length\":200000,\"touched\":\"2024-02-01T00:00:00Z\"}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"batchcomplete\":\"\",\"query\":{\"normalized\":[{\"from\":\"bikeshedding\",\"to\":\"Bikeshedding\"}],\"redirects\":[{\"from\":\"Bikeshedding\",\"to\":\"Law of triviality\"}],\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nonexistent\",\"missing\":\"\"},\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\"},\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\",\"extract\":\"Albert Einstein was...\"}}}}".to_owned(),
+        );
+        let pages = wikipedia
+            .get_pages(&[
+                "bikeshedding",
+                "Law of triviality",
+                "Albert Einstein",
+                "Nonexistent",
+            ])
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+        let law = &pages["Law of triviality"];
+        assert_eq!(law.pageid, 4138548);
+        assert_eq!(law.extract, Some("The law of triviality is...".to_owned()));
+        assert_eq!(law.pageprops["wikibase_item"], "Q1");
+        assert_eq!(
+            law.requested,
+            vec!["bikeshedding".to_owned(), "Law of triviality".to_owned()]
+        );
+        let einstein = &pages["Albert Einstein"];
+        assert_eq!(einstein.extract, Some("Albert Einstein was...".to_owned()));
+        assert_eq!(einstein.length, 200000);
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "extracts|info|pageprops".to_owned()),
+                ("exintro".to_owned(), "".to_owned()),
+                ("explaintext".to_owned(), "".to_owned()),
+                ("exlimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                (
+                    "titles".to_owned(),
+                    "bikeshedding|Law of triviality|Albert Einstein|Nonexistent".to_owned()
+                ),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][8..],
+            [
+                ("continue".to_owned(), "||info|pageprops".to_owned()),
+                ("excontinue".to_owned(), "1".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_pages_chunks() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for id in [1, 2] {
+            wikipedia.client.response.lock().unwrap().push(format!(
+                "{{\"query\":{{\"pages\":{{\"{0}\":{{\"pageid\":{0},\"ns\":0,\"title\":\"{0}\"}}}}}}}}",
+                id
+            ));
+        }
+        let ids: Vec<u64> = (1..=51).collect();
+        let pages = wikipedia.get_pages_by_id(&ids).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[&2].requested, vec!["2".to_owned()]);
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(arguments[0][7].0, "pageids");
+        assert_eq!(arguments[0][7].1.split('|').count(), 50);
+        assert_eq!(arguments[1][7], ("pageids".to_owned(), "51".to_owned()));
+    }
+
+    #[test]
+    fn pages_exist() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"batchcomplete\":\"\",\"query\":{\"normalized\":[{\"from\":\"albert_einstein\",\"to\":\"Albert einstein\"}],\"interwiki\":[{\"title\":\"de:Berlin\",\"iw\":\"de\"}],\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nonexistent\",\"missing\":\"\"},\"-2\":{\"title\":\"Foo|bar\",\"invalidreason\":\"The requested page title contains invalid characters: \\\"|\\\".\",\"invalid\":\"\"},\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert einstein\",\"redirect\":\"\"}}}}".to_owned(),
+        );
+        let status = wikipedia
+            .pages_exist(&["albert_einstein", "Nonexistent", "Foo|bar", "de:Berlin"])
+            .unwrap();
+        assert_eq!(status.len(), 4);
+        assert_eq!(status["albert_einstein"], super::TitleStatus::Exists);
+        assert_eq!(status["Nonexistent"], super::TitleStatus::Missing);
+        assert_eq!(status["Foo|bar"], super::TitleStatus::Invalid);
+        assert_eq!(status["de:Berlin"], super::TitleStatus::Invalid);
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "info".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                (
+                    "titles".to_owned(),
+                    "\u{1f}albert_einstein\u{1f}Nonexistent\u{1f}Foo|bar\u{1f}de:Berlin".to_owned()
+                ),
+                ("continue".to_owned(), "".to_owned()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn get_summaries() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"excontinue\":1,\"continue\":\"||\"},\"query\":{\"redirects\":[{\"from\":\"Bikeshedding\",\"to\":\"Law of triviality\"}],\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nonexistent\",\"missing\":\"\"},\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\",\"extract\":\"The law of triviality is...\"},\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\"}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"batchcomplete\":\"\",\"query\":{\"redirects\":[{\"from\":\"Bikeshedding\",\"to\":\"Law of triviality\"}],\"pages\":{\"-1\":{\"ns\":0,\"title\":\"Nonexistent\",\"missing\":\"\"},\"4138548\":{\"pageid\":4138548,\"ns\":0,\"title\":\"Law of triviality\"},\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\",\"extract\":\"Albert Einstein was...\"}}}}".to_owned(),
+        );
+        let summaries = wikipedia
+            .get_summaries(&["Bikeshedding", "Albert Einstein", "Nonexistent"])
+            .unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries["Bikeshedding"], "The law of triviality is...");
+        assert_eq!(summaries["Albert Einstein"], "Albert Einstein was...");
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(
+            arguments[0][..4],
+            [
+                ("prop".to_owned(), "extracts".to_owned()),
+                ("exintro".to_owned(), "".to_owned()),
+                ("explaintext".to_owned(), "".to_owned()),
+                ("exlimit".to_owned(), "max".to_owned()),
+            ]
+        );
+        assert_eq!(arguments[1][9], ("excontinue".to_owned(), "1".to_owned()));
+    }
+
+    fn retry_client(failures: Vec<super::Error>) -> super::http::Retry<MockClient> {
+        let client = MockClient::default();
+        *client.failures.lock().unwrap() = failures;
+        client.response.lock().unwrap().push("ok".to_owned());
+        super::http::Retry::new(
+            client,
+            super::http::RetryPolicy {
+                max_attempts: 3,
+                base_delay: std::time::Duration::from_millis(1),
+                honor_retry_after: true,
+                max_delay: std::time::Duration::from_secs(1),
+            },
+        )
+    }
+
+    fn cached_client(
+        ttl: std::time::Duration,
+        max_entries: usize,
+    ) -> super::http::CachedClient<MockClient> {
+        let client = MockClient::default();
+        for i in 0..4 {
+            client.response.lock().unwrap().push(i.to_string());
+        }
+        super::http::CachedClient::new(client, ttl, max_entries)
+    }
+
+    #[test]
+    fn cached_client_lru() {
+        use super::http::HttpClient;
+        let client = cached_client(std::time::Duration::from_secs(60), 2);
+        let get = |x| client.get("url", vec![("q", x)].into_iter()).unwrap();
+        assert_eq!(get("a"), "0");
+        assert_eq!(get("b"), "1");
+        assert_eq!(get("a"), "0");
+        // evicts "b", the least recently used
+        assert_eq!(get("c"), "2");
+        assert_eq!(get("a"), "0");
+        assert_eq!(get("b"), "3");
+        assert_eq!(client.inner().arguments.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn cached_client_expired() {
+        use super::http::HttpClient;
+        let client = cached_client(std::time::Duration::ZERO, 10);
+        assert_eq!(client.get("url", std::iter::empty()).unwrap(), "0");
+        assert_eq!(client.get("url", std::iter::empty()).unwrap(), "1");
+    }
+
+    #[test]
+    fn cached_client_bypass() {
+        use super::http::HttpClient;
+        let client = cached_client(std::time::Duration::from_secs(60), 10);
+        let no_cache = [("Cache-Control", "no-cache")];
+        assert_eq!(client.get("url", std::iter::empty()).unwrap(), "0");
+        assert_eq!(
+            client
+                .get_with_headers("url", std::iter::empty(), &no_cache)
+                .unwrap(),
+            "1"
+        );
+        assert_eq!(
+            client
+                .get_with_headers("url", std::iter::empty(), &no_cache)
+                .unwrap(),
+            "2"
+        );
+        client.purge();
+        assert_eq!(client.get("url", std::iter::empty()).unwrap(), "3");
+        // POST requests are always sent
+        assert!(client.post("url", std::iter::empty()).is_err());
+    }
+
+    #[test]
+    fn cached_client_refresh() {
+        use super::http::HttpClient;
+        let client = cached_client(std::time::Duration::from_secs(60), 10);
+        let args = || vec![("q", "a")].into_iter();
+        assert_eq!(client.get("url", args()).unwrap(), "0");
+        assert_eq!(
+            client
+                .get_with_headers("url", args(), &[("cache-control", "no-cache")])
+                .unwrap(),
+            "1"
+        );
+        // the refresh replaced the stale response
+        assert_eq!(client.get("url", args()).unwrap(), "1");
+        assert_eq!(client.inner().arguments.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn cached_client_custom_cache() {
+        use super::http::{HttpClient, ResponseCache};
+        let cache = Arc::new(super::http::MemoryCache::new(10));
+        let client = MockClient::default();
+        client.response.lock().unwrap().push("0".to_owned());
+        let client = super::http::CachedClient::with_cache(
+            client,
+            std::time::Duration::from_secs(60),
+            cache.clone(),
+        );
+        assert_eq!(
+            client.get("url", vec![("q", "a")].into_iter()).unwrap(),
+            "0"
+        );
+        let key = "[\"url\",[[\"q\",\"a\"]],[]]";
+        assert_eq!(cache.get(key).unwrap().body, "0");
+        cache.put(
+            key,
+            super::http::CachedResponse {
+                body: "1".to_owned(),
+                etag: None,
+                last_modified: None,
+                expires: std::time::SystemTime::now() + std::time::Duration::from_secs(60),
+            },
+        );
+        assert_eq!(
+            client.get("url", vec![("q", "a")].into_iter()).unwrap(),
+            "1"
+        );
+    }
+
+    #[cfg(feature = "file-cache")]
+    #[test]
+    fn file_cache() {
+        use super::http::ResponseCache;
+        let directory =
+            std::env::temp_dir().join(format!("wikipedia-cache-{}", std::process::id()));
+        let cache = super::http::FileCache::new(&directory).unwrap();
+        let response = super::http::CachedResponse {
+            body: "{\"a\":\"b\nc\"}\n".to_owned(),
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: None,
+            expires: std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+        };
+        assert_eq!(cache.get("a"), None);
+        cache.put("a", response.clone());
+        assert_eq!(cache.get("a"), Some(response.clone()));
+        // survives the cache object
+        let cache = super::http::FileCache::new(&directory).unwrap();
+        assert_eq!(cache.get("a"), Some(response));
+        assert_eq!(cache.get("b"), None);
+        let foreign = directory.join("notes.txt");
+        std::fs::write(&foreign, "keep").unwrap();
+        std::fs::write(directory.join("0123456789abcdef.partial"), "").unwrap();
+        cache.clear();
+        assert_eq!(cache.get("a"), None);
+        // only the files of the cache are removed
+        let left: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|x| x.unwrap().file_name())
+            .collect();
+        assert_eq!(left, vec![std::ffi::OsString::from("notes.txt")]);
+        assert_eq!(std::fs::read_to_string(&foreign).unwrap(), "keep");
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    fn status(status: u16) -> super::Error {
+        super::Error::Http {
+            status,
+            body_snippet: String::new(),
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn retry_transient_failures() {
+        use super::http::HttpClient;
+        let client = retry_client(vec![
+            status(503),
+            super::Error::RateLimited {
+                retry_after: Some(std::time::Duration::from_millis(1)),
+            },
+        ]);
+        assert_eq!(
+            client.get("url", vec![("a", "b")].into_iter()).unwrap(),
+            "ok"
+        );
+        assert_eq!(
+            *client.inner().arguments.lock().unwrap(),
+            vec![vec![("a".to_owned(), "b".to_owned())]; 3]
+        );
+    }
+
+    #[test]
+    fn retry_headers() {
+        use super::http::HttpClient;
+        let client = retry_client(vec![status(503)]);
+        client
+            .get_with_headers(
+                "url",
+                Vec::new().into_iter(),
+                &[("Accept-Language", "sr-el")],
+            )
+            .unwrap();
+        assert_eq!(
+            *client.inner().headers.lock().unwrap(),
+            vec![vec![("Accept-Language".to_owned(), "sr-el".to_owned())]; 2]
+        );
+    }
+
+    #[test]
+    fn retry_exhausted() {
+        use super::http::HttpClient;
+        let client = retry_client(vec![status(502), super::Error::Timeout, status(500)]);
+        match client.get("url", Vec::new().into_iter()) {
+            Err(super::Error::RetriesExhausted { attempts, source }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*source, super::Error::Http { status: 500, .. }));
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(client.inner().arguments.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn retry_after_above_max_delay() {
+        use super::http::HttpClient;
+        let client = retry_client(vec![super::Error::RateLimited {
+            retry_after: Some(std::time::Duration::from_secs(86400)),
+        }]);
+        let start = std::time::Instant::now();
+        assert!(matches!(
+            client.get("url", Vec::new().into_iter()),
+            Err(super::Error::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert_eq!(client.inner().arguments.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn retry_permanent_failure() {
+        use super::http::HttpClient;
+        let client = retry_client(vec![status(404)]);
+        assert!(matches!(
+            client.get("url", Vec::new().into_iter()),
+            Err(super::Error::Http { status: 404, .. })
+        ));
+        let client = retry_client(vec![status(503)]);
+        assert!(client.post("url", Vec::new().into_iter()).is_err());
+        assert_eq!(client.inner().arguments.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn maxlag_retry() {
+        let wikipedia = Wikipedia::<MockClient> {
+            maxlag: Some(5),
+            maxlag_max_wait: std::time::Duration::from_millis(20),
+            ..Wikipedia::default()
+        };
+        let lagged = "{\"error\":{\"code\":\"maxlag\",\"info\":\"Waiting for 10.64.0.1: 7 seconds lagged.\",\"host\":\"10.64.0.1\",\"lag\":7}}";
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .extend([lagged.to_owned(), "{\"query\":{\"search\":[]}}".to_owned()]);
+        assert_eq!(wikipedia.search("hello").unwrap(), Vec::<String>::new());
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert!(arguments
+            .iter()
+            .all(|x| x.last() == Some(&("maxlag".to_owned(), "5".to_owned()))));
+    }
+
+    #[test]
+    fn maxlag_retry_after() {
+        let wikipedia = Wikipedia::<MockClient> {
+            maxlag: Some(5),
+            maxlag_max_wait: std::time::Duration::from_secs(10),
+            ..Wikipedia::default()
+        };
+        let lagged = "{\"error\":{\"code\":\"maxlag\",\"info\":\"Waiting for 10.64.0.1: 7 seconds lagged.\",\"host\":\"10.64.0.1\",\"lag\":7}}";
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .extend([lagged.to_owned(), "{\"query\":{\"search\":[]}}".to_owned()]);
+        wikipedia
+            .client
+            .response_headers
+            .lock()
+            .unwrap()
+            .push(vec![("retry-after".to_owned(), "1".to_owned())]);
+        let start = std::time::Instant::now();
+        assert_eq!(wikipedia.search("hello").unwrap(), Vec::<String>::new());
+        // waits as asked instead of the 5 seconds of `maxlag`
+        let elapsed = start.elapsed();
+        assert!(elapsed >= std::time::Duration::from_secs(1));
+        assert!(elapsed < std::time::Duration::from_secs(5));
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn maxlag_max_wait() {
+        let wikipedia = Wikipedia::<MockClient> {
+            maxlag: Some(5),
+            maxlag_max_wait: std::time::Duration::from_millis(5),
+            ..Wikipedia::default()
+        };
+        let lagged = "{\"error\":{\"code\":\"maxlag\",\"info\":\"Waiting for 10.64.0.1: 7 seconds lagged.\"}}";
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .extend([lagged.to_owned(), lagged.to_owned()]);
+        match wikipedia.search("hello") {
+            Err(super::Error::Api { code, .. }) => assert_eq!(code, "maxlag"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rate_limiter() {
+        let wikipedia = Wikipedia::<MockClient> {
+            rate_limiter: Some(
+                super::http::RateLimiter::new(2, std::time::Duration::from_millis(100)).unwrap(),
+            ),
+            ..Wikipedia::default()
+        };
+        for _ in 0..4 {
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"query\":{\"search\":[]}}".to_owned());
+        }
+        let start = std::time::Instant::now();
+        for _ in 0..4 {
+            wikipedia.search("hello").unwrap();
+        }
+        // two without waiting, then one every 50ms
+        assert!(start.elapsed() >= std::time::Duration::from_millis(95));
+        assert!(super::http::RateLimiter::new(0, std::time::Duration::from_secs(1)).is_err());
+        assert!(super::http::RateLimiter::new(1, std::time::Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn long_query_post() {
+        let wikipedia = Wikipedia::<MockClient> {
+            post_threshold: 100,
+            ..Wikipedia::default()
+        };
+        for _ in 0..2 {
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"query\":{\"search\":[]}}".to_owned());
+        }
+        wikipedia.search("short").unwrap();
+        wikipedia.search(&"long query ".repeat(10)).unwrap();
+        assert_eq!(
+            *wikipedia.client.method.lock().unwrap(),
+            vec!["GET", "POST"]
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[1][3],
+            ("srsearch".to_owned(), "long query ".repeat(10))
+        );
+    }
+
+    #[test]
+    fn long_query_post_headers() {
+        let wikipedia = Wikipedia::<MockClient> {
+            post_threshold: 100,
+            ..Wikipedia::default()
+        };
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[]}}".to_owned());
+        let query = "long query ".repeat(10);
+        let headers = [("Accept-Language", "sr-el")];
+        wikipedia
+            .request(false, vec![("srsearch", &*query)], None, &headers)
+            .unwrap();
+        assert_eq!(*wikipedia.client.method.lock().unwrap(), vec!["POST"]);
+        assert_eq!(
+            *wikipedia.client.headers.lock().unwrap(),
+            vec![vec![("Accept-Language".to_owned(), "sr-el".to_owned())]]
+        );
+
+        let file = super::http::FilePart {
+            name: "file",
+            filename: "a.txt",
+            content: b"a",
+        };
+        match wikipedia.request(true, Vec::new(), Some(&file), &headers) {
+            Err(super::Error::InvalidParameter(x)) => assert_eq!(x, "headers"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(wikipedia.client.method.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn boxed_client() {
+        let recording = MockClient::default();
+        recording
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"search\":[{\"title\":\"Keyboard\"}]}}".to_owned());
+        // clients of different types behind the same type
+        let mut clients = vec![
+            super::http::BoxedClient::new(MockClient::default()),
+            super::http::BoxedClient::new(super::http::Retry::new(
+                recording,
+                super::http::RetryPolicy {
+                    max_attempts: 1,
+                    ..Default::default()
+                },
+            )),
+        ];
+        let client = clients.pop().unwrap();
+        let mut wikipedia = Wikipedia::new(client);
+        super::http::HttpClient::user_agent(&mut wikipedia.client, "test".to_owned());
+        assert_eq!(
+            wikipedia.search("keyboard").unwrap(),
+            vec!["Keyboard".to_owned()]
+        );
+        // the mock fails once it runs out of responses
+        assert!(wikipedia.search("keyboard").is_err());
+    }
+
+    fn api_error(
+        response: &str,
+        call: impl Fn(&Wikipedia<MockClient>) -> super::Result<String>,
+    ) -> (String, String) {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push(response.to_owned());
+        match call(&wikipedia) {
+            Err(super::Error::Api { code, info }) => (code, info),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn api_errors() {
+        assert_eq!(
+            api_error(
+                "{\"error\":{\"code\":\"invalidtitle\",\"info\":\"Bad title \\\"[[]]\\\".\",\"*\":\"See https://en.wikipedia.org/w/api.php for API usage.\"}}",
+                |w| w.page_from_title("[[]]".to_owned()).get_content()
+            ),
+            ("invalidtitle".to_owned(), "Bad title \"[[]]\".".to_owned())
+        );
+        assert_eq!(
+            api_error(
+                "{\"error\":{\"code\":\"missingtitle\",\"info\":\"The page you specified doesn't exist.\"}}",
+                |w| w.render_wikitext("{{PAGENAME}}", Some("Nothing"))
+            )
+            .0,
+            "missingtitle"
+        );
+    }
+
+    #[test]
+    fn api_rate_limited() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"error\":{\"code\":\"ratelimited\",\"info\":\"You've exceeded your rate limit. Please wait some time and try again.\"}}".to_owned());
+        assert!(matches!(
+            wikipedia.search("hello"),
+            Err(super::Error::RateLimited { retry_after: None })
+        ));
+    }
+
+    #[test]
+    fn api_warnings() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let collected = warnings.clone();
+        let wikipedia = Wikipedia::<MockClient> {
+            warning_hook: Some(super::WarningHook::new(move |w| {
+                collected.lock().unwrap().push(w.clone())
+            })),
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push("{\"warnings\":{\"main\":{\"*\":\"Unrecognized parameter: foo.\"},\"search\":{\"warnings\":\"srlimit may not be over 500.\"}},\"query\":{\"search\":[]}}".to_owned());
+        assert!(wikipedia.search("hello").unwrap().is_empty());
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                super::ApiWarning {
+                    module: "main".to_owned(),
+                    text: "Unrecognized parameter: foo.".to_owned(),
+                },
+                super::ApiWarning {
+                    module: "search".to_owned(),
+                    text: "srlimit may not be over 500.".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn login() {
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let collected = logged.clone();
+        let wikipedia = Wikipedia::<MockClient> {
+            request_hook: Some(super::RequestHook::new(move |r| {
+                collected
+                    .lock()
+                    .unwrap()
+                    .extend(r.params.iter().map(|x| (x.0.to_owned(), x.1.to_owned())));
+            })),
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"logintoken\":\"abc+\\\\\"}}}".to_owned(),
+            "{\"login\":{\"result\":\"Success\",\"lguserid\":1,\"lgusername\":\"Foo\"}}".to_owned(),
+            "{\"query\":{\"userinfo\":{\"id\":1,\"name\":\"Foo\"}}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"def+\\\\\"}}}".to_owned(),
+            "{}".to_owned(),
+            "{\"query\":{\"userinfo\":{\"id\":0,\"name\":\"127.0.0.1\",\"anon\":\"\"}}}".to_owned(),
+        ]);
+        wikipedia.login("Foo@bot", "secret").unwrap();
+        assert!(wikipedia.is_logged_in().unwrap());
+        wikipedia.logout().unwrap();
+        assert!(!wikipedia.is_logged_in().unwrap());
+        assert_eq!(
+            *wikipedia.client.method.lock().unwrap(),
+            vec!["GET", "POST", "GET", "GET", "POST", "GET"]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[0][1], ("type".to_owned(), "login".to_owned()));
+        assert_eq!(
+            arguments[1][..3],
+            [
+                ("lgname".to_owned(), "Foo@bot".to_owned()),
+                ("lgpassword".to_owned(), "secret".to_owned()),
+                ("lgtoken".to_owned(), "abc+\\".to_owned()),
+            ]
+        );
+        assert_eq!(arguments[4][0], ("token".to_owned(), "def+\\".to_owned()));
+        // the request hook doesn't see the credentials
+        let logged = logged.lock().unwrap();
+        for (name, value) in logged.iter() {
+            if ["lgpassword", "lgtoken", "token"].contains(&&**name) {
+                assert_eq!(value, "***");
+            }
+        }
+        assert!(logged.contains(&("lgpassword".to_owned(), "***".to_owned())));
+        assert!(logged.contains(&("lgname".to_owned(), "Foo@bot".to_owned())));
+    }
+
+    #[test]
+    fn whoami() {
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.set_oauth_token("token".to_owned());
+        assert_eq!(wikipedia.client.bearer_token, Some("token".to_owned()));
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"userinfo\":{\"id\":12,\"name\":\"Foo\",\"groups\":[\"*\",\"user\"],\"rights\":[\"read\",\"edit\",\"highlimits\"]}}}".to_owned(),
+        );
+        assert_eq!(
+            wikipedia.whoami().unwrap(),
+            super::UserInfo {
+                id: 12,
+                name: "Foo".to_owned(),
+                anonymous: false,
+                groups: vec!["*".to_owned(), "user".to_owned()],
+                rights: vec![
+                    "read".to_owned(),
+                    "edit".to_owned(),
+                    "highlimits".to_owned()
+                ],
+            }
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][1],
+            ("uiprop".to_owned(), "groups|rights".to_owned())
+        );
+    }
+
+    #[test]
+    fn page_edit() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"old+\\\\\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"badtoken\",\"info\":\"Invalid CSRF token.\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"new+\\\\\"}}}".to_owned(),
+            "{\"edit\":{\"result\":\"Success\",\"pageid\":1,\"title\":\"Sandbox\",\"contentmodel\":\"wikitext\",\"oldrevid\":10,\"newrevid\":11,\"newtimestamp\":\"2024-01-01T00:00:00Z\"}}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("Sandbox".to_owned());
+        let options = super::EditOptions {
+            bot: true,
+            section: Some("new".to_owned()),
+            ..super::EditOptions::default()
+        };
+        assert_eq!(
+            page.edit("Hello", "Greeting", &options).unwrap(),
+            super::EditResult {
+                pageid: 1,
+                title: "Sandbox".to_owned(),
+                old_revid: Some(10),
+                new_revid: Some(11),
+                new_timestamp: Some("2024-01-01T00:00:00Z".to_owned()),
+                no_change: false,
+            }
+        );
+        assert_eq!(
+            *wikipedia.client.method.lock().unwrap(),
+            vec!["GET", "POST", "GET", "POST"]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        let params: Vec<_> = arguments[3].iter().map(|x| (&*x.0, &*x.1)).collect();
+        assert_eq!(
+            params,
+            vec![
+                ("title", "Sandbox"),
+                ("text", "Hello"),
+                ("summary", "Greeting"),
+                ("bot", "1"),
+                ("section", "new"),
+                ("format", "json"),
+                ("action", "edit"),
+                ("token", "new+\\"),
+            ]
+        );
+        assert_eq!(arguments[1].last().unwrap().1, "old+\\");
+    }
+
+    #[test]
+    fn page_edit_errors() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"a\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"editconflict\",\"info\":\"Edit conflict.\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"a\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"badtoken\",\"info\":\"Invalid CSRF token.\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"b\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"badtoken\",\"info\":\"Invalid CSRF token.\"}}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        let options = super::EditOptions {
+            base_timestamp: Some("2024-01-01T00:00:00Z".to_owned()),
+            ..super::EditOptions::default()
+        };
+        assert!(matches!(
+            page.edit("Hello", "", &options),
+            Err(super::Error::EditConflict)
+        ));
+        assert!(matches!(
+            page.edit("Hello", "", &options),
+            Err(super::Error::BadToken)
+        ));
+        let options = super::EditOptions {
+            create_only: true,
+            no_create: true,
+            ..super::EditOptions::default()
+        };
+        assert!(matches!(
+            page.edit("Hello", "", &options),
+            Err(super::Error::InvalidParameter(_))
+        ));
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[1][0], ("pageid".to_owned(), "1".to_owned()));
+        assert_eq!(
+            arguments[1][3],
+            (
+                "basetimestamp".to_owned(),
+                "2024-01-01T00:00:00Z".to_owned()
+            )
+        );
+        assert_eq!(arguments.len(), 6);
+    }
+
+    #[test]
+    fn page_edit_cached_token() {
+        let client = super::http::CachedClient::new(
+            MockClient::default(),
+            std::time::Duration::from_secs(60),
+            10,
+        );
+        client.inner().response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"a\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"badtoken\",\"info\":\"Invalid CSRF token.\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"b\"}}}".to_owned(),
+            "{\"edit\":{\"result\":\"Success\",\"pageid\":1,\"title\":\"Foo\",\"oldrevid\":1,\"newrevid\":2,\"newtimestamp\":\"2024-01-01T00:00:00Z\"}}".to_owned(),
+        ]);
+        let wikipedia = Wikipedia::new(client);
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        page.edit("Hello", "", &super::EditOptions::default())
+            .unwrap();
+        let inner = wikipedia.client.inner();
+        // the refreshed token isn't the cached one
+        assert_eq!(
+            *inner.method.lock().unwrap(),
+            vec!["GET", "POST", "GET", "POST"]
+        );
+        assert!(inner.arguments.lock().unwrap()[3].contains(&("token".to_owned(), "b".to_owned())));
+        assert_eq!(
+            inner.headers.lock().unwrap()[2],
+            vec![("Cache-Control".to_owned(), "no-cache".to_owned())]
+        );
+    }
+
+    #[test]
+    fn page_add_section() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"edit\":{\"new\":\"\",\"result\":\"Success\",\"pageid\":2,\"title\":\"User talk:Foo\",\"oldrevid\":20,\"newrevid\":21,\"newtimestamp\":\"2024-01-01T00:00:00Z\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"error\":{\"code\":\"assertuserfailed\",\"info\":\"You are no longer logged in, so the action could not be completed.\"}}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("User talk:Foo".to_owned());
+        assert_eq!(
+            page.add_section("Notice", "Hello ~~~~", "New notice")
+                .unwrap()
+                .new_revid,
+            Some(21)
+        );
+        assert!(matches!(
+            page.add_section("Notice", "Hello ~~~~", "New notice"),
+            Err(super::Error::NotLoggedIn)
+        ));
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[1][3..6],
+            [
+                ("section".to_owned(), "new".to_owned()),
+                ("sectiontitle".to_owned(), "Notice".to_owned()),
+                ("assert".to_owned(), "user".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_purge() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"batchcomplete\":\"\",\"purge\":[{\"ns\":0,\"title\":\"Main Page\",\"purged\":\"\",\"linkupdate\":\"\"}]}".to_owned(),
+            "{\"batchcomplete\":\"\",\"purge\":[{\"ns\":0,\"title\":\"Main Page\"}],\"warnings\":{\"purge\":{\"warnings\":\"You've exceeded your rate limit.\"}}}".to_owned(),
+            "{\"batchcomplete\":\"\",\"purge\":[{\"pageid\":123,\"missing\":\"\"}]}".to_owned(),
+            "{\"batchcomplete\":\"\",\"purge\":[{\"ns\":0,\"title\":\"Nothing\",\"missing\":\"\"}]}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("Main Page".to_owned());
+        page.purge(true).unwrap();
+        assert!(matches!(
+            page.purge(false),
+            Err(super::Error::RateLimited { retry_after: None })
+        ));
+        assert!(matches!(
+            wikipedia.page_from_pageid("123".to_owned()).purge(false),
+            Err(super::Error::PageNotFound { .. })
+        ));
+        assert!(matches!(
+            wikipedia.page_from_title("Nothing".to_owned()).purge(false),
+            Err(super::Error::PageNotFound { .. })
+        ));
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][..2],
+            [
+                ("titles".to_owned(), "Main Page".to_owned()),
+                ("forcelinkupdate".to_owned(), "1".to_owned()),
+            ]
+        );
+        assert_eq!(wikipedia.client.method.lock().unwrap()[0], "POST");
+    }
+
+    #[test]
+    fn purge_pages() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"batchcomplete\":\"\",\"normalized\":[{\"from\":\"main Page\",\"to\":\"Main Page\"}],\"purge\":[{\"ns\":0,\"title\":\"Main Page\",\"purged\":\"\"},{\"ns\":0,\"title\":\"Nothing\",\"missing\":\"\"},{\"title\":\"A|B\",\"invalidreason\":\"The requested page title contains invalid characters: \\\"|\\\".\",\"invalid\":\"\"},{\"ns\":0,\"title\":\"Busy\"}]}".to_owned(),
+        );
+        let status = wikipedia
+            .purge_pages(&["main Page", "Nothing", "A|B", "Busy"])
+            .unwrap();
+        assert_eq!(status["main Page"], super::PurgeStatus::Purged);
+        assert_eq!(status["Nothing"], super::PurgeStatus::Missing);
+        assert_eq!(status["A|B"], super::PurgeStatus::Invalid);
+        assert_eq!(status["Busy"], super::PurgeStatus::RateLimited);
+    }
+
+    #[test]
+    fn watchlist() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"batchcomplete\":\"\",\"query\":{\"watchlist\":[{\"type\":\"edit\",\"ns\":0,\"title\":\"Physics\",\"pageid\":22939,\"revid\":1002,\"old_revid\":1001,\"user\":\"Someone\",\"oldlen\":100,\"newlen\":120,\"timestamp\":\"2024-01-01T00:00:01Z\",\"comment\":\"typo\"}]}}".to_owned(),
+            "{\"error\":{\"code\":\"notloggedin\",\"info\":\"You must be logged in to have a watchlist.\"}}".to_owned(),
+        ]);
+        let changes = wikipedia
+            .watchlist(
+                super::Limit::Count(50),
+                super::RecentChangesOptions::default(),
+            )
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0.title, "Physics");
+        assert_eq!(changes[0].0.old_revid, 1001);
+        assert!(matches!(
+            wikipedia.watchlist(super::Limit::Max, super::RecentChangesOptions::default()),
+            Err(super::Error::NotLoggedIn)
+        ));
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0][..3],
+            [
+                ("list".to_owned(), "watchlist".to_owned()),
+                (
+                    "wlprop".to_owned(),
+                    "title|ids|sizes|timestamp|user|comment".to_owned()
+                ),
+                ("wllimit".to_owned(), "50".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_watch() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"watchtoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"batchcomplete\":\"\",\"watch\":[{\"ns\":0,\"title\":\"Physics\",\"watched\":\"\"}]}".to_owned(),
+            "{\"query\":{\"tokens\":{\"watchtoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"batchcomplete\":\"\",\"watch\":[{\"ns\":0,\"title\":\"Physics\",\"unwatched\":\"\"}]}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("Physics".to_owned());
+        page.watch().unwrap();
+        page.unwatch().unwrap();
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[0][1], ("type".to_owned(), "watch".to_owned()));
+        assert_eq!(arguments[1].len(), 4);
+        assert_eq!(arguments[3][1], ("unwatch".to_owned(), "1".to_owned()));
+        assert_eq!(arguments[3][4], ("token".to_owned(), "+\\".to_owned()));
+    }
+
+    #[test]
+    fn upload() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"upload\":{\"result\":\"Warning\",\"warnings\":{\"exists\":\"Logo.png\",\"duplicate\":[\"Old_logo.png\"],\"badfilename\":\"Logo.png\"},\"filekey\":\"abc.png\"}}".to_owned(),
+            "{\"query\":{\"tokens\":{\"csrftoken\":\"+\\\\\"}}}".to_owned(),
+            "{\"upload\":{\"result\":\"Success\",\"filename\":\"Logo.png\",\"imageinfo\":{\"url\":\"https://upload.example.org/Logo.png\"}}}".to_owned(),
+        ]);
+        let upload = |ignore| {
+            let args = ("Logo.png", &b"\x89PNG"[..], "New logo", "== Summary ==");
+            if ignore {
+                wikipedia.upload_ignoring_warnings(args.0, args.1, args.2, args.3)
+            } else {
+                wikipedia.upload(args.0, args.1, args.2, args.3)
+            }
+        };
+        assert_eq!(
+            upload(false).unwrap(),
+            super::UploadResult::Warnings(vec![
+                super::UploadWarning::Other {
+                    code: "badfilename".to_owned(),
+                    value: "\"Logo.png\"".to_owned(),
+                },
+                super::UploadWarning::Duplicate(vec!["Old_logo.png".to_owned()]),
+                super::UploadWarning::Exists("Logo.png".to_owned()),
+            ])
+        );
+        assert_eq!(
+            upload(true).unwrap(),
+            super::UploadResult::Success {
+                filename: "Logo.png".to_owned(),
+                url: Some("https://upload.example.org/Logo.png".to_owned()),
+            }
+        );
+        assert_eq!(
+            *wikipedia.client.files.lock().unwrap(),
+            vec![
+                (
+                    "file".to_owned(),
+                    "Logo.png".to_owned(),
+                    b"\x89PNG".to_vec()
+                );
+                2
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[1].len(), 6);
+        assert_eq!(
+            arguments[3][3],
+            ("ignorewarnings".to_owned(), "1".to_owned())
+        );
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn cookie_jar() {
+        let mut jar = super::http::default::CookieJar::default();
+        let url = |x: &str| x.parse().unwrap();
+        let api = url("https://en.wikipedia.org/w/api.php");
+        jar.set(&api, "enwikiSession=abc; path=/; secure; HttpOnly");
+        jar.set(&api, "centralauth_User=Foo; domain=.wikipedia.org; path=/");
+        jar.set(&api, "other=1; domain=wikimedia.org; path=/");
+        jar.set(&api, "api=1");
+        assert_eq!(
+            jar.header(&api).unwrap(),
+            "enwikiSession=abc; centralauth_User=Foo; api=1"
+        );
+        assert_eq!(
+            jar.header(&url("https://de.wikipedia.org/w/api.php"))
+                .unwrap(),
+            "centralauth_User=Foo"
+        );
+        // secure cookies need https, other paths don't see `api`
+        assert_eq!(
+            jar.header(&url("http://en.wikipedia.org/wiki/Foo"))
+                .unwrap(),
+            "centralauth_User=Foo"
+        );
+        for other in [
+            "https://wikimedia.org/api/rest_v1/",
+            "https://upload.wikimedia.org/file",
+            "https://wikipedia.org.example.com/w/api.php",
+            "https://my.wiki/w/api.php",
+        ] {
+            assert_eq!(jar.header(&url(other)), None, "{}", other);
+        }
+        jar.set(&api, "enwikiSession=deleted; path=/; Max-Age=0");
+        assert_eq!(jar.header(&api).unwrap(), "centralauth_User=Foo; api=1");
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn multipart_body() {
+        let file = super::http::FilePart {
+            name: "file",
+            filename: "a\"b.txt",
+            content: b"wikipedia-rs-boundary",
+        };
+        let (boundary, body) = super::http::default::multipart(&[("action", "upload")], &file);
+        assert_eq!(boundary, "wikipedia-rs-boundary-1");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--wikipedia-rs-boundary-1\r\nContent-Disposition: form-data; name=\"action\"\r\n\r\nupload\r\n--wikipedia-rs-boundary-1\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a%22b.txt\"\r\nContent-Type: application/octet-stream\r\n\r\nwikipedia-rs-boundary\r\n--wikipedia-rs-boundary-1--\r\n"
+        );
+    }
+
+    #[test]
+    fn login_failed() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"tokens\":{\"logintoken\":\"abc+\\\\\"}}}".to_owned(),
+            "{\"login\":{\"result\":\"Failed\",\"reason\":\"Incorrect username or password entered. Please try again.\"}}".to_owned(),
+        ]);
+        match wikipedia.login("Foo@bot", "wrong") {
+            Err(super::Error::LoginFailed(reason)) => {
+                assert_eq!(
+                    reason,
+                    "Incorrect username or password entered. Please try again."
+                )
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    fn request_hook() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let collected = requests.clone();
+        let wikipedia = Wikipedia::<MockClient> {
+            request_hook: Some(super::RequestHook::new(move |r| {
+                let list = r.params.iter().find(|x| x.0 == "list").map(|x| x.1);
+                collected.lock().unwrap().push((
+                    r.url.to_owned(),
+                    r.post,
+                    list.map(|x| x.to_owned()),
+                    r.bytes,
+                    r.error.map(|x| x.to_string()),
+                ));
+            })),
+            ..Wikipedia::default()
+        };
+        wikipedia
+            .client
+            .failures
+            .lock()
+            .unwrap()
+            .push(super::Error::Timeout);
+        assert!(wikipedia.search("hello").is_err());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"plcontinue\":\"1|0|B\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"links\":[{\"title\":\"A\"}]}}}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"1\":{\"links\":[{\"title\":\"B\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        assert_eq!(page.get_links().unwrap().count(), 2);
+        let url = "https://en.wikipedia.org/w/api.php".to_owned();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                (
+                    url.clone(),
+                    false,
+                    Some("search".to_owned()),
+                    None,
+                    Some("Timeout".to_owned()),
+                ),
+                (url.clone(), false, None, Some(101), None),
+                (url, false, None, Some(51), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_revisions() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"rvcontinue\":\"20240101|2\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"revisions\":[{\"revid\":3,\"parentid\":2,\"user\":\"Foo\",\"timestamp\":\"2024-01-02T00:00:00Z\",\"size\":120,\"comment\":\"typo\"}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"revisions\":[{\"revid\":2,\"parentid\":0,\"userhidden\":\"\",\"timestamp\":\"2024-01-01T00:00:00Z\",\"size\":100,\"comment\":\"\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        assert_eq!(
+            page.get_revisions(600).unwrap(),
+            vec![
+                super::Revision {
+                    revid: 3,
+                    parentid: 2,
+                    timestamp: "2024-01-02T00:00:00Z".to_owned(),
+                    user: "Foo".to_owned(),
+                    comment: "typo".to_owned(),
+                    size: 120,
+                },
+                super::Revision {
+                    revid: 2,
+                    parentid: 0,
+                    timestamp: "2024-01-01T00:00:00Z".to_owned(),
+                    user: "".to_owned(),
+                    comment: "".to_owned(),
+                    size: 100,
+                },
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("prop".to_owned(), "revisions".to_owned()),
+                    (
+                        "rvprop".to_owned(),
+                        "ids|timestamp|user|comment|size".to_owned()
+                    ),
+                    ("rvlimit".to_owned(), "500".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("pageids".to_owned(), "1".to_owned()),
+                    ("continue".to_owned(), "".to_owned()),
+                ],
+                vec![
+                    ("prop".to_owned(), "revisions".to_owned()),
+                    (
+                        "rvprop".to_owned(),
+                        "ids|timestamp|user|comment|size".to_owned()
+                    ),
+                    ("rvlimit".to_owned(), "500".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("pageids".to_owned(), "1".to_owned()),
+                    ("continue".to_owned(), "||".to_owned()),
+                    ("rvcontinue".to_owned(), "20240101|2".to_owned()),
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn page_revision_content() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Hello world\",\"revisions\":[{\"slots\":{\"main\":{\"*\":\"old text\"}}}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"badrevids\":{\"999\":{\"revid\":999,\"missing\":\"\"}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"2\":{\"pageid\":2,\"title\":\"Other\",\"revisions\":[{\"slots\":{\"main\":{\"*\":\"other\"}}}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Hello_world".to_owned());
+        assert_eq!(page.get_revision_content(5).unwrap(), "old text".to_owned());
+        assert!(matches!(
+            page.get_revision_content(999),
+            Err(super::Error::BadRevisionId(999))
+        ));
+        assert!(matches!(
+            page.get_revision_content(6),
+            Err(super::Error::BadRevisionId(6))
+        ));
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "revisions".to_owned()),
+                ("rvprop".to_owned(), "content".to_owned()),
+                ("rvslots".to_owned(), "main".to_owned()),
+                ("revids".to_owned(), "5".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_revision_html() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"parse\":{\"title\":\"Hello\",\"pageid\":1,\"revid\":5,\"text\":{\"*\":\"<p>old</p>\"}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"error\":{\"code\":\"nosuchrevid\",\"info\":\"There is no revision with ID 999.\"}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        assert_eq!(page.get_revision_html(5).unwrap(), "<p>old</p>".to_owned());
+        assert!(matches!(
+            page.get_revision_html(999),
+            Err(super::Error::BadRevisionId(999))
+        ));
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "text".to_owned()),
+                ("oldid".to_owned(), "5".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "parse".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn page_summary() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
+        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
+        let summary = page.get_summary().unwrap();
+        assert_eq!(summary, "hello".to_owned());
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "extracts".to_owned()),
+                ("explaintext".to_owned(), "".to_owned()),
+                ("exintro".to_owned(), "".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                (
+                    "titles".to_owned(),
+                    "Parkinson\'s law of triviality".to_owned()
+                )
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_summary_limits() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for _ in 0..2 {
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello.\"}}}}".to_owned());
+        }
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(page.get_summary_sentences(2).unwrap(), "hello.".to_owned());
+        assert_eq!(page.get_summary_chars(100).unwrap(), "hello.".to_owned());
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("prop".to_owned(), "extracts".to_owned()),
+                    ("explaintext".to_owned(), "".to_owned()),
+                    ("exintro".to_owned(), "".to_owned()),
+                    ("exsentences".to_owned(), "2".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned())
+                ],
+                vec![
+                    ("prop".to_owned(), "extracts".to_owned()),
+                    ("explaintext".to_owned(), "".to_owned()),
+                    ("exintro".to_owned(), "".to_owned()),
+                    ("exchars".to_owned(), "100".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned())
+                ]
+            ]
+        );
+        assert!(matches!(
+            page.get_summary_sentences(11),
+            Err(super::Error::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            page.get_summary_chars(1201),
+            Err(super::Error::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn page_redirect_summary() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"redirects\":[{\"to\":\"hello world\"}]}}".to_owned());
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
+        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
+        let summary = page.get_summary().unwrap();
+        assert_eq!(summary, "hello".to_owned());
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec![
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("prop".to_owned(), "extracts".to_owned()),
+                    ("explaintext".to_owned(), "".to_owned()),
+                    ("exintro".to_owned(), "".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    (
+                        "titles".to_owned(),
+                        "Parkinson\'s law of triviality".to_owned()
+                    )
+                ],
+                vec![
+                    ("prop".to_owned(), "extracts".to_owned()),
+                    ("explaintext".to_owned(), "".to_owned()),
+                    ("exintro".to_owned(), "".to_owned()),
+                    ("redirects".to_owned(), "".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "hello world".to_owned())
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn page_images_detailed() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"gimcontinue\":\"736|B.png\",\"continue\":\"gimcontinue||\"},\"query\":{\"pages\":{\"-1\":{\"ns\":6,\"title\":\"File:A.jpg\",\"missing\":\"\",\"known\":\"\",\"imagerepository\":\"shared\",\"imageinfo\":[{\"size\":52000,\"width\":800,\"height\":600,\"url\":\"https://upload.wikimedia.org/a.jpg\",\"descriptionurl\":\"https://commons.wikimedia.org/wiki/File:A.jpg\",\"mime\":\"image/jpeg\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"-1\":{\"ns\":6,\"title\":\"File:B.png\",\"imageinfo\":[{\"size\":1300,\"width\":32,\"height\":16,\"url\":\"https://upload.wikimedia.org/b.png\",\"descriptionurl\":\"https://commons.wikimedia.org/wiki/File:B.png\",\"mime\":\"image/png\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        let images = page.get_images_detailed().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            images[0],
+            iter::DetailedImage {
+                url: "https://upload.wikimedia.org/a.jpg".to_owned(),
+                title: "File:A.jpg".to_owned(),
+                description_url: "https://commons.wikimedia.org/wiki/File:A.jpg".to_owned(),
+                mime: "image/jpeg".to_owned(),
+                width: 800,
+                height: 600,
+                size: 52000,
+            }
+        );
+        assert_eq!(
+            images
+                .iter()
+                .map(|x| (&*x.mime, x.width, x.height))
+                .collect::<Vec<_>>(),
+            vec![("image/jpeg", 800, 600), ("image/png", 32, 16)]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0][3],
+            ("iiprop".to_owned(), "url|mime|size".to_owned())
+        );
+        assert_eq!(
+            arguments[1][8],
+            ("gimcontinue".to_owned(), "736|B.png".to_owned())
+        );
+    }
+
+    #[test]
+    fn page_images() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"title\":\"Image 1\", \"imageinfo\":[{\"url\": \"http://example.com/image1.jpg\", \"descriptionurl\": \"http://example.com/image1.jpg.html\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"a\":{\"title\":\"Image 2\", \"imageinfo\":[{\"url\": \"http://example.com/image2.jpg\", \"descriptionurl\": \"http://example.com/image2.jpg.html\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
+        let images = page.get_images().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            images,
+            vec![
+                iter::Image {
+                    url: "http://example.com/image1.jpg".to_owned(),
+                    title: "Image 1".to_owned(),
+                    description_url: "http://example.com/image1.jpg.html".to_owned(),
+                },
+                iter::Image {
+                    url: "http://example.com/image2.jpg".to_owned(),
+                    title: "Image 2".to_owned(),
+                    description_url: "http://example.com/image2.jpg.html".to_owned(),
+                }
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec![
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+            ]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![
+                vec![
+                    ("generator".to_owned(), "images".to_owned()),
+                    ("gimlimit".to_owned(), "max".to_owned()),
+                    ("prop".to_owned(), "imageinfo".to_owned()),
+                    ("iiprop".to_owned(), "url".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    (
+                        "titles".to_owned(),
+                        "Parkinson\'s law of triviality".to_owned()
+                    ),
+                    ("continue".to_owned(), "".to_owned())
+                ],
+                vec![
+                    ("generator".to_owned(), "images".to_owned()),
+                    ("gimlimit".to_owned(), "max".to_owned()),
+                    ("prop".to_owned(), "imageinfo".to_owned()),
+                    ("iiprop".to_owned(), "url".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    (
+                        "titles".to_owned(),
+                        "Parkinson\'s law of triviality".to_owned()
+                    ),
+                    ("lol".to_owned(), "1".to_owned())
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn page_coordinates() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"coordinates\":[{\"lat\": 2.1, \"lon\":-1.3}]}}}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_title("World".to_owned());
+        let coordinates = page.get_coordinates().unwrap().unwrap();
+        assert_eq!(coordinates, (2.1, -1.3));
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "coordinates".to_owned()),
+                ("colimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "World".to_owned())
+            ]]
+        );
+    }
+
+    #[test]
+    fn page_all_coordinates() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"continue\":{\"cocontinue\":\"1|5\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"coordinates\":[{\"lat\":25.197,\"lon\":55.274,\"primary\":\"\",\"globe\":\"earth\",\"type\":\"landmark\",\"dim\":1000},{\"lat\":40.748,\"lon\":-73.986,\"globe\":\"earth\",\"type\":\"landmark\",\"name\":\"Empire State Building\"}]}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"coordinates\":[{\"lat\":-14.6,\"lon\":175.5,\"globe\":\"mars\",\"name\":\"Gusev\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("List of tallest buildings".to_owned());
+        let coordinates = page.get_all_coordinates().unwrap();
+        assert_eq!(
+            coordinates,
+            vec![
+                super::Coordinate {
+                    lat: 25.197,
+                    lon: 55.274,
+                    primary: true,
+                    kind: Some("landmark".to_owned()),
+                    name: None,
+                    dim: Some(1000),
+                    globe: "earth".to_owned(),
+                },
+                super::Coordinate {
+                    lat: 40.748,
+                    lon: -73.986,
+                    primary: false,
+                    kind: Some("landmark".to_owned()),
+                    name: Some("Empire State Building".to_owned()),
+                    dim: None,
+                    globe: "earth".to_owned(),
+                },
+                super::Coordinate {
+                    lat: -14.6,
+                    lon: 175.5,
+                    primary: false,
+                    kind: None,
+                    name: Some("Gusev".to_owned()),
+                    dim: None,
+                    globe: "mars".to_owned(),
+                },
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "coordinates".to_owned()),
+                ("coprop".to_owned(), "type|name|dim|globe".to_owned()),
+                ("coprimary".to_owned(), "all".to_owned()),
+                ("colimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "List of tallest buildings".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(arguments[1][8], ("cocontinue".to_owned(), "1|5".to_owned()));
+    }
+
+    #[test]
+    fn page_all_coordinates_none() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"1\":{\"pageid\":1}}}}".to_owned());
+        let page = wikipedia.page_from_title("Bikeshedding".to_owned());
+        assert_eq!(page.get_all_coordinates().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn page_thumbnail() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"thumbnail\":{\"source\":\"https://upload.wikimedia.org/hello.jpg\",\"width\":200,\"height\":150},\"pageimage\":\"Hello.jpg\"}}}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(
+            page.get_thumbnail(200).unwrap(),
+            Some(super::Thumbnail {
+                source: "https://upload.wikimedia.org/hello.jpg".to_owned(),
+                width: 200,
+                height: 150,
+                file: "Hello.jpg".to_owned(),
+            })
+        );
+        assert_eq!(page.get_thumbnail(200).unwrap(), None);
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "pageimages".to_owned()),
+                ("piprop".to_owned(), "thumbnail|name".to_owned()),
+                ("pithumbsize".to_owned(), "200".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "World".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn page_description() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"description\":\"Hello world\",\"pageprops\":{\"wikibase-shortdesc\":\"Other\"}}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"pageprops\":{\"wikibase-shortdesc\":\"Local\"}}}}}"
+                .to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(
+            page.get_description().unwrap(),
+            Some("Hello world".to_owned())
+        );
+        assert_eq!(page.get_description().unwrap(), Some("Local".to_owned()));
+        assert_eq!(page.get_description().unwrap(), None);
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "description|pageprops".to_owned()),
+                ("ppprop".to_owned(), "wikibase-shortdesc".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "World".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn page_without_thumbnail_or_description() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"World\"}}}}".to_owned(),
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"World\",\"thumbnail\":{\"width\":200}}}}}".to_owned(),
+            "{\"batchcomplete\":\"\"}".to_owned(),
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"ns\":0,\"title\":\"World\"}}}}".to_owned(),
+            "{\"batchcomplete\":\"\"}".to_owned(),
+            "{\"query\":{\"pages\":{\"-1\":{\"ns\":0,\"title\":\"World\",\"missing\":\"\"}}}}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(page.get_thumbnail(200).unwrap(), None);
+        assert_eq!(page.get_thumbnail(200).unwrap(), None);
+        assert_eq!(page.get_thumbnail(200).unwrap(), None);
+        assert_eq!(page.get_description().unwrap(), None);
+        assert_eq!(page.get_description().unwrap(), None);
+        assert!(matches!(
+            page.get_description(),
+            Err(super::Error::PageNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn page_wikidata_id() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"normalized\":[{\"from\":\"usa\",\"to\":\"Usa\"}],\"redirects\":[{\"from\":\"Usa\",\"to\":\"United States\"}],\"pages\":{\"3434750\":{\"pageid\":3434750,\"ns\":0,\"title\":\"United States\",\"pageprops\":{\"wikibase_item\":\"Q30\"}}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"ns\":0,\"title\":\"Hello\"}}}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_title("usa".to_owned());
+        assert_eq!(page.get_wikidata_id().unwrap(), Some("Q30".to_owned()));
+        assert_eq!(page.get_wikidata_id().unwrap(), None);
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "pageprops".to_owned()),
+                ("ppprop".to_owned(), "wikibase_item".to_owned()),
+                ("redirects".to_owned(), "1".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "usa".to_owned())
+            ]
+        );
     }
 
-    impl super::http::HttpClient for MockClient {
-        fn user_agent(&mut self, user_agent: String) {
-            self.user_agent = Some(user_agent)
-        }
+    #[test]
+    fn page_wikidata_id_no_follow_redirects() {
+        let wikipedia = Wikipedia::<MockClient> {
+            follow_redirects: false,
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"redirects\":[{\"from\":\"USA\",\"to\":\"United States\"}],\"pages\":{\"3434750\":{\"pageid\":3434750,\"ns\":0,\"title\":\"United States\",\"pageprops\":{\"wikibase_item\":\"Q30\"}}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("USA".to_owned());
+        assert_eq!(page.get_wikidata_id().unwrap(), Some("Q30".to_owned()));
+        assert!(wikipedia.client.arguments.lock().unwrap()[0]
+            .contains(&("redirects".to_owned(), "1".to_owned())));
+    }
 
-        fn bearer_token(&mut self, bearer_token: String) {
-            self.bearer_token = Some(bearer_token)
-        }
+    #[test]
+    fn page_disambiguation() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"19694\":{\"pageid\":19694,\"title\":\"Mercury\",\"pageprops\":{\"disambiguation\":\"\"}}}}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"19694\":{\"links\":[{\"ns\":0,\"title\":\"Mercury (element)\"},{\"ns\":0,\"title\":\"Mercury (planet)\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Mercury".to_owned());
+        assert_eq!(
+            page.get_disambiguation_links().unwrap(),
+            vec![
+                "Mercury (element)".to_owned(),
+                "Mercury (planet)".to_owned()
+            ]
+        );
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "pageprops".to_owned()),
+                ("ppprop".to_owned(), "disambiguation".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Mercury".to_owned()),
+            ]
+        );
+    }
 
-        fn get<'a, I>(&self, base_url: &str, args: I) -> Result<String, super::http::Error>
-        where
-            I: Iterator<Item = (&'a str, &'a str)>,
+    #[test]
+    fn page_article_summary() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"pages\":{\"19694\":{\"pageid\":19694,\"title\":\"Mercury\",\"pageprops\":{\"disambiguation\":\"\"}}}}}".to_owned(),
+            "{\"query\":{\"pages\":{\"19694\":{\"links\":[{\"ns\":0,\"title\":\"Mercury (element)\"},{\"ns\":0,\"title\":\"Mercury (planet)\"}]}}}}".to_owned(),
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"title\":\"Albert Einstein\"}}}}".to_owned(),
+            "{\"query\":{\"pages\":{\"736\":{\"extract\":\"Albert Einstein was a physicist.\"}}}}".to_owned(),
+        ]);
+        match wikipedia
+            .page_from_title("Mercury".to_owned())
+            .get_article_summary()
         {
-            self.url.lock().unwrap().push(base_url.to_owned());
-            self.arguments
-                .lock()
-                .unwrap()
-                .push(args.map(|x| (x.0.to_owned(), x.1.to_owned())).collect());
-            Ok(self.response.lock().unwrap().remove(0))
+            Err(super::Error::Disambiguation(titles)) => assert_eq!(
+                titles,
+                vec![
+                    "Mercury (element)".to_owned(),
+                    "Mercury (planet)".to_owned()
+                ]
+            ),
+            x => panic!("{:?}", x),
         }
+        assert_eq!(
+            wikipedia
+                .page_from_title("Albert Einstein".to_owned())
+                .get_article_summary()
+                .unwrap(),
+            "Albert Einstein was a physicist."
+        );
     }
 
     #[test]
-    fn base_url() {
-        let mut wikipedia = Wikipedia::<MockClient>::default();
-        assert_eq!(wikipedia.base_url(), "https://en.wikipedia.org/w/api.php");
-        wikipedia.language = "es".to_owned();
-        assert_eq!(wikipedia.base_url(), "https://es.wikipedia.org/w/api.php");
-
-        wikipedia.set_base_url("https://hello.{language}.world/");
-        assert_eq!(wikipedia.base_url(), "https://hello.es.world/");
-
-        wikipedia.set_base_url("https://hello.world/");
-        assert_eq!(wikipedia.base_url(), "https://hello.world/");
+    fn page_not_disambiguation() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"title\":\"Albert Einstein\"}}}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        assert!(page.get_disambiguation_links().unwrap().is_empty());
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 1);
     }
 
     #[test]
-    fn user_agent() {
-        let mut wikipedia = Wikipedia::<MockClient>::default();
+    fn page_no_coordinates() {
+        let wikipedia = Wikipedia::<MockClient>::default();
         wikipedia
             .client
             .response
             .lock()
             .unwrap()
-            .push("{}".to_owned());
-        wikipedia.search("hello world").unwrap_err();
-        assert_eq!(&*wikipedia.client.user_agent.unwrap(), DEFAULT_AGENT);
-
-        let mut client = MockClient::default();
-        client.user_agent("hello world".to_owned());
-        client.response.lock().unwrap().push("{}".to_owned());
-        wikipedia.client = client;
-        wikipedia.search("hello world").unwrap_err();
-        assert_eq!(&*wikipedia.client.user_agent.unwrap(), "hello world");
+            .push("{\"query\":{\"pages\":{\"a\":{}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert!(page.get_coordinates().unwrap().is_none());
+        assert_eq!(
+            *wikipedia.client.url.lock().unwrap(),
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+        );
+        assert_eq!(
+            *wikipedia.client.arguments.lock().unwrap(),
+            vec![vec![
+                ("prop".to_owned(), "coordinates".to_owned()),
+                ("colimit".to_owned(), "max".to_owned()),
+                ("redirects".to_owned(), "".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "World".to_owned())
+            ]]
+        );
     }
 
     #[test]
-    fn search() {
+    fn page_citations() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"search\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+        let html = r##"<p>Text.<sup id="cite_ref-1" class="reference"><a href="#cite_note-1">[1]</a></sup></p><div class="reflist"><ol class="references"><li id="cite_note-1"><span class="mw-cite-backlink"><b><a href="#cite_ref-1">^</a></b></span> <span class="reference-text"><cite class="citation web"><a rel="nofollow" class="external text" href="https://example.com/a?x=1&amp;y=2">Some &quot;title&quot;</a>. <a rel="nofollow" class="external text" href="https://web.archive.org/web/2020/https://example.com/a">Archived</a></cite></span></li><li id="cite_note-smith-2"><span class="mw-cite-backlink">^</span> <span class="reference-text">Smith (1999), p. 4.</span></li></ol></div>"##;
+        let response = serde_json::json!({
+            "query": {"pages": {"1": {"revisions": [{"*": html}]}}}
+        });
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push(response.to_string());
+        let page = wikipedia.page_from_title("Foo".to_owned());
+        let citations = page.get_citations().unwrap();
+        assert_eq!(
+            citations,
+            vec![
+                super::Citation {
+                    id: "cite_note-1".to_owned(),
+                    text: "Some \"title\". Archived".to_owned(),
+                    url: Some("https://example.com/a?x=1&y=2".to_owned()),
+                    title: Some("Some \"title\"".to_owned()),
+                    archive_url: Some(
+                        "https://web.archive.org/web/2020/https://example.com/a".to_owned()
+                    ),
+                },
+                super::Citation {
+                    id: "cite_note-smith-2".to_owned(),
+                    text: "Smith (1999), p. 4.".to_owned(),
+                    url: None,
+                    title: None,
+                    archive_url: None,
+                },
+            ]
         );
+        assert!(citations[0].is_archived());
+    }
+
+    #[test]
+    fn get_references() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"extlinks\":[{\"*\": \"//example.com/reference1.html\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"a\":{\"extlinks\":[{\"*\": \"//example.com/reference2.html\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
         assert_eq!(
-            wikipedia.search("hello world").unwrap(),
-            vec!["hello".to_owned(), "world".to_owned(),]
+            page.get_references().unwrap().collect::<Vec<_>>(),
+            vec![
+                iter::Reference {
+                    url: "http://example.com/reference1.html".to_owned(),
+                },
+                iter::Reference {
+                    url: "http://example.com/reference2.html".to_owned(),
+                }
+            ]
         );
         assert_eq!(
             *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            vec![
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+            ]
         );
         assert_eq!(
             *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("list".to_owned(), "search".to_owned()),
-                ("srprop".to_owned(), "".to_owned()),
-                ("srlimit".to_owned(), "10".to_owned()),
-                ("srsearch".to_owned(), "hello world".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned())
-            ]]
+            vec![
+                vec![
+                    ("prop".to_owned(), "extlinks".to_owned()),
+                    ("ellimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("continue".to_owned(), "".to_owned())
+                ],
+                vec![
+                    ("prop".to_owned(), "extlinks".to_owned()),
+                    ("ellimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("lol".to_owned(), "1".to_owned())
+                ]
+            ]
         );
     }
 
     #[test]
-    fn geosearch() {
+    fn get_external_links() {
+        let wikipedia = Wikipedia::<MockClient> {
+            extlinks_results: super::Limit::Count(2),
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"elcontinue\":\"736|12\",\"continue\":\"||\"},\"query\":{\"pages\":{\"736\":{\"extlinks\":[{\"*\":\"https://example.com/a\"},{\"*\":\"//example.com/b\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"736\":{\"extlinks\":[{\"*\":\"http://example.com/c\"}]}}}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        assert_eq!(
+            page.get_external_links()
+                .unwrap()
+                .map(|x| x.url)
+                .collect::<Vec<_>>(),
+            vec![
+                "https://example.com/a".to_owned(),
+                "http://example.com/b".to_owned(),
+                "http://example.com/c".to_owned(),
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[0][1], ("ellimit".to_owned(), "2".to_owned()));
+        assert_eq!(
+            arguments[1][5..],
+            [
+                ("continue".to_owned(), "||".to_owned()),
+                ("elcontinue".to_owned(), "736|12".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_links() {
         let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\": \"Hello\"}]}}}}".to_owned());
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"geosearch\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+            "{\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\": \"World\"}]}}}}".to_owned(),
         );
+        let page = wikipedia.page_from_title("World".to_owned());
         assert_eq!(
-            wikipedia.geosearch(-34.603333, -58.381667, 10).unwrap(),
-            vec!["hello".to_owned(), "world".to_owned(),]
+            page.get_links().unwrap().collect::<Vec<_>>(),
+            vec![
+                iter::Link {
+                    title: "Hello".to_owned(),
+                    namespace: 0,
+                },
+                iter::Link {
+                    title: "World".to_owned(),
+                    namespace: 0,
+                }
+            ]
         );
         assert_eq!(
             *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            vec![
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+                "https://en.wikipedia.org/w/api.php".to_owned(),
+            ]
         );
         assert_eq!(
             *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("list".to_owned(), "geosearch".to_owned()),
-                ("gsradius".to_owned(), "10".to_owned()),
-                ("gscoord".to_owned(), "-34.603333|-58.381667".to_owned()),
-                ("gslimit".to_owned(), "10".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned())
-            ]]
+            vec![
+                vec![
+                    ("prop".to_owned(), "links".to_owned()),
+                    ("pllimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("continue".to_owned(), "".to_owned()),
+                ],
+                vec![
+                    ("prop".to_owned(), "links".to_owned()),
+                    ("pllimit".to_owned(), "max".to_owned()),
+                    ("format".to_owned(), "json".to_owned()),
+                    ("action".to_owned(), "query".to_owned()),
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("lol".to_owned(), "1".to_owned()),
+                ]
+            ]
         );
     }
 
     #[test]
-    fn random_count() {
+    fn get_links_default_namespaces() {
         let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"query\":{\"pages\":{\"a\":{\"links\":[]}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(page.get_links().unwrap().count(), 0);
+        assert!(wikipedia.client.arguments.lock().unwrap()[0]
+            .iter()
+            .all(|x| x.0 != "plnamespace"));
+    }
+
+    #[test]
+    fn get_links_main_namespace_descending() {
+        let wikipedia = Wikipedia::<MockClient> {
+            links_namespaces: vec![0],
+            links_descending: true,
+            ..Wikipedia::default()
+        };
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"random\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+            "{\"query\":{\"pages\":{\"a\":{\"links\":[{\"ns\":0,\"title\":\"Physics\"},{\"ns\":0,\"title\":\"Chemistry\"}]}}}}"
+                .to_owned(),
         );
+        let page = wikipedia.page_from_title("World".to_owned());
         assert_eq!(
-            wikipedia.random_count(10).unwrap(),
-            vec!["hello".to_owned(), "world".to_owned(),]
+            page.get_links()
+                .unwrap()
+                .map(|x| (x.namespace, x.title))
+                .collect::<Vec<_>>(),
+            vec![(0, "Physics".to_owned()), (0, "Chemistry".to_owned())]
         );
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            wikipedia.client.arguments.lock().unwrap()[0][..4],
+            [
+                ("prop".to_owned(), "links".to_owned()),
+                ("plnamespace".to_owned(), "0".to_owned()),
+                ("pllimit".to_owned(), "max".to_owned()),
+                ("pldir".to_owned(), "descending".to_owned()),
+            ]
         );
+    }
+
+    #[test]
+    fn links_continue_token() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        let first = "{\"continue\":{\"plcontinue\":\"1|0|C\",\"continue\":\"||\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"A\"},{\"title\":\"B\"}]}}}}";
+        let second = "{\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"C\"}]}}}}";
+        for response in [first, first, second, second] {
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push(response.to_owned());
+        }
+        let page = wikipedia.page_from_title("World".to_owned());
+        let mut links = page.get_links().unwrap();
+        assert_eq!(links.next().unwrap().title, "A");
+        let token = links.continue_token().unwrap();
+
+        // resuming in the middle of a batch fetches the batch again
+        let mut links = page.get_links_from(&token).unwrap();
+        assert_eq!(links.next().unwrap().title, "B");
+        let token = links.continue_token().unwrap();
+        {
+            let arguments = wikipedia.client.arguments.lock().unwrap();
+            assert_eq!(arguments[1], arguments[0]);
+        }
+
+        // at the end of a batch the token points to the next one
+        let mut links = page.get_links_from(&token).unwrap();
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("list".to_owned(), "random".to_owned()),
-                ("rnnamespace".to_owned(), "0".to_owned()),
-                ("rnlimit".to_owned(), "10".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned())
-            ]]
+            wikipedia.client.arguments.lock().unwrap()[2][5..],
+            [
+                ("continue".to_owned(), "||".to_owned()),
+                ("plcontinue".to_owned(), "1|0|C".to_owned()),
+            ]
         );
+        assert!(links.continue_token().is_some());
+        assert_eq!(links.next().unwrap().title, "C");
+        assert_eq!(links.continue_token(), None);
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 3);
+
+        assert!(matches!(
+            page.get_links_from("not a token"),
+            Err(super::Error::InvalidParameter(_))
+        ));
     }
 
     #[test]
-    fn random() {
+    fn list_continue_token() {
         let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"iucontinue\":\"0|Physics\",\"continue\":\"-||\"},\"query\":{\"imageusage\":[{\"pageid\":736,\"ns\":0,\"title\":\"Albert Einstein\"}]}}".to_owned());
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"random\":[{\"title\":\"hello\"}, {\"title\":\"world\"}]}}".to_owned(),
+            "{\"query\":{\"imageusage\":[{\"pageid\":22939,\"ns\":0,\"title\":\"Physics\"}]}}"
+                .to_owned(),
         );
-        assert_eq!(wikipedia.random().unwrap(), Some("hello".to_owned()));
+        let mut usage = wikipedia.image_usage("Einstein.jpg").unwrap();
+        assert_eq!(usage.next().unwrap().title, "Albert Einstein");
+        let token = usage.continue_token().unwrap();
+
+        let mut usage: iter::ListIter<'_, _, iter::ImageUsage> =
+            iter::ListIter::from_token(&wikipedia, &token).unwrap();
+        assert_eq!(usage.next().unwrap().title, "Physics");
+        assert!(usage.next().is_none());
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments[1][..3], arguments[0][..3]);
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            arguments[1][5..],
+            [
+                ("continue".to_owned(), "-||".to_owned()),
+                ("iucontinue".to_owned(), "0|Physics".to_owned()),
+            ]
         );
+    }
+
+    #[test]
+    fn get_images_opts() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        for title in ["A", "B", "C"] {
+            wikipedia.client.response.lock().unwrap().push(format!(
+                "{{\"continue\":{{\"gimcontinue\":\"{0}\",\"continue\":\"||\"}},\"query\":{{\"pages\":{{\"1\":{{\"title\":\"File:{0}1.jpg\"}},\"2\":{{\"title\":\"File:{0}2.jpg\"}}}}}}}}",
+                title
+            ));
+        }
+        let page = wikipedia.page_from_title("World".to_owned());
+        let options = IterOptions::new().page_size(2).max_items(3);
+        let images = page.get_images_opts(&options).unwrap();
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("list".to_owned(), "random".to_owned()),
-                ("rnnamespace".to_owned(), "0".to_owned()),
-                ("rnlimit".to_owned(), "1".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned())
-            ]]
+            images.map(|x| x.title).collect::<Vec<_>>(),
+            vec!["File:A1.jpg", "File:A2.jpg", "File:B1.jpg"]
         );
+        // the third batch is never requested
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(arguments[0][1], ("gimlimit".to_owned(), "2".to_owned()));
+        assert_eq!(arguments[1][1], ("gimlimit".to_owned(), "2".to_owned()));
     }
 
     #[test]
-    fn page_content() {
+    fn iter_options_page_size() {
         let wikipedia = Wikipedia::<MockClient>::default();
+        let page = wikipedia.page_from_title("World".to_owned());
+        for size in ["0", "-1", "ten", ""] {
+            let options = IterOptions::new().page_size(size);
+            assert!(matches!(
+                page.get_links_opts(&options),
+                Err(super::Error::InvalidParameter(_))
+            ));
+        }
+        assert!(wikipedia.client.arguments.lock().unwrap().is_empty());
         wikipedia
             .client
             .response
             .lock()
             .unwrap()
-            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
-        let page = wikipedia.page_from_pageid("4138548".to_owned());
-        let html = page.get_content().unwrap();
-        assert_eq!(html, "hello".to_owned());
-        assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
-        );
+            .push("{\"query\":{\"pages\":{\"a\":{\"categories\":[]}}}}".to_owned());
+        let options = IterOptions::new().page_size("max");
+        assert_eq!(page.get_categories_opts(&options).unwrap().count(), 0);
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("prop".to_owned(), "extracts|revisions".to_owned()),
-                ("explaintext".to_owned(), "".to_owned()),
-                ("rvprop".to_owned(), "ids".to_owned()),
-                ("redirects".to_owned(), "".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned()),
-                ("pageids".to_owned(), "4138548".to_owned()),
-            ]]
+            wikipedia.client.arguments.lock().unwrap()[0][2],
+            ("cllimit".to_owned(), "max".to_owned())
         );
     }
 
     #[test]
-    fn page_html_content() {
-        let wikipedia = Wikipedia::<MockClient>::default();
+    fn limit() {
+        use super::Limit;
+        assert_eq!(Limit::Max.to_string(), "max");
+        assert_eq!(Limit::Count(20).to_string(), "20");
+        assert_eq!("max".parse::<Limit>().unwrap(), Limit::Max);
+        assert_eq!("500".parse::<Limit>().unwrap(), Limit::Count(500));
+        for invalid in ["0", "501", "-1", "all", ""] {
+            assert!(invalid.parse::<Limit>().is_err(), "{}", invalid);
+        }
+        assert!(Limit::count(0).is_err());
+        assert_eq!(Limit::count(1).unwrap(), Limit::Count(1));
+
+        let mut wikipedia = Wikipedia::<MockClient>::default();
+        assert!(wikipedia.set_images_results(Limit::Count(1000)).is_err());
+        assert_eq!(wikipedia.images_results, Limit::Max);
+        wikipedia.set_search_results(Limit::Max).unwrap();
         wikipedia
             .client
             .response
             .lock()
             .unwrap()
-            .push("{\"query\":{\"pages\":{\"a\":{\"revisions\":[{\"*\":\"hello\"}]}}}}".to_owned());
-        let page = wikipedia.page_from_pageid("4138548".to_owned());
-        let html = page.get_html_content().unwrap();
-        assert_eq!(html, "hello".to_owned());
-        assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
-        );
-        assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("prop".to_owned(), "revisions".to_owned()),
-                ("rvprop".to_owned(), "content".to_owned()),
-                ("rvlimit".to_owned(), "1".to_owned()),
-                ("rvparse".to_owned(), "".to_owned()),
-                ("redirects".to_owned(), "".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned()),
-                ("pageids".to_owned(), "4138548".to_owned()),
-            ]]
-        );
+            .push("{\"query\":{\"search\":[]}}".to_owned());
+        wikipedia.search("hello").unwrap();
+        assert!(wikipedia.client.arguments.lock().unwrap()[0]
+            .contains(&("srlimit".to_owned(), "max".to_owned())));
     }
 
     #[test]
-    fn page_summary() {
+    fn try_get_links_error() {
         let wikipedia = Wikipedia::<MockClient>::default();
+        // the second batch fails since there is no response for it
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"plcontinue\":\"1|0|C\",\"continue\":\"||\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"A\"},{\"title\":\"B\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        let mut links = page.try_get_links().unwrap();
+        assert_eq!(links.next().unwrap().unwrap().title, "A");
+        assert_eq!(links.next().unwrap().unwrap().title, "B");
+        assert!(matches!(links.next(), Some(Err(super::Error::IOError(_)))));
+        assert!(links.next().is_none());
+        assert_eq!(wikipedia.client.arguments.lock().unwrap().len(), 2);
+
+        // the failed batch can be retried
+        let token = links.continue_token().unwrap();
         wikipedia
             .client
             .response
             .lock()
             .unwrap()
-            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
-        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
-        let summary = page.get_summary().unwrap();
-        assert_eq!(summary, "hello".to_owned());
+            .push("{\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"C\"}]}}}}".to_owned());
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            page.get_links_from(&token)
+                .unwrap()
+                .map(|x| x.title)
+                .collect::<Vec<_>>(),
+            vec!["C"]
         );
-        assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("prop".to_owned(), "extracts".to_owned()),
-                ("explaintext".to_owned(), "".to_owned()),
-                ("exintro".to_owned(), "".to_owned()),
-                ("redirects".to_owned(), "".to_owned()),
-                ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "query".to_owned()),
-                (
-                    "titles".to_owned(),
-                    "Parkinson\'s law of triviality".to_owned()
-                )
-            ]]
+    }
+
+    #[test]
+    fn try_get_images_parse_error() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"title\":\"File:A.jpg\"},\"2\":\"garbage\",\"3\":{\"title\":\"File:C.jpg\"}}}}".to_owned(),
         );
+        let page = wikipedia.page_from_title("World".to_owned());
+        let images: Vec<_> = page.try_get_images().unwrap().collect();
+        assert_eq!(images.len(), 3);
+        assert!(images[0].is_ok());
+        assert!(matches!(
+            images[1],
+            Err(super::Error::JSONPathError("query.pages[]"))
+        ));
+        assert_eq!(images[2].as_ref().unwrap().title, "File:C.jpg");
     }
 
     #[test]
-    fn page_redirect_summary() {
+    fn iter_size_hint() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia
-            .client
-            .response
-            .lock()
-            .unwrap()
-            .push("{\"query\":{\"redirects\":[{\"to\":\"hello world\"}]}}".to_owned());
-        wikipedia
-            .client
-            .response
-            .lock()
-            .unwrap()
-            .push("{\"query\":{\"pages\":{\"a\":{\"extract\":\"hello\"}}}}".to_owned());
-        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
-        let summary = page.get_summary().unwrap();
-        assert_eq!(summary, "hello".to_owned());
-        assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec![
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-            ]
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"plcontinue\":\"1|0|C\",\"continue\":\"||\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"A\"},{\"title\":\"B\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"C\"},{\"title\":\"D\"}]}}}}"
+                .to_owned(),
         );
+        let page = wikipedia.page_from_title("World".to_owned());
+        let mut links = page.get_links().unwrap();
+        assert_eq!(links.size_hint(), (2, None));
+        links.next();
+        links.next();
+        assert_eq!(links.size_hint(), (0, None));
+        links.next();
+        assert_eq!(links.size_hint(), (1, Some(1)));
+
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"plcontinue\":\"1|0|C\",\"continue\":\"||\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\":\"A\"},{\"title\":\"B\"}]}}}}".to_owned());
+        let links = page
+            .get_links_opts(&IterOptions::new().max_items(1))
+            .unwrap();
+        assert_eq!(links.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn category_members_count_hint() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"cmcontinue\":\"page|X\",\"continue\":\"-||\"},\"query\":{\"categorymembers\":[{\"pageid\":1,\"ns\":0,\"title\":\"A\",\"type\":\"page\"},{\"pageid\":2,\"ns\":14,\"title\":\"Category:B\",\"type\":\"subcat\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"ns\":14,\"title\":\"Category:Physics\",\"categoryinfo\":{\"size\":12,\"pages\":9,\"files\":1,\"subcats\":2}}}}}".to_owned());
+        let mut members = wikipedia
+            .category_members("Physics")
+            .unwrap()
+            .with_count_hint()
+            .unwrap();
+        assert_eq!(members.size_hint(), (2, Some(12)));
+        members.next();
+        assert_eq!(members.size_hint(), (1, Some(11)));
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
+            wikipedia.client.arguments.lock().unwrap()[1],
             vec![
-                vec![
-                    ("prop".to_owned(), "extracts".to_owned()),
-                    ("explaintext".to_owned(), "".to_owned()),
-                    ("exintro".to_owned(), "".to_owned()),
-                    ("redirects".to_owned(), "".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    (
-                        "titles".to_owned(),
-                        "Parkinson\'s law of triviality".to_owned()
-                    )
-                ],
-                vec![
-                    ("prop".to_owned(), "extracts".to_owned()),
-                    ("explaintext".to_owned(), "".to_owned()),
-                    ("exintro".to_owned(), "".to_owned()),
-                    ("redirects".to_owned(), "".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    ("titles".to_owned(), "hello world".to_owned())
-                ]
+                ("prop".to_owned(), "categoryinfo".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Category:Physics".to_owned()),
             ]
         );
+
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"cmcontinue\":\"page|X\",\"continue\":\"-||\"},\"query\":{\"categorymembers\":[{\"pageid\":2,\"ns\":14,\"title\":\"Category:B\",\"type\":\"subcat\"}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"ns\":14,\"title\":\"Category:Physics\",\"categoryinfo\":{\"size\":12,\"pages\":9,\"files\":1,\"subcats\":2}}}}}".to_owned());
+        let options = super::CategoryMembersOptions {
+            types: vec![
+                super::CategoryMemberType::Subcat,
+                super::CategoryMemberType::File,
+            ],
+            ..Default::default()
+        };
+        let members = wikipedia
+            .category_members_opts("Physics", &options)
+            .unwrap()
+            .with_count_hint()
+            .unwrap();
+        assert_eq!(members.size_hint(), (1, Some(3)));
     }
 
     #[test]
-    fn page_images() {
+    fn get_categories() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"title\":\"Image 1\", \"imageinfo\":[{\"url\": \"http://example.com/image1.jpg\", \"descriptionurl\": \"http://example.com/image1.jpg.html\"}]}}}}".to_owned());
-        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"a\":{\"title\":\"Image 2\", \"imageinfo\":[{\"url\": \"http://example.com/image2.jpg\", \"descriptionurl\": \"http://example.com/image2.jpg.html\"}]}}}}".to_owned());
-        let page = wikipedia.page_from_title("Parkinson's law of triviality".to_owned());
-        let images = page.get_images().unwrap().collect::<Vec<_>>();
+        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"categories\":[{\"title\": \"Hello\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"a\":{\"categories\":[{\"title\": \"Category: World\",\"sortkey\":\"4c4f52\",\"sortkeyprefix\":\"Earth\",\"hidden\":\"\"}]}}}}"
+                .to_owned(),
+        );
+        let page = wikipedia.page_from_title("World".to_owned());
         assert_eq!(
-            images,
+            page.get_categories().unwrap().collect::<Vec<_>>(),
             vec![
-                iter::Image {
-                    url: "http://example.com/image1.jpg".to_owned(),
-                    title: "Image 1".to_owned(),
-                    description_url: "http://example.com/image1.jpg.html".to_owned(),
+                iter::Category {
+                    title: "Hello".to_owned(),
+                    hidden: false,
+                    sortkey: None,
                 },
-                iter::Image {
-                    url: "http://example.com/image2.jpg".to_owned(),
-                    title: "Image 2".to_owned(),
-                    description_url: "http://example.com/image2.jpg.html".to_owned(),
+                iter::Category {
+                    title: "World".to_owned(),
+                    hidden: true,
+                    sortkey: Some("Earth".to_owned()),
                 }
             ]
         );
@@ -1205,260 +9995,501 @@ mod test {
             *wikipedia.client.arguments.lock().unwrap(),
             vec![
                 vec![
-                    ("generator".to_owned(), "images".to_owned()),
-                    ("gimlimit".to_owned(), "max".to_owned()),
-                    ("prop".to_owned(), "imageinfo".to_owned()),
-                    ("iiprop".to_owned(), "url".to_owned()),
+                    ("prop".to_owned(), "categories".to_owned()),
+                    ("clprop".to_owned(), "hidden|sortkey".to_owned()),
+                    ("cllimit".to_owned(), "max".to_owned()),
                     ("format".to_owned(), "json".to_owned()),
                     ("action".to_owned(), "query".to_owned()),
-                    (
-                        "titles".to_owned(),
-                        "Parkinson\'s law of triviality".to_owned()
-                    ),
-                    ("continue".to_owned(), "".to_owned())
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("continue".to_owned(), "".to_owned()),
                 ],
                 vec![
-                    ("generator".to_owned(), "images".to_owned()),
-                    ("gimlimit".to_owned(), "max".to_owned()),
-                    ("prop".to_owned(), "imageinfo".to_owned()),
-                    ("iiprop".to_owned(), "url".to_owned()),
+                    ("prop".to_owned(), "categories".to_owned()),
+                    ("clprop".to_owned(), "hidden|sortkey".to_owned()),
+                    ("cllimit".to_owned(), "max".to_owned()),
                     ("format".to_owned(), "json".to_owned()),
                     ("action".to_owned(), "query".to_owned()),
-                    (
-                        "titles".to_owned(),
-                        "Parkinson\'s law of triviality".to_owned()
-                    ),
-                    ("lol".to_owned(), "1".to_owned())
+                    ("titles".to_owned(), "World".to_owned()),
+                    ("lol".to_owned(), "1".to_owned()),
                 ]
             ]
         );
     }
 
     #[test]
-    fn page_coordinates() {
-        let wikipedia = Wikipedia::<MockClient>::default();
+    fn get_categories_not_hidden() {
+        let wikipedia = Wikipedia::<MockClient> {
+            hidden_categories: false,
+            ..Wikipedia::default()
+        };
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"pages\":{\"a\":{\"coordinates\":[{\"lat\": 2.1, \"lon\":-1.3}]}}}}"
+            "{\"query\":{\"pages\":{\"a\":{\"categories\":[{\"title\":\"Category:Physics\"}]}}}}"
                 .to_owned(),
         );
         let page = wikipedia.page_from_title("World".to_owned());
-        let coordinates = page.get_coordinates().unwrap().unwrap();
-        assert_eq!(coordinates, (2.1, -1.3));
+        assert_eq!(page.get_categories().unwrap().count(), 1);
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            wikipedia.client.arguments.lock().unwrap()[0][..4],
+            [
+                ("prop".to_owned(), "categories".to_owned()),
+                ("clprop".to_owned(), "hidden|sortkey".to_owned()),
+                ("cllimit".to_owned(), "max".to_owned()),
+                ("clshow".to_owned(), "!hidden".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_templates() {
+        let wikipedia = Wikipedia::<MockClient> {
+            templates_namespaces: vec![10],
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"tlcontinue\":\"736|10|Citation\",\"continue\":\"||\"},\"query\":{\"pages\":{\"736\":{\"templates\":[{\"ns\":10,\"title\":\"Template:About\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"736\":{\"templates\":[{\"ns\":10,\"title\":\"Template:Infobox scientist\"}]}}}}".to_owned(),
         );
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("prop".to_owned(), "coordinates".to_owned()),
-                ("colimit".to_owned(), "max".to_owned()),
-                ("redirects".to_owned(), "".to_owned()),
+            page.get_templates().unwrap().collect::<Vec<_>>(),
+            vec![
+                iter::Template {
+                    title: "Template:About".to_owned(),
+                    ns: 10,
+                },
+                iter::Template {
+                    title: "Template:Infobox scientist".to_owned(),
+                    ns: 10,
+                }
+            ]
+        );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "templates".to_owned()),
+                ("tllimit".to_owned(), "max".to_owned()),
+                ("tlnamespace".to_owned(), "10".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "query".to_owned()),
+                ("titles".to_owned(), "Albert Einstein".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][7],
+            ("tlcontinue".to_owned(), "736|10|Citation".to_owned())
+        );
+    }
+
+    #[test]
+    fn has_template() {
+        let wikipedia = Wikipedia::<MockClient> {
+            language: "de".to_owned(),
+            templates_namespaces: vec![0],
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().extend([
+            "{\"query\":{\"pages\":{\"736\":{\"templates\":[{\"ns\":10,\"title\":\"Vorlage:Infobox Person\"}]}}}}".to_owned(),
+            "{\"batchcomplete\":\"\",\"query\":{\"pages\":{\"736\":{\"pageid\":736,\"title\":\"Albert Einstein\"}}}}".to_owned(),
+        ]);
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        assert!(page.has_template("infobox_Person").unwrap());
+        assert!(!page.has_template("Template:Navbox").unwrap());
+        let arguments = wikipedia.client.arguments.lock().unwrap();
+        assert_eq!(
+            arguments[0],
+            vec![
+                ("prop".to_owned(), "templates".to_owned()),
+                (
+                    "tltemplates".to_owned(),
+                    "Template:Infobox Person".to_owned()
+                ),
                 ("format".to_owned(), "json".to_owned()),
                 ("action".to_owned(), "query".to_owned()),
-                ("titles".to_owned(), "World".to_owned())
-            ]]
+                ("titles".to_owned(), "Albert Einstein".to_owned()),
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][1],
+            ("tltemplates".to_owned(), "Template:Navbox".to_owned())
         );
     }
 
     #[test]
-    fn page_no_coordinates() {
+    fn has_template_error() {
         let wikipedia = Wikipedia::<MockClient>::default();
         wikipedia
             .client
-            .response
+            .failures
             .lock()
             .unwrap()
-            .push("{\"query\":{\"pages\":{\"a\":{}}}}".to_owned());
-        let page = wikipedia.page_from_title("World".to_owned());
-        assert!(page.get_coordinates().unwrap().is_none());
+            .push(super::Error::Timeout);
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        assert!(matches!(
+            page.has_template("Infobox scientist"),
+            Err(super::Error::Timeout)
+        ));
+    }
+
+    #[test]
+    fn get_backlinks() {
+        let wikipedia = Wikipedia::<MockClient> {
+            backlinks_namespaces: vec![0, 4],
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"blcontinue\":\"0|1234\",\"continue\":\"-||\"},\"query\":{\"backlinks\":[{\"pageid\":1,\"ns\":0,\"title\":\"Physics\"},{\"pageid\":2,\"ns\":0,\"title\":\"Einstein\",\"redirect\":\"\",\"redirlinks\":[{\"pageid\":3,\"ns\":0,\"title\":\"Relativity\"}]}]}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"backlinks\":[{\"pageid\":4,\"ns\":4,\"title\":\"Wikipedia:Vital articles\"}]}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+        let backlinks = page.get_backlinks().unwrap().collect::<Vec<_>>();
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            backlinks
+                .iter()
+                .map(|x| (&*x.title, x.redirect, x.via.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Physics", false, None),
+                ("Einstein", true, None),
+                ("Relativity", false, Some("Einstein")),
+                ("Wikipedia:Vital articles", false, None),
+            ]
         );
+        assert_eq!(backlinks[3].ns, 4);
+        let arguments = wikipedia.client.arguments.lock().unwrap();
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
-            vec![vec![
-                ("prop".to_owned(), "coordinates".to_owned()),
-                ("colimit".to_owned(), "max".to_owned()),
-                ("redirects".to_owned(), "".to_owned()),
+            arguments[0],
+            vec![
+                ("list".to_owned(), "backlinks".to_owned()),
+                ("bltitle".to_owned(), "Albert Einstein".to_owned()),
+                ("bllimit".to_owned(), "max".to_owned()),
+                ("blnamespace".to_owned(), "0|4".to_owned()),
+                ("blredirect".to_owned(), "".to_owned()),
                 ("format".to_owned(), "json".to_owned()),
                 ("action".to_owned(), "query".to_owned()),
-                ("titles".to_owned(), "World".to_owned())
-            ]]
+                ("continue".to_owned(), "".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][7..],
+            [
+                ("blcontinue".to_owned(), "0|1234".to_owned()),
+                ("continue".to_owned(), "-||".to_owned()),
+            ]
         );
     }
 
     #[test]
-    fn get_references() {
+    fn get_redirects_here() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"extlinks\":[{\"*\": \"//example.com/reference1.html\"}]}}}}".to_owned());
-        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"a\":{\"extlinks\":[{\"*\": \"//example.com/reference2.html\"}]}}}}".to_owned());
-        let page = wikipedia.page_from_title("World".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"rdcontinue\":\"645042|15\",\"continue\":\"||\"},\"query\":{\"pages\":{\"645042\":{\"pageid\":645042,\"title\":\"New York City\",\"redirects\":[{\"pageid\":12,\"ns\":0,\"title\":\"NYC\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"645042\":{\"pageid\":645042,\"title\":\"New York City\",\"redirects\":[{\"pageid\":15,\"ns\":0,\"title\":\"History of NYC\",\"fragment\":\"History\"}]}}}}".to_owned(),
+        );
+        let page = wikipedia.page_from_title("New York City".to_owned());
         assert_eq!(
-            page.get_references().unwrap().collect::<Vec<_>>(),
+            page.get_redirects_here().unwrap().collect::<Vec<_>>(),
             vec![
-                iter::Reference {
-                    url: "http://example.com/reference1.html".to_owned(),
+                iter::Redirect {
+                    title: "NYC".to_owned(),
+                    pageid: 12,
+                    fragment: None,
                 },
-                iter::Reference {
-                    url: "http://example.com/reference2.html".to_owned(),
+                iter::Redirect {
+                    title: "History of NYC".to_owned(),
+                    pageid: 15,
+                    fragment: Some("History".to_owned()),
                 }
             ]
         );
+        let arguments = wikipedia.client.arguments.lock().unwrap();
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
+            arguments[0][..3],
+            [
+                ("prop".to_owned(), "redirects".to_owned()),
+                ("rdprop".to_owned(), "pageid|title|fragment".to_owned()),
+                ("rdlimit".to_owned(), "max".to_owned()),
+            ]
+        );
+        assert_eq!(
+            arguments[1][7],
+            ("rdcontinue".to_owned(), "645042|15".to_owned())
+        );
+    }
+
+    #[test]
+    fn get_langlinks() {
+        let wikipedia = Wikipedia::<MockClient> {
+            langlinks_results: super::Limit::Count(1),
+            ..Wikipedia::default()
+        };
+        wikipedia.client.response.lock().unwrap().push("{\"continue\":{\"llcontinue\":\"1|es\",\"continue\":\"||\"},\"query\":{\"pages\":{\"1\":{\"langlinks\":[{\"lang\":\"de\",\"url\":\"https://de.wikipedia.org/wiki/Welt\",\"*\":\"Welt\"}]}}}}".to_owned());
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"pages\":{\"1\":{\"langlinks\":[{\"lang\":\"es\",\"url\":\"https://es.wikipedia.org/wiki/Mundo\",\"*\":\"Mundo\"}]}}}}".to_owned());
+        let page = wikipedia.page_from_title("World".to_owned());
+        assert_eq!(
+            page.get_langlinks().unwrap().collect::<Vec<_>>(),
             vec![
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-                "https://en.wikipedia.org/w/api.php".to_owned(),
+                iter::LangLink {
+                    lang: "de".to_owned(),
+                    title: Some("Welt".to_owned()),
+                    url: Some("https://de.wikipedia.org/wiki/Welt".to_owned()),
+                },
+                iter::LangLink {
+                    lang: "es".to_owned(),
+                    title: Some("Mundo".to_owned()),
+                    url: Some("https://es.wikipedia.org/wiki/Mundo".to_owned()),
+                }
             ]
         );
         assert_eq!(
             *wikipedia.client.arguments.lock().unwrap(),
             vec![
                 vec![
-                    ("prop".to_owned(), "extlinks".to_owned()),
-                    ("ellimit".to_owned(), "max".to_owned()),
+                    ("prop".to_owned(), "langlinks".to_owned()),
+                    ("llprop".to_owned(), "url".to_owned()),
+                    ("lllimit".to_owned(), "1".to_owned()),
                     ("format".to_owned(), "json".to_owned()),
                     ("action".to_owned(), "query".to_owned()),
                     ("titles".to_owned(), "World".to_owned()),
-                    ("continue".to_owned(), "".to_owned())
+                    ("continue".to_owned(), "".to_owned()),
                 ],
                 vec![
-                    ("prop".to_owned(), "extlinks".to_owned()),
-                    ("ellimit".to_owned(), "max".to_owned()),
+                    ("prop".to_owned(), "langlinks".to_owned()),
+                    ("llprop".to_owned(), "url".to_owned()),
+                    ("lllimit".to_owned(), "1".to_owned()),
                     ("format".to_owned(), "json".to_owned()),
                     ("action".to_owned(), "query".to_owned()),
                     ("titles".to_owned(), "World".to_owned()),
-                    ("lol".to_owned(), "1".to_owned())
+                    ("continue".to_owned(), "||".to_owned()),
+                    ("llcontinue".to_owned(), "1|es".to_owned()),
                 ]
             ]
         );
     }
 
     #[test]
-    fn get_links() {
+    fn sections() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\": \"Hello\"}]}}}}".to_owned());
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"pages\":{\"a\":{\"links\":[{\"title\": \"World\"}]}}}}".to_owned(),
+            "{\"parse\":{\"sections\":[{\"line\":\"hello\"}, {\"line\":\"world\"}]}}".to_owned(),
         );
-        let page = wikipedia.page_from_title("World".to_owned());
+        let page = wikipedia.page_from_pageid("123".to_owned());
         assert_eq!(
-            page.get_links().unwrap().collect::<Vec<_>>(),
-            vec![
-                iter::Link {
-                    title: "Hello".to_owned(),
-                },
-                iter::Link {
-                    title: "World".to_owned(),
-                }
-            ]
+            page.get_sections().unwrap(),
+            vec!["hello".to_owned(), "world".to_owned()]
         );
         assert_eq!(
             *wikipedia.client.url.lock().unwrap(),
-            vec![
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-            ]
+            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
         );
         assert_eq!(
             *wikipedia.client.arguments.lock().unwrap(),
-            vec![
-                vec![
-                    ("prop".to_owned(), "links".to_owned()),
-                    ("plnamespace".to_owned(), "0".to_owned()),
-                    ("ellimit".to_owned(), "max".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    ("titles".to_owned(), "World".to_owned()),
-                    ("continue".to_owned(), "".to_owned()),
-                ],
-                vec![
-                    ("prop".to_owned(), "links".to_owned()),
-                    ("plnamespace".to_owned(), "0".to_owned()),
-                    ("ellimit".to_owned(), "max".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    ("titles".to_owned(), "World".to_owned()),
-                    ("lol".to_owned(), "1".to_owned()),
-                ]
-            ]
+            vec![vec![
+                ("prop".to_owned(), "sections".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "parse".to_owned()),
+                ("pageid".to_owned(), "123".to_owned())
+            ]]
         );
     }
 
     #[test]
-    fn get_categories() {
+    fn compare_revisions() {
         let wikipedia = Wikipedia::<MockClient>::default();
-        wikipedia.client.response.lock().unwrap().push("{\"continue\": {\"lol\":\"1\"},\"query\":{\"pages\":{\"a\":{\"categories\":[{\"title\": \"Hello\"}]}}}}".to_owned());
         wikipedia.client.response.lock().unwrap().push(
-            "{\"query\":{\"pages\":{\"a\":{\"categories\":[{\"title\": \"Category: World\"}]}}}}"
+            "{\"compare\":{\"fromid\":1,\"fromrevid\":10,\"toid\":2,\"torevid\":20,\"*\":\"<tr><td>diff</td></tr>\"}}".to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"error\":{\"code\":\"nosuchrevid\",\"info\":\"There is no revision with ID 999.\"}}"
                 .to_owned(),
         );
-        let page = wikipedia.page_from_title("World".to_owned());
+        // revisions of different pages can be compared
         assert_eq!(
-            page.get_categories().unwrap().collect::<Vec<_>>(),
+            wikipedia.compare_revisions(10, 20).unwrap(),
+            "<tr><td>diff</td></tr>".to_owned()
+        );
+        assert!(matches!(
+            wikipedia.compare_revisions(10, 999),
+            Err(super::Error::BadRevisionId(999))
+        ));
+        for (from, to, bad) in [(12, 123, 123), (123, 12, 123)] {
+            wikipedia.client.response.lock().unwrap().push(
+                "{\"error\":{\"code\":\"nosuchrevid\",\"info\":\"There is no revision with ID 123.\"}}"
+                    .to_owned(),
+            );
+            match wikipedia.compare_revisions(from, to) {
+                Err(super::Error::BadRevisionId(x)) => assert_eq!(x, bad),
+                x => panic!("{:?}", x),
+            }
+        }
+        assert_eq!(
+            wikipedia.client.arguments.lock().unwrap()[0],
             vec![
-                iter::Category {
-                    title: "Hello".to_owned(),
-                },
-                iter::Category {
-                    title: "World".to_owned(),
-                }
+                ("fromrev".to_owned(), "10".to_owned()),
+                ("torev".to_owned(), "20".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "compare".to_owned()),
             ]
         );
+    }
+
+    #[test]
+    fn page_diff_to_previous() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"query\":{\"pages\":{\"1\":{\"pageid\":1,\"title\":\"Hello\",\"revisions\":[{\"revid\":20,\"parentid\":10}]}}}}".to_owned(),
+        );
+        wikipedia
+            .client
+            .response
+            .lock()
+            .unwrap()
+            .push("{\"compare\":{\"fromrevid\":10,\"torevid\":20,\"*\":\"<tr></tr>\"}}".to_owned());
+        let page = wikipedia.page_from_pageid("1".to_owned());
+        assert_eq!(page.diff_to_previous(20).unwrap(), "<tr></tr>".to_owned());
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
+            wikipedia.client.arguments.lock().unwrap()[1],
             vec![
-                "https://en.wikipedia.org/w/api.php".to_owned(),
-                "https://en.wikipedia.org/w/api.php".to_owned(),
+                ("fromrev".to_owned(), "10".to_owned()),
+                ("torev".to_owned(), "20".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "compare".to_owned()),
             ]
         );
+    }
+
+    #[test]
+    fn section_tree() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"parse\":{\"sections\":[{\"toclevel\":1,\"level\":\"2\",\"line\":\"History\",\"number\":\"1\",\"index\":\"1\",\"byteoffset\":100,\"anchor\":\"History\"},{\"toclevel\":2,\"level\":\"3\",\"line\":\"Early\",\"number\":\"1.1\",\"index\":\"2\",\"byteoffset\":200,\"anchor\":\"Early\"},{\"toclevel\":1,\"level\":\"2\",\"line\":\"Notes\",\"number\":\"2\",\"index\":\"T-1\",\"byteoffset\":null,\"anchor\":\"Notes\"}]}}".to_owned(),
+        );
+        let page = wikipedia.page_from_pageid("123".to_owned());
         assert_eq!(
-            *wikipedia.client.arguments.lock().unwrap(),
+            page.get_section_tree().unwrap(),
             vec![
-                vec![
-                    ("prop".to_owned(), "categories".to_owned()),
-                    ("cllimit".to_owned(), "max".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    ("titles".to_owned(), "World".to_owned()),
-                    ("continue".to_owned(), "".to_owned()),
-                ],
-                vec![
-                    ("prop".to_owned(), "categories".to_owned()),
-                    ("cllimit".to_owned(), "max".to_owned()),
-                    ("format".to_owned(), "json".to_owned()),
-                    ("action".to_owned(), "query".to_owned()),
-                    ("titles".to_owned(), "World".to_owned()),
-                    ("lol".to_owned(), "1".to_owned()),
-                ]
+                super::Section {
+                    toclevel: 1,
+                    level: 2,
+                    index: "1".to_owned(),
+                    number: "1".to_owned(),
+                    line: "History".to_owned(),
+                    anchor: "History".to_owned(),
+                    byteoffset: Some(100),
+                    children: vec![super::Section {
+                        toclevel: 2,
+                        level: 3,
+                        index: "2".to_owned(),
+                        number: "1.1".to_owned(),
+                        line: "Early".to_owned(),
+                        anchor: "Early".to_owned(),
+                        byteoffset: Some(200),
+                        children: vec![],
+                    }],
+                },
+                super::Section {
+                    toclevel: 1,
+                    level: 2,
+                    index: "T-1".to_owned(),
+                    number: "2".to_owned(),
+                    line: "Notes".to_owned(),
+                    anchor: "Notes".to_owned(),
+                    byteoffset: None,
+                    children: vec![],
+                },
             ]
         );
     }
 
     #[test]
-    fn sections() {
+    fn section_html() {
         let wikipedia = Wikipedia::<MockClient>::default();
         wikipedia.client.response.lock().unwrap().push(
-            "{\"parse\":{\"sections\":[{\"line\":\"hello\"}, {\"line\":\"world\"}]}}".to_owned(),
+            "{\"parse\":{\"title\":\"Hello\",\"pageid\":123,\"text\":{\"*\":\"<h2>Notes</h2>\"}}}"
+                .to_owned(),
+        );
+        wikipedia.client.response.lock().unwrap().push(
+            "{\"error\":{\"code\":\"nosuchsection\",\"info\":\"There is no section T-9 in Hello.\"}}".to_owned(),
         );
         let page = wikipedia.page_from_pageid("123".to_owned());
         assert_eq!(
-            page.get_sections().unwrap(),
-            vec!["hello".to_owned(), "world".to_owned()]
+            page.get_section_html("T-2").unwrap(),
+            "<h2>Notes</h2>".to_owned()
         );
+        match page.get_section_html("T-9") {
+            Err(super::Error::SectionNotFound(index)) => assert_eq!(index, "T-9"),
+            _ => panic!("expected a section not found error"),
+        }
         assert_eq!(
-            *wikipedia.client.url.lock().unwrap(),
-            vec!["https://en.wikipedia.org/w/api.php".to_owned()]
+            wikipedia.client.arguments.lock().unwrap()[0],
+            vec![
+                ("prop".to_owned(), "text".to_owned()),
+                ("section".to_owned(), "T-2".to_owned()),
+                ("format".to_owned(), "json".to_owned()),
+                ("action".to_owned(), "parse".to_owned()),
+                ("pageid".to_owned(), "123".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn namespaces() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"query\":{\"namespaces\":{\"-1\":{\"id\":-1,\"case\":\"first-letter\",\"canonical\":\"Special\",\"*\":\"Spezial\"},\"0\":{\"id\":0,\"case\":\"first-letter\",\"content\":\"\",\"*\":\"\"},\"10\":{\"id\":10,\"canonical\":\"Template\",\"*\":\"Vorlage\"},\"2\":{\"id\":2,\"canonical\":\"User\",\"*\":\"Benutzer\"}}}}".to_owned());
+        let namespaces = wikipedia.get_namespaces().unwrap();
+        assert_eq!(
+            namespaces
+                .iter()
+                .map(|x| (x.id, &*x.name))
+                .collect::<Vec<_>>(),
+            vec![(-1, "Spezial"), (0, ""), (2, "Benutzer"), (10, "Vorlage")]
+        );
+        assert_eq!(
+            namespaces[0],
+            super::Namespace {
+                id: -1,
+                name: "Spezial".to_owned(),
+                canonical: Some("Special".to_owned()),
+                content: false,
+            }
+        );
+        assert!(namespaces[1].content);
+        assert_eq!(namespaces[1].canonical, None);
+    }
+
+    #[test]
+    fn siteinfo() {
+        let wikipedia = Wikipedia::<MockClient>::default();
+        wikipedia.client.response.lock().unwrap().push("{\"batchcomplete\":\"\",\"query\":{\"general\":{\"mainpage\":\"Main Page\",\"base\":\"https://en.wikipedia.org/wiki/Main_Page\",\"sitename\":\"Wikipedia\",\"generator\":\"MediaWiki 1.42.0-wmf.5\",\"server\":\"//en.wikipedia.org\",\"articlepath\":\"/wiki/$1\",\"lang\":\"en\"},\"namespaces\":{\"0\":{\"id\":0,\"content\":\"\",\"*\":\"\"}},\"statistics\":{\"pages\":60000000,\"articles\":6700000,\"edits\":1200000000,\"images\":900000,\"users\":47000000,\"activeusers\":120000,\"admins\":860,\"jobs\":0}}}".to_owned());
+        let info = wikipedia.get_siteinfo().unwrap();
+        assert_eq!(info.sitename, "Wikipedia");
+        assert_eq!(info.generator, "MediaWiki 1.42.0-wmf.5");
+        assert_eq!(info.article_path, "/wiki/$1");
+        assert_eq!(info.namespaces.len(), 1);
+        assert_eq!(
+            info.statistics,
+            super::SiteStatistics {
+                pages: 60000000,
+                articles: 6700000,
+                edits: 1200000000,
+                images: 900000,
+                users: 47000000,
+                active_users: 120000,
+                admins: 860,
+            }
         );
         assert_eq!(
             *wikipedia.client.arguments.lock().unwrap(),
             vec![vec![
-                ("prop".to_owned(), "sections".to_owned()),
+                ("meta".to_owned(), "siteinfo".to_owned()),
+                (
+                    "siprop".to_owned(),
+                    "namespaces|general|statistics".to_owned()
+                ),
                 ("format".to_owned(), "json".to_owned()),
-                ("action".to_owned(), "parse".to_owned()),
-                ("pageid".to_owned(), "123".to_owned())
+                ("action".to_owned(), "query".to_owned()),
             ]]
         );
     }
@@ -1488,4 +10519,227 @@ mod test {
             ]]
         );
     }
+
+    #[cfg(feature = "rest")]
+    mod rest {
+        use super::super::rest::Granularity;
+        use super::super::{Error, Wikipedia};
+        use super::MockClient;
+
+        #[test]
+        fn pageviews() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            wikipedia.client.response.lock().unwrap().push("{\"items\":[{\"project\":\"en.wikipedia\",\"article\":\"AC/DC\",\"granularity\":\"daily\",\"timestamp\":\"2024010100\",\"access\":\"all-access\",\"agent\":\"user\",\"views\":1200},{\"timestamp\":\"2024010200\",\"views\":1100}]}".to_owned());
+            assert_eq!(
+                wikipedia
+                    .pageviews("AC/DC (band) ?", "20240101", "20240102", Granularity::Daily)
+                    .unwrap(),
+                vec![("20240101".to_owned(), 1200), ("20240102".to_owned(), 1100)]
+            );
+            assert_eq!(
+                *wikipedia.client.url.lock().unwrap(),
+                vec!["https://wikimedia.org/api/rest_v1/metrics/pageviews/per-article/en.wikipedia.org/all-access/user/AC%2FDC_(band)_%3F/daily/20240101/20240102".to_owned()]
+            );
+            assert!(wikipedia.client.arguments.lock().unwrap()[0].is_empty());
+        }
+
+        #[test]
+        fn top_articles() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            for _ in 0..2 {
+                wikipedia.client.response.lock().unwrap().push("{\"items\":[{\"project\":\"en.wikipedia\",\"access\":\"all-access\",\"year\":\"2024\",\"month\":\"01\",\"day\":\"05\",\"articles\":[{\"article\":\"Main_Page\",\"views\":5000000,\"rank\":1},{\"article\":\"Special:Search\",\"views\":900000,\"rank\":2},{\"article\":\"Albert_Einstein\",\"views\":80000,\"rank\":3}]}]}".to_owned());
+            }
+            assert_eq!(wikipedia.top_articles(2024, 1, Some(5)).unwrap().len(), 3);
+            assert_eq!(
+                wikipedia.top_articles_filtered(2024, 1, None).unwrap(),
+                vec![("Albert Einstein".to_owned(), 80000, 3)]
+            );
+            assert_eq!(
+                *wikipedia.client.url.lock().unwrap(),
+                vec![
+                    "https://wikimedia.org/api/rest_v1/metrics/pageviews/top/en.wikipedia.org/all-access/2024/01/05".to_owned(),
+                    "https://wikimedia.org/api/rest_v1/metrics/pageviews/top/en.wikipedia.org/all-access/2024/01/all-days".to_owned(),
+                ]
+            );
+        }
+
+        #[test]
+        fn top_articles_invalid_date() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            assert!(matches!(
+                wikipedia.top_articles(2024, 13, None),
+                Err(Error::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                wikipedia.top_articles(2024, 1, Some(0)),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+
+        #[test]
+        fn rest_summary() {
+            let wikipedia = Wikipedia::<MockClient> {
+                language: "de".to_owned(),
+                ..Wikipedia::default()
+            };
+            wikipedia.client.response.lock().unwrap().push("{\"type\":\"standard\",\"title\":\"Albert Einstein\",\"displaytitle\":\"<span>Albert Einstein</span>\",\"pageid\":736,\"thumbnail\":{\"source\":\"https://upload.wikimedia.org/e-320px.jpg\",\"width\":320,\"height\":400},\"originalimage\":{\"source\":\"https://upload.wikimedia.org/e.jpg\",\"width\":2400,\"height\":3000},\"description\":\"Physiker\",\"coordinates\":{\"lat\":48.4,\"lon\":9.99},\"extract\":\"Albert Einstein war ein Physiker.\",\"extract_html\":\"<p>Albert Einstein war ein Physiker.</p>\"}".to_owned());
+            let page = wikipedia.page_from_title("Albert Einstein".to_owned());
+            let summary = page.get_rest_summary().unwrap();
+            assert!(wikipedia.client.headers.lock().unwrap()[0].is_empty());
+            assert_eq!(summary.pageid, 736);
+            assert_eq!(summary.kind, "standard");
+            assert_eq!(summary.description, Some("Physiker".to_owned()));
+            assert_eq!(summary.extract, "Albert Einstein war ein Physiker.");
+            assert_eq!(summary.thumbnail.unwrap().width, 320);
+            assert_eq!(summary.coordinates, Some((48.4, 9.99)));
+            assert_eq!(
+                *wikipedia.client.url.lock().unwrap(),
+                vec![
+                    "https://de.wikipedia.org/api/rest_v1/page/summary/Albert_Einstein".to_owned()
+                ]
+            );
+        }
+
+        #[test]
+        fn rest_summary_with_headers() {
+            let wikipedia = Wikipedia::<MockClient> {
+                language: "zh".to_owned(),
+                ..Wikipedia::default()
+            };
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"title\":\"愛因斯坦\",\"extract\":\"愛因斯坦是物理學家。\"}".to_owned());
+            let page = wikipedia.page_from_title("愛因斯坦".to_owned());
+            let summary = page
+                .get_rest_summary_with_headers(&[("Accept-Language", "zh-hant")])
+                .unwrap();
+            assert_eq!(summary.extract, "愛因斯坦是物理學家。");
+            assert_eq!(
+                *wikipedia.client.headers.lock().unwrap(),
+                vec![vec![("Accept-Language".to_owned(), "zh-hant".to_owned())]]
+            );
+        }
+
+        #[test]
+        fn rest_summary_unsupported_wiki() {
+            let mut wikipedia = Wikipedia::<MockClient>::default();
+            wikipedia.set_base_url("https://wiki.example.com/api.php");
+            let page = wikipedia.page_from_title("Rust".to_owned());
+            match page.get_rest_summary() {
+                Err(Error::InvalidParameter(p)) => assert_eq!(p, "base_url"),
+                r => panic!("unexpected {:?}", r),
+            }
+            assert!(wikipedia.client.url.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn featured_feed() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            wikipedia.client.response.lock().unwrap().push("{\"tfa\":{\"type\":\"standard\",\"title\":\"Albert_Einstein\",\"normalizedtitle\":\"Albert Einstein\",\"extract\":\"Albert Einstein was a physicist.\"},\"image\":{\"title\":\"File:Aurora.jpg\",\"thumbnail\":{\"source\":\"https://upload.wikimedia.org/thumb/Aurora.jpg\",\"width\":640,\"height\":480},\"image\":{\"source\":\"https://upload.wikimedia.org/Aurora.jpg\",\"width\":4000,\"height\":3000},\"description\":{\"html\":\"<i>An aurora</i>\",\"text\":\"An aurora\",\"lang\":\"en\"}},\"news\":[{\"links\":[{\"title\":\"Mount_Etna\",\"normalizedtitle\":\"Mount Etna\"},{\"title\":\"Sicily\"}],\"story\":\"<b><a href=\\\"./Mount_Etna\\\">Mount Etna</a></b> erupts.\"}]}".to_owned());
+            let feed = wikipedia.featured_feed((2024, 2, 29)).unwrap();
+            assert_eq!(
+                feed.article,
+                Some(super::super::rest::FeaturedArticle {
+                    title: "Albert Einstein".to_owned(),
+                    extract: "Albert Einstein was a physicist.".to_owned(),
+                })
+            );
+            let picture = feed.picture.unwrap();
+            assert_eq!(picture.image_url, "https://upload.wikimedia.org/Aurora.jpg");
+            assert_eq!(picture.description, "An aurora");
+            assert_eq!(feed.news.len(), 1);
+            assert_eq!(
+                feed.news[0].links,
+                vec!["Mount Etna".to_owned(), "Sicily".to_owned()]
+            );
+            assert_eq!(
+                *wikipedia.client.url.lock().unwrap(),
+                vec!["https://en.wikipedia.org/api/rest_v1/feed/featured/2024/02/29".to_owned()]
+            );
+        }
+
+        #[test]
+        fn featured_feed_invalid_date() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            for date in [(2023, 2, 29), (2024, 4, 31), (2024, 0, 1), (2024, 1, 0)] {
+                assert!(matches!(
+                    wikipedia.featured_feed(date),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+        }
+
+        #[test]
+        fn on_this_day() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            wikipedia.client.response.lock().unwrap().push("{\"selected\":[{\"text\":\"Repeated\",\"year\":1905,\"pages\":[]}],\"births\":[{\"text\":\"Albert Einstein, physicist\",\"year\":1879,\"pages\":[{\"title\":\"Albert_Einstein\",\"normalizedtitle\":\"Albert Einstein\"}]}],\"events\":[{\"text\":\"Something happened\",\"year\":-44,\"pages\":[]}],\"holidays\":[{\"text\":\"Pi Day\",\"pages\":[{\"title\":\"Pi_Day\"}]}]}".to_owned());
+            wikipedia
+                .client
+                .response
+                .lock()
+                .unwrap()
+                .push("{\"births\":[]}".to_owned());
+            let entries = wikipedia
+                .on_this_day(3, 14, super::super::rest::OnThisDayKind::All)
+                .unwrap();
+            assert_eq!(
+                entries
+                    .iter()
+                    .map(|x| (x.year, &*x.text))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Some(-44), "Something happened"),
+                    (Some(1879), "Albert Einstein, physicist"),
+                    (None, "Pi Day"),
+                ]
+            );
+            assert_eq!(entries[1].pages, vec!["Albert Einstein".to_owned()]);
+            assert_eq!(entries[2].kind, super::super::rest::OnThisDayKind::Holidays);
+            assert!(wikipedia
+                .on_this_day(2, 29, super::super::rest::OnThisDayKind::Births)
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                *wikipedia.client.url.lock().unwrap(),
+                vec![
+                    "https://en.wikipedia.org/api/rest_v1/feed/onthisday/all/03/14".to_owned(),
+                    "https://en.wikipedia.org/api/rest_v1/feed/onthisday/births/02/29".to_owned(),
+                ]
+            );
+        }
+
+        #[test]
+        fn on_this_day_invalid_date() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            for (month, day) in [(2, 30), (13, 1), (6, 31), (1, 0)] {
+                assert!(matches!(
+                    wikipedia.on_this_day(month, day, super::super::rest::OnThisDayKind::Events),
+                    Err(Error::InvalidParameter(_))
+                ));
+            }
+            assert!(wikipedia.client.url.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn pageviews_invalid_date() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            match wikipedia.pageviews("Rust", "2024-01-01", "20240102", Granularity::Monthly) {
+                Err(Error::InvalidParameter(p)) => assert_eq!(p, "start"),
+                r => panic!("unexpected {:?}", r),
+            }
+        }
+
+        #[test]
+        fn pageviews_not_found() {
+            let wikipedia = Wikipedia::<MockClient>::default();
+            wikipedia.client.response.lock().unwrap().push("{\"type\":\"https://mediawiki.org/wiki/HyperSwitch/errors/not_found\",\"title\":\"Not found.\",\"method\":\"get\",\"detail\":\"The date(s) you used are valid, but we either do not have data for those date(s), or the project you asked for is not loaded yet.\"}".to_owned());
+            match wikipedia.pageviews("Nope", "20240101", "20240102", Granularity::Daily) {
+                Err(Error::Api { code, .. }) => assert_eq!(code, "Not found."),
+                r => panic!("unexpected {:?}", r),
+            }
+        }
+    }
 }
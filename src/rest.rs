@@ -0,0 +1,564 @@
+//! Endpoints of the Wikimedia REST API, which live on a different host than
+//! the Action API used by the rest of the crate.
+
+use std::time::Instant;
+
+use super::{http, Error, Page, Result, Wikipedia};
+
+/// Base url of the REST API serving metrics for all Wikimedia projects.
+const METRICS_URL: &str = "https://wikimedia.org/api/rest_v1/metrics";
+
+/// How page views are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Daily,
+    Monthly,
+}
+
+impl Granularity {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Granularity::Daily => "daily",
+            Granularity::Monthly => "monthly",
+        }
+    }
+}
+
+/// An image in a REST response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub source: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Summary of a page as returned by the REST API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestSummary {
+    pub title: String,
+    /// The title with html formatting, e.g. for italic titles.
+    pub display_title: String,
+    pub pageid: u64,
+    /// `standard`, `disambiguation`, `mainpage` or `no-extract`.
+    pub kind: String,
+    /// Short description, as in `Page::get_description`.
+    pub description: Option<String>,
+    /// Plain text of the first paragraph.
+    pub extract: String,
+    pub extract_html: String,
+    pub thumbnail: Option<Image>,
+    pub original_image: Option<Image>,
+    /// Latitude and longitude.
+    pub coordinates: Option<(f64, f64)>,
+}
+
+/// The featured article of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeaturedArticle {
+    pub title: String,
+    pub extract: String,
+}
+
+/// The picture of the day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeaturedPicture {
+    /// Title of the file page, including the `File:` prefix.
+    pub title: String,
+    /// Url of the full size image.
+    pub image_url: String,
+    pub thumbnail_url: Option<String>,
+    /// Plain text description.
+    pub description: String,
+}
+
+/// An "In the news" item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsItem {
+    /// The text of the item, as html.
+    pub story: String,
+    /// Titles of the pages linked from the item.
+    pub links: Vec<String>,
+}
+
+/// The featured content of a day, as returned by
+/// `Wikipedia::featured_feed`. Parts missing on a wiki or a day are
+/// `None` or empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeaturedContent {
+    pub article: Option<FeaturedArticle>,
+    pub picture: Option<FeaturedPicture>,
+    pub news: Vec<NewsItem>,
+}
+
+/// Kinds of anniversaries listed by `Wikipedia::on_this_day`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnThisDayKind {
+    Events,
+    Births,
+    Deaths,
+    Holidays,
+    /// All of the above.
+    All,
+}
+
+impl OnThisDayKind {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            OnThisDayKind::Events => "events",
+            OnThisDayKind::Births => "births",
+            OnThisDayKind::Deaths => "deaths",
+            OnThisDayKind::Holidays => "holidays",
+            OnThisDayKind::All => "all",
+        }
+    }
+}
+
+/// Something that happened on a day of the year.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnThisDayEntry {
+    /// One of `Events`, `Births`, `Deaths` or `Holidays`.
+    pub kind: OnThisDayKind,
+    /// `None` for holidays. Negative for years BC.
+    pub year: Option<i64>,
+    pub text: String,
+    /// Titles of the related pages.
+    pub pages: Vec<String>,
+}
+
+/// Titles of the pages in a `links`/`pages` array of a feed response.
+fn page_titles(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|x| x.as_array())
+        .map(|x| {
+            x.iter()
+                .filter_map(|x| {
+                    let o = x.as_object()?;
+                    o.get("normalizedtitle")
+                        .or_else(|| o.get("title"))?
+                        .as_str()
+                        .map(|x| x.replace('_', " "))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_image(value: Option<&serde_json::Value>) -> Option<Image> {
+    let o = value?.as_object()?;
+    Some(Image {
+        source: o.get("source")?.as_str()?.to_owned(),
+        width: o.get("width").and_then(|x| x.as_u64()).unwrap_or(0) as u32,
+        height: o.get("height").and_then(|x| x.as_u64()).unwrap_or(0) as u32,
+    })
+}
+
+/// Encodes a title for use as a path segment: spaces become underscores and
+/// everything but unreserved characters is percent-encoded, slashes included.
+fn encode_title(title: &str) -> String {
+    let mut encoded = String::new();
+    for b in title.trim().replace(' ', "_").bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b':'
+            | b'('
+            | b')'
+            | b'!'
+            | b'*'
+            | b','
+            | b'\'' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Checks that `month` and `day` form a valid date in some year.
+fn check_month_day(month: u8, day: u8) -> Result<()> {
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 => 29,
+        _ => return Err(Error::InvalidParameter("month".to_owned())),
+    };
+    if day == 0 || day > days {
+        return Err(Error::InvalidParameter("day".to_owned()));
+    }
+    Ok(())
+}
+
+/// Checks a date given as `YYYYMMDD` or `YYYYMMDDHH`.
+fn check_date(date: &str, name: &str) -> Result<()> {
+    if (date.len() != 8 && date.len() != 10) || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidParameter(name.to_owned()));
+    }
+    Ok(())
+}
+
+/// Turns a REST error response (`{"type": ..., "title": ..., "detail": ...}`)
+/// into `Error::Api`.
+fn check_rest_error(data: &serde_json::Value) -> Result<()> {
+    let o = match data.as_object() {
+        Some(o) => o,
+        None => return Ok(()),
+    };
+    if !o.contains_key("type") || !o.contains_key("detail") {
+        return Ok(());
+    }
+    let field = |name| o.get(name).and_then(|x| x.as_str()).unwrap_or("");
+    let detail = match o.get("detail") {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Err(Error::Api {
+        code: field("title").to_owned(),
+        info: detail,
+    })
+}
+
+impl<A: http::HttpClient> Wikipedia<A> {
+    /// Host of the wiki, e.g. `en.wikipedia.org`, used as the project in
+    /// metrics requests.
+    fn rest_project(&self) -> Result<String> {
+        let base_url = self.base_url();
+        let host = base_url
+            .split("://")
+            .nth(1)
+            .and_then(|x| x.split('/').next())
+            .filter(|x| !x.is_empty())
+            .ok_or_else(|| Error::URLError {
+                url: base_url.clone(),
+                source: None,
+            })?;
+        Ok(host.to_owned())
+    }
+
+    /// Url of the REST API of the wiki itself, derived from the Action API
+    /// url which must end in `/w/api.php` like on Wikimedia wikis.
+    fn rest_base_url(&self) -> Result<String> {
+        let base_url = self.base_url();
+        match base_url.strip_suffix("/w/api.php") {
+            Some(root) => Ok(format!("{}/api/rest_v1", root)),
+            None => Err(Error::InvalidParameter("base_url".to_owned())),
+        }
+    }
+
+    /// Fetches `url` and parses the response, which is expected to be json.
+    fn rest_query(&self, url: &str) -> Result<serde_json::Value> {
+        self.rest_query_with_headers(url, &[])
+    }
+
+    /// Like `rest_query`, sending `headers` with the request.
+    fn rest_query_with_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<serde_json::Value> {
+        if let Some(ref limiter) = self.rate_limiter {
+            limiter.acquire();
+        }
+        let start = Instant::now();
+        let response = self
+            .client
+            .get_with_headers(url, std::iter::empty(), headers);
+        self.report_request(url, false, &[], start, response.as_deref());
+        let response_str = response?;
+        let json = serde_json::from_str(&response_str).map_err(Error::JSONError)?;
+        check_rest_error(&json)?;
+        Ok(json)
+    }
+
+    /// Returns the featured article, the picture of the day and the "In the
+    /// news" items of a day, given as (`year`, `month`, `day`).
+    ///
+    /// The feed is only available on some Wikimedia wikis.
+    pub fn featured_feed(&self, date: (u16, u8, u8)) -> Result<FeaturedContent> {
+        let (year, month, day) = date;
+        check_month_day(month, day)?;
+        if month == 2 && day == 29 && !(year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)) {
+            return Err(Error::InvalidParameter("day".to_owned()));
+        }
+        let url = format!(
+            "{}/feed/featured/{}/{:02}/{:02}",
+            self.rest_base_url()?,
+            year,
+            month,
+            day
+        );
+        let q = self.rest_query(&url)?;
+        let o = q.as_object().ok_or(Error::JSONPathError("(root)"))?;
+
+        let article = o.get("tfa").and_then(|x| x.as_object()).and_then(|x| {
+            Some(FeaturedArticle {
+                title: x
+                    .get("normalizedtitle")
+                    .or_else(|| x.get("title"))?
+                    .as_str()?
+                    .replace('_', " "),
+                extract: x
+                    .get("extract")
+                    .and_then(|x| x.as_str())
+                    .unwrap_or("")
+                    .to_owned(),
+            })
+        });
+        let picture = o.get("image").and_then(|x| x.as_object()).and_then(|x| {
+            Some(FeaturedPicture {
+                title: x.get("title")?.as_str()?.replace('_', " "),
+                image_url: parse_image(x.get("image"))?.source,
+                thumbnail_url: parse_image(x.get("thumbnail")).map(|x| x.source),
+                description: x
+                    .get("description")
+                    .and_then(|x| x.as_object())
+                    .and_then(|x| x.get("text"))
+                    .and_then(|x| x.as_str())
+                    .unwrap_or("")
+                    .to_owned(),
+            })
+        });
+        let news = o
+            .get("news")
+            .and_then(|x| x.as_array())
+            .map(|x| {
+                x.iter()
+                    .filter_map(|x| {
+                        let o = x.as_object()?;
+                        Some(NewsItem {
+                            story: o.get("story")?.as_str()?.to_owned(),
+                            links: page_titles(o.get("links")),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(FeaturedContent {
+            article,
+            picture,
+            news,
+        })
+    }
+
+    /// Returns the events, births, deaths or holidays on a day of the year,
+    /// as listed by the REST feed.
+    ///
+    /// The feed is only available on some Wikimedia wikis.
+    pub fn on_this_day(
+        &self,
+        month: u8,
+        day: u8,
+        kind: OnThisDayKind,
+    ) -> Result<Vec<OnThisDayEntry>> {
+        check_month_day(month, day)?;
+        let url = format!(
+            "{}/feed/onthisday/{}/{:02}/{:02}",
+            self.rest_base_url()?,
+            kind.as_str(),
+            month,
+            day
+        );
+        let q = self.rest_query(&url)?;
+        let o = q.as_object().ok_or(Error::JSONPathError("(root)"))?;
+
+        let mut entries = Vec::new();
+        // `all` also returns a `selected` list, which repeats some events
+        for &kind in &[
+            OnThisDayKind::Events,
+            OnThisDayKind::Births,
+            OnThisDayKind::Deaths,
+            OnThisDayKind::Holidays,
+        ] {
+            let list = match o.get(kind.as_str()).and_then(|x| x.as_array()) {
+                Some(l) => l,
+                None => continue,
+            };
+            entries.extend(list.iter().filter_map(|x| {
+                let o = x.as_object()?;
+                Some(OnThisDayEntry {
+                    kind,
+                    year: o.get("year").and_then(|x| x.as_i64()),
+                    text: o.get("text")?.as_str()?.to_owned(),
+                    pages: page_titles(o.get("pages")),
+                })
+            }));
+        }
+        Ok(entries)
+    }
+
+    /// Builds a metrics url from its path segments.
+    fn metrics_url(&self, segments: &[&str]) -> String {
+        format!("{}/{}", METRICS_URL, segments.join("/"))
+    }
+
+    /// Returns the number of views of the article `title` by users (not
+    /// bots) between `start` and `end`, given as `YYYYMMDD`, as a list of
+    /// (`YYYYMMDD`, `views`).
+    ///
+    /// This only works for Wikimedia wikis.
+    pub fn pageviews(
+        &self,
+        title: &str,
+        start: &str,
+        end: &str,
+        granularity: Granularity,
+    ) -> Result<Vec<(String, u64)>> {
+        check_date(start, "start")?;
+        check_date(end, "end")?;
+        let project = self.rest_project()?;
+        let title = encode_title(title);
+        let url = self.metrics_url(&[
+            "pageviews",
+            "per-article",
+            &project,
+            "all-access",
+            "user",
+            &title,
+            granularity.as_str(),
+            start,
+            end,
+        ]);
+        let q = self.rest_query(&url)?;
+
+        Ok(q.as_object()
+            .and_then(|x| x.get("items"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("items"))?
+            .iter()
+            .filter_map(|x| {
+                let o = x.as_object()?;
+                let timestamp = o.get("timestamp")?.as_str()?;
+                Some((
+                    timestamp.get(..8).unwrap_or(timestamp).to_owned(),
+                    o.get("views")?.as_u64()?,
+                ))
+            })
+            .collect())
+    }
+
+    /// Returns the most viewed articles of a month, or of a single day if
+    /// `day` is given, as a list of (`title`, `views`, `rank`).
+    ///
+    /// This only works for Wikimedia wikis.
+    pub fn top_articles(
+        &self,
+        year: u16,
+        month: u8,
+        day: Option<u8>,
+    ) -> Result<Vec<(String, u64, u32)>> {
+        if !(1..=12).contains(&month) {
+            return Err(Error::InvalidParameter("month".to_owned()));
+        }
+        let day = match day {
+            Some(d) if (1..=31).contains(&d) => format!("{:02}", d),
+            Some(_) => return Err(Error::InvalidParameter("day".to_owned())),
+            None => "all-days".to_owned(),
+        };
+        let project = self.rest_project()?;
+        let year = format!("{}", year);
+        let month = format!("{:02}", month);
+        let url = self.metrics_url(&[
+            "pageviews",
+            "top",
+            &project,
+            "all-access",
+            &year,
+            &month,
+            &day,
+        ]);
+        let q = self.rest_query(&url)?;
+
+        Ok(q.as_object()
+            .and_then(|x| x.get("items"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.iter().next())
+            .and_then(|x| x.as_object())
+            .and_then(|x| x.get("articles"))
+            .and_then(|x| x.as_array())
+            .ok_or(Error::JSONPathError("items[].articles"))?
+            .iter()
+            .filter_map(|x| {
+                let o = x.as_object()?;
+                Some((
+                    o.get("article")?.as_str()?.replace('_', " "),
+                    o.get("views")?.as_u64()?,
+                    o.get("rank")?.as_u64()? as u32,
+                ))
+            })
+            .collect())
+    }
+
+    /// Like `top_articles`, leaving out the main page and special pages
+    /// like `Special:Search` (by their English names), keeping the
+    /// original ranks.
+    pub fn top_articles_filtered(
+        &self,
+        year: u16,
+        month: u8,
+        day: Option<u8>,
+    ) -> Result<Vec<(String, u64, u32)>> {
+        Ok(self
+            .top_articles(year, month, day)?
+            .into_iter()
+            .filter(|x| x.0 != "Main Page" && x.0 != "-" && !x.0.starts_with("Special:"))
+            .collect())
+    }
+}
+
+impl<A: http::HttpClient> Page<'_, A> {
+    /// Gets the title, description, first paragraph, thumbnail and
+    /// coordinates of the `Page` in a single request to the REST API,
+    /// following redirects.
+    ///
+    /// Returns `Error::InvalidParameter("base_url")` if the wiki doesn't
+    /// look like it has the REST API, i.e. its url doesn't end in
+    /// `/w/api.php`.
+    pub fn get_rest_summary(&self) -> Result<RestSummary> {
+        self.get_rest_summary_with_headers(&[])
+    }
+
+    /// Like `get_rest_summary`, sending `headers` with the request, like
+    /// `Accept-Language` to get the summary in a variant of the language
+    /// (e.g. `zh-hant`) on wikis that support them.
+    pub fn get_rest_summary_with_headers(&self, headers: &[(&str, &str)]) -> Result<RestSummary> {
+        let base = self.wikipedia.rest_base_url()?;
+        let title = encode_title(&self.get_title()?);
+        let url = format!("{}/page/summary/{}", base, title);
+        let q = self.wikipedia.rest_query_with_headers(&url, headers)?;
+
+        let o = q.as_object().ok_or(Error::JSONPathError("(root)"))?;
+        let string = |name| {
+            o.get(name)
+                .and_then(|x| x.as_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        let coordinates = o
+            .get("coordinates")
+            .and_then(|x| x.as_object())
+            .and_then(|x| Some((x.get("lat")?.as_f64()?, x.get("lon")?.as_f64()?)));
+        Ok(RestSummary {
+            title: o
+                .get("title")
+                .and_then(|x| x.as_str())
+                .ok_or(Error::JSONPathError("title"))?
+                .to_owned(),
+            display_title: string("displaytitle"),
+            pageid: o.get("pageid").and_then(|x| x.as_u64()).unwrap_or(0),
+            kind: string("type"),
+            description: o
+                .get("description")
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_owned()),
+            extract: string("extract"),
+            extract_html: string("extract_html"),
+            thumbnail: parse_image(o.get("thumbnail")),
+            original_image: parse_image(o.get("originalimage")),
+            coordinates,
+        })
+    }
+}
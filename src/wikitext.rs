@@ -0,0 +1,109 @@
+//! Best-effort helpers to pick data out of wikitext.
+
+use std::collections::HashMap;
+
+/// Removes `<!-- ... -->` comments, including an unterminated one at the end.
+fn strip_comments(wikitext: &str) -> String {
+    let mut out = String::new();
+    let mut rest = wikitext;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Finds the start of the first `{{Infobox ...}}` template, matching the
+/// name case insensitively.
+fn find_infobox(wikitext: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(i) = wikitext[offset..].find("{{") {
+        let start = offset + i;
+        let name = wikitext[start + 2..].trim_start();
+        if name
+            .get(..7)
+            .is_some_and(|x| x.eq_ignore_ascii_case("infobox"))
+        {
+            return Some(start);
+        }
+        offset = start + 2;
+    }
+    None
+}
+
+/// Splits the template starting at `wikitext` (which must begin with `{{`)
+/// into its name and parameters, on the `|` which are not nested in other
+/// templates or links. Returns `None` if the template isn't closed.
+fn split_template(wikitext: &str) -> Option<Vec<&str>> {
+    let bytes = wikitext.as_bytes();
+    let mut parts = Vec::new();
+    let mut braces = 0;
+    let mut brackets = 0;
+    let mut part_start = 2;
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'{', Some(b'{')) => {
+                braces += 1;
+                i += 2;
+                continue;
+            }
+            (b'}', Some(b'}')) => {
+                braces -= 1;
+                if braces == 0 {
+                    parts.push(&wikitext[part_start..i]);
+                    return Some(parts);
+                }
+                i += 2;
+                continue;
+            }
+            (b'[', Some(b'[')) => {
+                brackets += 1;
+                i += 2;
+                continue;
+            }
+            (b']', Some(b']')) if brackets > 0 => {
+                brackets -= 1;
+                i += 2;
+                continue;
+            }
+            (b'|', _) if braces == 1 && brackets == 0 => {
+                parts.push(&wikitext[part_start..i]);
+                part_start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parses the named parameters of the first infobox in `wikitext`.
+///
+/// Values are kept as raw wikitext, so they may contain links, templates
+/// and html. Comments are removed and positional or empty parameters are
+/// left out. Tables (`{| ... |}`) and `<nowiki>` inside values are not
+/// understood and may split a value.
+pub fn parse_infobox(wikitext: &str) -> Option<HashMap<String, String>> {
+    let wikitext = strip_comments(wikitext);
+    let start = find_infobox(&wikitext)?;
+    let parts = split_template(&wikitext[start..])?;
+    Some(
+        parts
+            .iter()
+            .skip(1)
+            .filter_map(|part| {
+                let (key, value) = part.split_once('=')?;
+                let (key, value) = (key.trim(), value.trim());
+                if key.is_empty() || value.is_empty() {
+                    return None;
+                }
+                Some((key.to_owned(), value.to_owned()))
+            })
+            .collect(),
+    )
+}
@@ -3,13 +3,289 @@ extern crate wikipedia;
 #[cfg(feature = "http-client")]
 mod tests {
     use std::collections::HashSet;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
     use wikipedia::http;
-    use wikipedia::Wikipedia;
+    use wikipedia::{Error, Limit, Wikipedia};
 
     fn w() -> Wikipedia<http::default::Client> {
         Wikipedia::default()
     }
 
+    #[test]
+    fn timeout() {
+        // accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let client = http::default::Client::with_timeout(Duration::from_millis(200));
+        let start = Instant::now();
+        let result = http::HttpClient::get(&client, &url, vec![("action", "query")].into_iter());
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    const OK: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}";
+
+    /// Answers one request with `response` on a local port, returning the
+    /// address and the lines of the request head.
+    fn serve_once(response: &'static [u8]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let (address, server) = serve(vec![response]);
+        (
+            address,
+            thread::spawn(move || server.join().unwrap().remove(0)),
+        )
+    }
+
+    /// Like `serve_once`, answering a request with each of `responses`.
+    fn serve(responses: Vec<&'static [u8]>) -> (String, thread::JoinHandle<Vec<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let lines = BufReader::new(&stream)
+                        .lines()
+                        .map(|x| x.unwrap())
+                        .take_while(|x| !x.is_empty())
+                        .collect();
+                    stream.write_all(response).unwrap();
+                    lines
+                })
+                .collect()
+        });
+        (address, server)
+    }
+
+    #[test]
+    fn cookies() {
+        let (address, server) = serve(vec![
+            b"HTTP/1.1 200 OK\r\nSet-Cookie: wikiSession=abc; path=/; HttpOnly\r\nSet-Cookie: wikiUserID=1; Max-Age=3600\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+            b"HTTP/1.1 200 OK\r\nSet-Cookie: wikiSession=deleted; expires=Thu, 01 Jan 1970 00:00:01 GMT; Max-Age=0\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+            OK,
+            OK,
+        ]);
+        let mut client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            http::HttpClient::get(&client, &address, Vec::new().into_iter()).unwrap();
+        }
+        // not sent along an OAuth token
+        http::HttpClient::bearer_token(&mut client, "token".to_owned());
+        http::HttpClient::get(&client, &address, Vec::new().into_iter()).unwrap();
+        let requests = server.join().unwrap();
+        let cookies = |i: usize| {
+            requests[i]
+                .iter()
+                .find(|x| x.to_lowercase().starts_with("cookie:"))
+                .cloned()
+        };
+        assert_eq!(cookies(0), None);
+        assert_eq!(cookies(1).unwrap()[8..], *"wikiSession=abc; wikiUserID=1");
+        assert_eq!(cookies(2).unwrap()[8..], *"wikiUserID=1");
+        assert_eq!(cookies(3), None);
+        assert!(requests[3]
+            .iter()
+            .any(|x| x.to_lowercase() == "authorization: bearer token"));
+    }
+
+    #[test]
+    fn revalidate_cached() {
+        let (address, server) = serve(vec![
+            b"HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nLast-Modified: Tue, 01 Oct 2024 00:00:00 GMT\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+            b"HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+        ]);
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap();
+        let client = http::CachedClient::new(client, Duration::ZERO, 10);
+        for _ in 0..2 {
+            let response = http::HttpClient::get(&client, &address, Vec::new().into_iter());
+            assert_eq!(response.unwrap(), "{}");
+        }
+        let requests = server.join().unwrap();
+        let lines: Vec<_> = requests[1].iter().map(|x| x.to_lowercase()).collect();
+        assert!(lines.contains(&"if-none-match: \"v1\"".to_owned()));
+        assert!(lines.contains(&"if-modified-since: tue, 01 oct 2024 00:00:00 gmt".to_owned()));
+        assert!(!requests[0]
+            .iter()
+            .any(|x| x.to_lowercase().starts_with("if-")));
+    }
+
+    #[test]
+    fn headers() {
+        let (address, server) = serve(vec![OK, OK]);
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .with_header("Api-User-Agent", "crawler/1.0 (ops@example.com)");
+        http::HttpClient::get_with_headers(
+            &client,
+            &address,
+            Vec::new().into_iter(),
+            &[("Accept-Language", "sr-el")],
+        )
+        .unwrap();
+        http::HttpClient::post_with_headers(
+            &client,
+            &address,
+            Vec::new().into_iter(),
+            &[("Accept-Language", "sr-el")],
+        )
+        .unwrap();
+        for lines in server.join().unwrap() {
+            let lines: Vec<_> = lines.iter().map(|x| x.to_lowercase()).collect();
+            assert!(lines.contains(&"api-user-agent: crawler/1.0 (ops@example.com)".to_owned()));
+            assert!(lines.contains(&"accept-language: sr-el".to_owned()));
+            assert!(lines.iter().any(|x| x.starts_with("user-agent: wikipedia")));
+        }
+    }
+
+    #[test]
+    fn builder_timeout_keeps_client() {
+        let (address, server) = serve_once(OK);
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .with_header("Api-User-Agent", "crawler/1.0 (ops@example.com)");
+        let mut wikipedia = Wikipedia::builder(client)
+            .base_url(&format!("{}/w/api.php", address))
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        assert!(wikipedia.search("hello").is_err());
+        let lines = server.join().unwrap();
+        let lines: Vec<_> = lines.iter().map(|x| x.to_lowercase()).collect();
+        assert!(lines.contains(&"api-user-agent: crawler/1.0 (ops@example.com)".to_owned()));
+
+        // accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        wikipedia.set_base_url(&format!("http://{}/", listener.local_addr().unwrap()));
+        let start = Instant::now();
+        assert!(matches!(wikipedia.search("hello"), Err(Error::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    #[test]
+    fn compression() {
+        let body = include_bytes!("fixtures/search.json.gz");
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend(body);
+        let (address, server) = serve(vec![response.leak(), OK]);
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap();
+        let wikipedia = Wikipedia::builder(client)
+            .base_url(&format!("{}/w/api.php", address))
+            .build()
+            .unwrap();
+        assert_eq!(
+            wikipedia.search("keyboard").unwrap(),
+            vec!["Computer keyboard", "Keyboard instrument"]
+        );
+
+        // raw responses are asked for without compression
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .compression(false)
+            .build()
+            .unwrap();
+        http::HttpClient::get(&client, &address, Vec::new().into_iter()).unwrap();
+        let requests = server.join().unwrap();
+        let encoding = |i: usize| {
+            requests[i]
+                .iter()
+                .find(|x| x.to_lowercase().starts_with("accept-encoding:"))
+                .map(|x| x.to_lowercase())
+        };
+        let accepted = encoding(0).unwrap();
+        assert!(accepted.contains("gzip") && accepted.contains("br"));
+        assert_eq!(encoding(1), None);
+    }
+
+    #[test]
+    fn http_status() {
+        let (address, server) = serve_once(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 42\r\nConnection: close\r\n\r\nPlease set a user-agent and respect policy");
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap();
+        match http::HttpClient::get(&client, &address, Vec::new().into_iter()) {
+            Err(Error::Http {
+                status,
+                body_snippet,
+                ..
+            }) => {
+                assert_eq!(status, 403);
+                assert_eq!(body_snippet, "Please set a user-agent and respect policy");
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn rate_limited() {
+        let (address, server) = serve_once(b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 30\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        let client = http::default::ClientBuilder::new()
+            .no_proxy()
+            .build()
+            .unwrap();
+        assert!(matches!(
+            http::HttpClient::get(&client, &address, Vec::new().into_iter()),
+            Err(Error::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(30)
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn proxy() {
+        let (proxy, server) = serve_once(OK);
+        let client = http::default::Client::with_proxy(&proxy).unwrap();
+        let response = http::HttpClient::get(
+            &client,
+            "http://wiki.invalid/w/api.php",
+            vec![("action", "query")].into_iter(),
+        )
+        .unwrap();
+        assert_eq!(response, "{}");
+        assert!(server.join().unwrap()[0]
+            .starts_with("GET http://wiki.invalid/w/api.php?action=query "));
+    }
+
+    #[test]
+    fn invalid_proxy() {
+        assert!(matches!(
+            http::default::Client::with_proxy("::not a url::"),
+            Err(Error::URLError { ref url, source: Some(_) }) if url == "::not a url::"
+        ));
+    }
+
+    #[test]
+    fn url_error() {
+        let mut wikipedia = w();
+        wikipedia.set_base_url("wiki.example.com/w/api.php");
+        let error = wikipedia.search("hello").unwrap_err();
+        assert_eq!(error.to_string(), "URL Error: wiki.example.com/w/api.php");
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "relative URL without a base");
+    }
+
     #[test]
     fn search() {
         let wikipedia = w();
@@ -18,6 +294,27 @@ mod tests {
         assert!(results.contains(&"\"Hello, World!\" program".to_owned()));
     }
 
+    #[test]
+    fn search_detailed() {
+        let wikipedia = w();
+        let results = wikipedia.search_detailed("hello world").unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.word_count > 0));
+    }
+
+    #[test]
+    fn prefix_search() {
+        let wikipedia = w();
+        let results = wikipedia.prefix_search("Computer key").unwrap();
+        assert!(results.contains(&"Computer keyboard".to_owned()));
+    }
+
+    #[test]
+    fn search_count() {
+        let wikipedia = w();
+        assert!(wikipedia.search_count("hello world").unwrap() > 10);
+    }
+
     #[test]
     fn geosearch() {
         let wikipedia = w();
@@ -39,6 +336,16 @@ mod tests {
         assert_eq!(wikipedia.random_count(3).unwrap().len(), 3);
     }
 
+    #[test]
+    fn page_info() {
+        let wikipedia = w();
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        let info = page.get_info().unwrap();
+        assert_eq!(info.pageid, 4138548);
+        assert_eq!(info.title, "Law of triviality".to_owned());
+        assert!(!info.is_redirect);
+    }
+
     #[test]
     fn page_content() {
         let wikipedia = w();
@@ -78,6 +385,15 @@ mod tests {
         assert!(html.contains("</div>")); // it would not be html otherwise
     }
 
+    #[test]
+    fn page_wikitext() {
+        let wikipedia = w();
+        let page = wikipedia.page_from_pageid("4138548".to_owned());
+        let wikitext = page.get_wikitext().unwrap();
+        assert!(wikitext.contains("[["));
+        assert!(wikitext.contains("{{"));
+    }
+
     #[test]
     fn page_summary() {
         let wikipedia = w();
@@ -101,7 +417,7 @@ mod tests {
     #[test]
     fn page_images() {
         let mut wikipedia = w();
-        wikipedia.images_results = "5".to_owned();
+        wikipedia.images_results = Limit::Count(5);
         let page = wikipedia.page_from_title("Argentina".to_owned());
         let images = page.get_images().unwrap();
         let mut c = 0;
@@ -119,6 +435,13 @@ mod tests {
         assert_eq!(set.len(), 11);
     }
 
+    #[test]
+    fn wikidata_id() {
+        let wikipedia = w();
+        let page = wikipedia.page_from_title("USA".to_owned());
+        assert_eq!(page.get_wikidata_id().unwrap(), Some("Q30".to_owned()));
+    }
+
     #[test]
     fn coordinates() {
         let wikipedia = w();
@@ -138,7 +461,7 @@ mod tests {
     #[test]
     fn references() {
         let mut wikipedia = w();
-        wikipedia.links_results = "3".to_owned();
+        wikipedia.extlinks_results = Limit::Count(3);
         let page = wikipedia.page_from_title("Argentina".to_owned());
         let references = page.get_references().unwrap();
         let mut c = 0;
@@ -157,7 +480,7 @@ mod tests {
     #[test]
     fn links() {
         let mut wikipedia = w();
-        wikipedia.links_results = "3".to_owned();
+        wikipedia.links_results = Limit::Count(3);
         let page = wikipedia.page_from_title("Argentina".to_owned());
         let links = page.get_links().unwrap();
         let mut c = 0;
@@ -175,7 +498,7 @@ mod tests {
     #[test]
     fn langlinks() {
         let mut wikipedia = w();
-        wikipedia.links_results = "3".to_owned();
+        wikipedia.langlinks_results = Limit::Count(3);
         let page = wikipedia.page_from_title("Law of triviality".to_owned());
         let langlinks = page.get_langlinks().unwrap().collect::<Vec<_>>();
         assert_eq!(
@@ -186,12 +509,13 @@ mod tests {
             langlinks.iter().find(|ll| ll.lang == *"fr").unwrap().title,
             Some("Loi de futilité de Parkinson".into()),
         );
+        assert!(langlinks.iter().all(|ll| ll.url.is_some()));
     }
 
     #[test]
     fn categories() {
         let mut wikipedia = w();
-        wikipedia.categories_results = "3".to_owned();
+        wikipedia.categories_results = Limit::Count(3);
         let page = wikipedia.page_from_title("Argentina".to_owned());
         let categories = page.get_links().unwrap();
         let mut c = 0;
@@ -257,4 +581,22 @@ mod tests {
         assert!(languages.contains(&("en".to_owned(), "English".to_owned())));
         assert!(languages.contains(&("es".to_owned(), "español".to_owned())));
     }
+
+    #[test]
+    fn image_info() {
+        let info = w()
+            .image_info("File:Einstein_1921_by_F_Schmutzer_-_restoration.jpg")
+            .unwrap();
+        assert_eq!(info.mime, "image/jpeg");
+        assert!(info.width > 0 && info.height > 0);
+        assert!(info.license().is_some());
+    }
+
+    #[test]
+    fn siteinfo() {
+        let info = w().get_siteinfo().unwrap();
+        assert_eq!(info.sitename, "Wikipedia");
+        assert!(info.namespaces.iter().any(|x| x.id == 14));
+        assert!(info.statistics.articles > 1_000_000);
+    }
 }